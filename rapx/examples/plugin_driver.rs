@@ -0,0 +1,124 @@
+//! A rapx driver with two plugins: one reporting the calls of foreign functions, e.g., to review
+//! the FFI layer of a crate, and one reporting the raw pointers returned into the locals of a
+//! function. Run it with `RAP_DRIVER=target/debug/examples/plugin_driver cargo rapx -ffi`.
+
+#![feature(rustc_private)]
+
+extern crate rustc_driver;
+extern crate rustc_hir;
+extern crate rustc_middle;
+
+use rapx::{
+    analysis::{
+        context::{AnalysisContext, CoreAnalysis},
+        core::alias_analysis::AliasAnalysis,
+        Analysis,
+    },
+    plugin::Plugin,
+    rap_warn, RapCallback,
+};
+use rustc_hir::def::DefKind;
+use rustc_middle::mir::{Body, Local, Place, TerminatorKind, VarDebugInfoContents, RETURN_PLACE};
+
+struct FfiCalls<'a, 'tcx> {
+    ctx: &'a AnalysisContext<'tcx>,
+}
+
+impl<'a, 'tcx> Analysis for FfiCalls<'a, 'tcx> {
+    fn name(&self) -> &'static str {
+        "Calls of foreign functions"
+    }
+
+    fn run(&mut self) {
+        let tcx = self.ctx.tcx;
+        let callgraph = self.ctx.callgraph();
+        let mut calls: Vec<_> = callgraph
+            .fn_calls
+            .iter()
+            .flat_map(|(caller, callees)| callees.iter().map(move |callee| (*caller, *callee)))
+            .filter(|(_, callee)| tcx.is_foreign_item(*callee))
+            .map(|(caller, callee)| (tcx.def_path_str(caller), tcx.def_path_str(callee)))
+            .collect();
+        calls.sort();
+        calls.dedup();
+        for (caller, callee) in calls {
+            rap_warn!("Foreign function `{}` called in `{}`", callee, caller);
+        }
+    }
+
+    fn reset(&mut self) {}
+}
+
+struct EscapingPointers<'a, 'tcx> {
+    ctx: &'a AnalysisContext<'tcx>,
+}
+
+impl<'a, 'tcx> Analysis for EscapingPointers<'a, 'tcx> {
+    fn name(&self) -> &'static str {
+        "Raw pointers escaping from their function"
+    }
+
+    fn run(&mut self) {
+        let tcx = self.ctx.tcx;
+        let alias = self.ctx.alias();
+        for local_def_id in tcx.mir_keys(()) {
+            let def_id = local_def_id.to_def_id();
+            if !matches!(tcx.def_kind(def_id), DefKind::Fn | DefKind::AssocFn) {
+                continue;
+            }
+            let body = tcx.optimized_mir(def_id);
+            if !body.local_decls[RETURN_PLACE].ty.is_raw_ptr() {
+                continue;
+            }
+            for (block, data) in body.basic_blocks.iter_enumerated() {
+                if !matches!(data.terminator().kind, TerminatorKind::Return) {
+                    continue;
+                }
+                let location = body.terminator_loc(block);
+                // the variables of the function, other than its arguments
+                let mut variables: Vec<String> = alias
+                    .points_to(def_id, location, Place::from(RETURN_PLACE))
+                    .into_iter()
+                    .filter(|pointee| pointee.local > body.arg_count)
+                    .filter_map(|pointee| variable_name(body, Local::from_usize(pointee.local)))
+                    .collect();
+                variables.dedup();
+                for variable in variables {
+                    rap_warn!(
+                        "The raw pointer returned by `{}` may point to the local `{}`",
+                        tcx.def_path_str(def_id),
+                        variable
+                    );
+                }
+            }
+        }
+    }
+
+    fn reset(&mut self) {}
+}
+
+fn variable_name(body: &Body<'_>, local: Local) -> Option<String> {
+    body.var_debug_info
+        .iter()
+        .find(|info| {
+            matches!(info.value, VarDebugInfoContents::Place(place) if place.as_local() == Some(local))
+        })
+        .map(|info| info.name.to_string())
+}
+
+fn main() {
+    let mut callback = RapCallback::default();
+    callback.register_plugin(
+        Plugin::new("ffi-calls", "-ffi", |ctx| Box::new(FfiCalls { ctx }))
+            .help("report the calls of foreign functions")
+            .requires(CoreAnalysis::CallGraph),
+    );
+    callback.register_plugin(
+        Plugin::new("escaping-ptr", "-escaping-ptr", |ctx| {
+            Box::new(EscapingPointers { ctx })
+        })
+        .help("report the raw pointers returned into the locals of a function")
+        .requires(CoreAnalysis::Alias),
+    );
+    rapx::driver::run(callback);
+}
@@ -0,0 +1,110 @@
+//! The results of the core analyses shared by the analyses of a run.
+//!
+//! Each core analysis runs at the first request and its result is kept for the rest of the run, so
+//! that, e.g., `-F -M -V` computes the owned heaps and the aliases once rather than once per
+//! detector. The results must not depend on the client: the options tuning a core analysis, e.g.,
+//! [`crate::utils::precision`], are fixed for the whole run before the context is created.
+
+use crate::{
+    analysis::{
+        core::{
+            alias_analysis::{andersen::AndersenAnalyzer, default::AliasAnalyzer},
+            callgraph::{default::CallGraphAnalyzer, CallGraph, CallGraphAnalysis},
+            dataflow::default::DataFlowAnalyzer,
+            ownedheap_analysis::{default::OwnedHeapAnalyzer, OHAResultMap, OwnedHeapAnalysis},
+        },
+        Analysis,
+    },
+    utils::profile,
+};
+use rustc_middle::ty::TyCtxt;
+use std::cell::OnceCell;
+
+/// The core analyses kept by the context, which a plugin may depend on.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum CoreAnalysis {
+    Alias,
+    Andersen,
+    CallGraph,
+    DataFlow,
+    OwnedHeap,
+}
+
+pub struct AnalysisContext<'tcx> {
+    pub tcx: TyCtxt<'tcx>,
+    alias: OnceCell<AliasAnalyzer<'tcx>>,
+    andersen: OnceCell<AndersenAnalyzer<'tcx>>,
+    callgraph: OnceCell<CallGraph>,
+    dataflow: OnceCell<DataFlowAnalyzer<'tcx>>,
+    ownedheap: OnceCell<OHAResultMap>,
+}
+
+impl<'tcx> AnalysisContext<'tcx> {
+    pub fn new(tcx: TyCtxt<'tcx>) -> Self {
+        Self {
+            tcx,
+            alias: OnceCell::new(),
+            andersen: OnceCell::new(),
+            callgraph: OnceCell::new(),
+            dataflow: OnceCell::new(),
+            ownedheap: OnceCell::new(),
+        }
+    }
+
+    /// Run a core analysis ahead of its first request.
+    pub fn prepare(&self, analysis: CoreAnalysis) {
+        match analysis {
+            CoreAnalysis::Alias => _ = self.alias(),
+            CoreAnalysis::Andersen => _ = self.andersen(),
+            CoreAnalysis::CallGraph => _ = self.callgraph(),
+            CoreAnalysis::DataFlow => _ = self.dataflow(),
+            CoreAnalysis::OwnedHeap => _ = self.ownedheap(),
+        }
+    }
+
+    /// Return the alias analysis (MoP) of the functions in the scope and their callees.
+    pub fn alias(&self) -> &AliasAnalyzer<'tcx> {
+        self.alias.get_or_init(|| {
+            let mut analyzer = AliasAnalyzer::new(self.tcx);
+            profile::analysis("alias", || analyzer.run());
+            analyzer
+        })
+    }
+
+    /// Return the points-to analysis (Andersen) of the functions in the scope.
+    pub fn andersen(&self) -> &AndersenAnalyzer<'tcx> {
+        self.andersen.get_or_init(|| {
+            let mut analyzer = AndersenAnalyzer::new(self.tcx);
+            profile::analysis("andersen", || analyzer.run());
+            analyzer
+        })
+    }
+
+    /// Return the call graph of the whole local crate.
+    pub fn callgraph(&self) -> &CallGraph {
+        self.callgraph.get_or_init(|| {
+            let mut analyzer = CallGraphAnalyzer::new(self.tcx);
+            profile::analysis("callgraph", || analyzer.run());
+            analyzer.get_callgraph()
+        })
+    }
+
+    /// Return the dataflow graphs of the functions in the scope and their callees. All graphs are
+    /// built, including the ones of the functions whose arg2ret summaries are cached.
+    pub fn dataflow(&self) -> &DataFlowAnalyzer<'tcx> {
+        self.dataflow.get_or_init(|| {
+            let mut analyzer = DataFlowAnalyzer::new(self.tcx, false);
+            profile::analysis("dataflow", || analyzer.build_graphs());
+            analyzer
+        })
+    }
+
+    /// Return the owned heaps of the types.
+    pub fn ownedheap(&self) -> &OHAResultMap {
+        self.ownedheap.get_or_init(|| {
+            let mut analyzer = OwnedHeapAnalyzer::new(self.tcx);
+            profile::analysis("ownedheap", || analyzer.run());
+            analyzer.get_all_items()
+        })
+    }
+}
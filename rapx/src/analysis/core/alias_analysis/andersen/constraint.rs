@@ -0,0 +1,374 @@
+use super::{
+    graph::{Base, ConstraintGraph, NodeId},
+    AndersenAnalyzer,
+};
+use crate::analysis::core::callgraph::indirect::{indirect_callees, is_fn_trait_method};
+use rustc_hir::def_id::DefId;
+use rustc_middle::{
+    mir::{
+        AggregateKind, BinOp, Body, Location, Operand, Place, ProjectionElem, Rvalue,
+        StatementKind, TerminatorKind,
+    },
+    ty::{self, Instance, InstanceKind, TypingEnv},
+};
+
+/// The std functions returning a new allocation, without the crate of their paths.
+const ALLOCATORS: &[&str] = &[
+    "alloc::alloc",
+    "alloc::alloc_zeroed",
+    "alloc::realloc",
+    "alloc::exchange_malloc",
+    "boxed::Box::<T>::new",
+    "boxed::Box::<T>::new_uninit",
+    "rc::Rc::<T>::new",
+    "sync::Arc::<T>::new",
+    "string::String::new",
+    "string::String::with_capacity",
+    "vec::Vec::<T>::new",
+    "vec::Vec::<T>::with_capacity",
+];
+
+/// The allocators of the C library called through FFI.
+const FOREIGN_ALLOCATORS: &[&str] = &["malloc", "calloc", "realloc"];
+
+/// A place, either a node or a field of the targets of a pointer.
+#[derive(Debug, Clone)]
+enum PlaceNode {
+    Direct(NodeId),
+    Deref(NodeId, Vec<usize>),
+}
+
+/// The value assigned to a place.
+#[derive(Debug, Clone)]
+enum Value {
+    /// The value of a place.
+    Place(PlaceNode),
+    /// A pointer to a node.
+    Addr(NodeId),
+}
+
+/// A call whose callees are bound once the functions reaching its fn pointer are known.
+#[derive(Debug, Clone)]
+pub struct CallSite<'tcx> {
+    caller: DefId,
+    args: Vec<Operand<'tcx>>,
+    destination: Place<'tcx>,
+    // the arguments are passed in a tuple to a method of the `Fn*` traits.
+    fn_trait: bool,
+}
+
+impl<'tcx> AndersenAnalyzer<'tcx> {
+    /// Generate the constraints of a function.
+    pub(super) fn build_fn(
+        &self,
+        graph: &mut ConstraintGraph,
+        calls: &mut Vec<CallSite<'tcx>>,
+        def_id: DefId,
+        body: &'tcx Body<'tcx>,
+    ) {
+        // the arguments point to the memory of the callers out of the crate
+        for arg in 1..=body.arg_count {
+            let local = graph.node(Base::Local(def_id, arg));
+            let param = graph.node(Base::Param(def_id, arg));
+            graph.add_addr(local, param);
+        }
+        for (block, data) in body.basic_blocks.iter_enumerated() {
+            for stmt in data.statements.iter() {
+                if let StatementKind::Assign(ref assign) = stmt.kind {
+                    self.build_assign(graph, def_id, assign.0, &assign.1);
+                }
+            }
+            let location = body.terminator_loc(block);
+            if let TerminatorKind::Call {
+                ref func,
+                ref args,
+                destination,
+                ..
+            } = data.terminator().kind
+            {
+                let call = CallSite {
+                    caller: def_id,
+                    args: args.iter().map(|arg| arg.node.clone()).collect(),
+                    destination,
+                    fn_trait: false,
+                };
+                self.build_call(graph, calls, call, func, location);
+            }
+        }
+    }
+
+    fn build_assign(
+        &self,
+        graph: &mut ConstraintGraph,
+        def_id: DefId,
+        place: Place<'tcx>,
+        rvalue: &Rvalue<'tcx>,
+    ) {
+        match rvalue {
+            Rvalue::Use(operand)
+            | Rvalue::Cast(_, operand, _)
+            | Rvalue::Repeat(operand, _)
+            | Rvalue::ShallowInitBox(operand, _) => {
+                if let Some(value) = self.operand(graph, def_id, operand) {
+                    let dst = self.place(graph, def_id, place);
+                    Self::assign(graph, dst, value);
+                }
+            }
+            // the pointer arithmetic keeps the object pointed to
+            Rvalue::BinaryOp(BinOp::Offset, operands) => {
+                if let Some(value) = self.operand(graph, def_id, &operands.0) {
+                    let dst = self.place(graph, def_id, place);
+                    Self::assign(graph, dst, value);
+                }
+            }
+            Rvalue::CopyForDeref(src) => {
+                let value = Value::Place(self.place(graph, def_id, *src));
+                let dst = self.place(graph, def_id, place);
+                Self::assign(graph, dst, value);
+            }
+            Rvalue::Ref(_, _, src) | Rvalue::RawPtr(_, src) => {
+                let dst = self.place(graph, def_id, place);
+                let dst = Self::store(graph, dst);
+                match self.place(graph, def_id, *src) {
+                    PlaceNode::Direct(target) => graph.add_addr(dst, target),
+                    PlaceNode::Deref(ptr, fields) if fields.is_empty() => graph.add_copy(ptr, dst),
+                    PlaceNode::Deref(ptr, fields) => graph.add_field_addr(ptr, fields, dst),
+                }
+            }
+            Rvalue::Aggregate(kind, operands) => {
+                let dst = self.place(graph, def_id, place);
+                let dst = Self::store(graph, dst);
+                for (idx, operand) in operands.iter_enumerated() {
+                    let Some(value) = self.operand(graph, def_id, operand) else {
+                        continue;
+                    };
+                    let field = match **kind {
+                        // the elements of an array are merged into the array
+                        AggregateKind::Array(_) => dst,
+                        AggregateKind::Adt(_, _, _, _, Some(active_field)) => {
+                            graph.field(dst, active_field.as_usize())
+                        }
+                        // the metadata of a wide pointer points to nothing
+                        AggregateKind::RawPtr(..) if idx.as_usize() > 0 => continue,
+                        AggregateKind::RawPtr(..) => dst,
+                        _ => graph.field(dst, idx.as_usize()),
+                    };
+                    Self::assign(graph, PlaceNode::Direct(field), value);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn build_call(
+        &self,
+        graph: &mut ConstraintGraph,
+        calls: &mut Vec<CallSite<'tcx>>,
+        mut call: CallSite<'tcx>,
+        func: &Operand<'tcx>,
+        location: Location,
+    ) {
+        let caller = call.caller;
+        let &ty::FnDef(callee, generic_args) = func.ty(self.bodies[&caller], self.tcx).kind()
+        else {
+            // a call through a fn pointer, bound on the fly
+            if let Some(Value::Place(ptr)) = self.operand(graph, caller, func) {
+                let ptr = Self::load(graph, ptr);
+                calls.push(call);
+                graph.add_call(ptr, calls.len() - 1);
+            }
+            return;
+        };
+        call.fn_trait = is_fn_trait_method(self.tcx, callee);
+        if self.is_allocator(callee) {
+            let dst = self.place(graph, caller, call.destination);
+            let dst = Self::store(graph, dst);
+            let object = graph.node(Base::Alloc(caller, location));
+            graph.add_addr(dst, object);
+            return;
+        }
+        let typing_env = TypingEnv::post_analysis(self.tcx, caller);
+        let resolved = match Instance::try_resolve(self.tcx, typing_env, callee, generic_args) {
+            Ok(Some(instance)) if !matches!(instance.def, InstanceKind::Virtual(..)) => {
+                Some(instance.def_id())
+            }
+            _ => None,
+        };
+        let callees: Vec<DefId> = match resolved {
+            Some(callee) if self.bodies.contains_key(&callee) => vec![callee],
+            // calls through trait objects and the closures of generic parameters
+            _ => indirect_callees(self.tcx, caller, location.block)
+                .iter()
+                .copied()
+                .filter(|callee| self.bodies.contains_key(callee))
+                .collect(),
+        };
+        if callees.is_empty() {
+            self.build_opaque_call(graph, &call);
+        }
+        for callee in callees {
+            self.bind(graph, &call, callee);
+        }
+    }
+
+    /// Bind the arguments and the return value of a call to the locals of a callee.
+    pub(super) fn bind(&self, graph: &mut ConstraintGraph, call: &CallSite<'tcx>, callee: DefId) {
+        let Some(body) = self.bodies.get(&callee) else {
+            return;
+        };
+        let caller = call.caller;
+        let mut args = Vec::new();
+        for (idx, arg) in call.args.iter().enumerate() {
+            let value = self.operand(graph, caller, arg);
+            // the closures take the arguments in the tuple one by one
+            if call.fn_trait && idx + 1 == call.args.len() && self.tcx.is_closure_like(callee) {
+                if let Some(Value::Place(tuple)) = value {
+                    for field in 0..body.arg_count.saturating_sub(args.len()) {
+                        args.push(Some(Value::Place(Self::field(graph, tuple.clone(), field))));
+                    }
+                }
+                continue;
+            }
+            args.push(value);
+        }
+        // a closure called through a fn pointer takes its captures first
+        let skip = usize::from(!call.fn_trait && self.tcx.is_closure_like(callee));
+        for (idx, value) in args.into_iter().enumerate() {
+            let local = idx + 1 + skip;
+            if local > body.arg_count {
+                break;
+            }
+            if let Some(value) = value {
+                let param = graph.node(Base::Local(callee, local));
+                Self::assign(graph, PlaceNode::Direct(param), value);
+            }
+        }
+        let ret = graph.node(Base::Local(callee, 0));
+        let dst = self.place(graph, caller, call.destination);
+        Self::assign(graph, dst, Value::Place(PlaceNode::Direct(ret)));
+    }
+
+    /// A callee without MIR may return the arguments and the values behind them, and store each
+    /// argument behind the pointers of the others.
+    fn build_opaque_call(&self, graph: &mut ConstraintGraph, call: &CallSite<'tcx>) {
+        let body = self.bodies[&call.caller];
+        let dst = self.place(graph, call.caller, call.destination);
+        let dst = Self::store(graph, dst);
+        let mut args = Vec::new();
+        for arg in call.args.iter() {
+            if let Some(value) = self.operand(graph, call.caller, arg) {
+                let node = graph.temp();
+                Self::assign(graph, PlaceNode::Direct(node), value);
+                args.push((node, arg.ty(body, self.tcx).is_any_ptr()));
+            }
+        }
+        for (node, is_ptr) in args.iter() {
+            graph.add_copy(*node, dst);
+            if *is_ptr {
+                graph.add_load(*node, Vec::new(), dst);
+                for (other, _) in args.iter().filter(|(other, _)| other != node) {
+                    graph.add_store(*other, *node, Vec::new());
+                }
+            }
+        }
+    }
+
+    fn is_allocator(&self, def_id: DefId) -> bool {
+        if self.tcx.is_foreign_item(def_id) {
+            let name = self.tcx.item_name(def_id);
+            return FOREIGN_ALLOCATORS.contains(&name.as_str());
+        }
+        if self.tcx.lang_items().exchange_malloc_fn() == Some(def_id) {
+            return true;
+        }
+        let path = self.tcx.def_path_str(def_id);
+        let path = ["std::", "alloc::", "core::"]
+            .iter()
+            .find_map(|krate| path.strip_prefix(krate))
+            .unwrap_or(&path);
+        ALLOCATORS.contains(&path)
+    }
+
+    fn operand(
+        &self,
+        graph: &mut ConstraintGraph,
+        def_id: DefId,
+        operand: &Operand<'tcx>,
+    ) -> Option<Value> {
+        match operand {
+            Operand::Copy(place) | Operand::Move(place) => {
+                Some(Value::Place(self.place(graph, def_id, *place)))
+            }
+            Operand::Constant(constant) => match constant.const_.ty().kind() {
+                // a fn item turned into a fn pointer
+                ty::FnDef(callee, _) => Some(Value::Addr(graph.node(Base::Fn(*callee)))),
+                _ => None,
+            },
+        }
+    }
+
+    fn place(&self, graph: &mut ConstraintGraph, def_id: DefId, place: Place<'tcx>) -> PlaceNode {
+        let mut current =
+            PlaceNode::Direct(graph.node(Base::Local(def_id, place.local.as_usize())));
+        for proj in place.projection {
+            match proj {
+                ProjectionElem::Deref => {
+                    let ptr = Self::load(graph, current);
+                    current = PlaceNode::Deref(ptr, Vec::new());
+                }
+                ProjectionElem::Field(field, _) => {
+                    current = Self::field(graph, current, field.as_usize());
+                }
+                // the indices and the variants are merged
+                _ => {}
+            }
+        }
+        current
+    }
+
+    fn field(graph: &mut ConstraintGraph, place: PlaceNode, field: usize) -> PlaceNode {
+        match place {
+            PlaceNode::Direct(node) => PlaceNode::Direct(graph.field(node, field)),
+            PlaceNode::Deref(ptr, mut fields) => {
+                fields.push(field);
+                PlaceNode::Deref(ptr, fields)
+            }
+        }
+    }
+
+    /// Return a node holding the value of the place: the node itself, or a temporary loaded from
+    /// the targets of the pointer.
+    fn load(graph: &mut ConstraintGraph, place: PlaceNode) -> NodeId {
+        match place {
+            PlaceNode::Direct(node) => node,
+            PlaceNode::Deref(ptr, fields) => {
+                let temp = graph.temp();
+                graph.add_load(ptr, fields, temp);
+                temp
+            }
+        }
+    }
+
+    /// Return a node whose value is stored into the place.
+    fn store(graph: &mut ConstraintGraph, place: PlaceNode) -> NodeId {
+        match place {
+            PlaceNode::Direct(node) => node,
+            PlaceNode::Deref(ptr, fields) => {
+                let temp = graph.temp();
+                graph.add_store(temp, ptr, fields);
+                temp
+            }
+        }
+    }
+
+    fn assign(graph: &mut ConstraintGraph, dst: PlaceNode, value: Value) {
+        let dst = Self::store(graph, dst);
+        match value {
+            Value::Place(src) => {
+                let src = Self::load(graph, src);
+                graph.add_copy(src, dst);
+            }
+            Value::Addr(target) => graph.add_addr(dst, target),
+        }
+    }
+}
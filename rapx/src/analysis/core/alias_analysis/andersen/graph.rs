@@ -0,0 +1,254 @@
+use rustc_data_structures::fx::{FxHashMap, FxHashSet};
+use rustc_hir::def_id::DefId;
+use rustc_middle::mir::Location;
+use std::collections::VecDeque;
+
+/// The fields deeper than this are merged into their parent, which bounds the nodes created for
+/// recursive types and for the assignments of a value into its own fields.
+pub const FIELD_DEPTH: usize = 8;
+
+pub type NodeId = usize;
+
+/// The memory a node stands for, before the fields are projected.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum Base {
+    /// A local of a function.
+    Local(DefId, usize),
+    /// The objects allocated by the call terminating a block of a function.
+    Alloc(DefId, Location),
+    /// The object an argument of a function points to on entry, which stands for the memory of
+    /// the callers out of the crate.
+    Param(DefId, usize),
+    /// A function, which the fn pointers point to.
+    Fn(DefId),
+    /// A temporary introduced to dereference a place several times.
+    Temp(usize),
+}
+
+#[derive(Debug, Clone)]
+pub struct Node {
+    pub base: Base,
+    pub fields: Vec<usize>,
+    pub children: FxHashMap<usize, NodeId>,
+    pub pts: FxHashSet<NodeId>,
+    // the targets added to pts but not propagated yet.
+    pending: FxHashSet<NodeId>,
+    // the nodes which include the points-to set of this node, field by field.
+    copy_to: FxHashSet<NodeId>,
+    // dst ⊇ *(self).fields
+    loads: Vec<(Vec<usize>, NodeId)>,
+    // *(self).fields ⊇ src
+    stores: Vec<(NodeId, Vec<usize>)>,
+    // dst ∋ &(*self).fields
+    addrs: Vec<(Vec<usize>, NodeId)>,
+    // the calls through this fn pointer.
+    calls: Vec<usize>,
+}
+
+impl Node {
+    fn new(base: Base, fields: Vec<usize>) -> Self {
+        Node {
+            base,
+            fields,
+            children: FxHashMap::default(),
+            pts: FxHashSet::default(),
+            pending: FxHashSet::default(),
+            copy_to: FxHashSet::default(),
+            loads: Vec::new(),
+            stores: Vec::new(),
+            addrs: Vec::new(),
+            calls: Vec::new(),
+        }
+    }
+}
+
+/// The inclusion constraints among the nodes, solved by propagating the new targets of each node
+/// along its copy edges until none is left.
+#[derive(Debug, Clone, Default)]
+pub struct ConstraintGraph {
+    pub nodes: Vec<Node>,
+    bases: FxHashMap<Base, NodeId>,
+    temps: usize,
+    worklist: VecDeque<NodeId>,
+    in_worklist: FxHashSet<NodeId>,
+    // the functions reaching the fn pointers of the calls, to be bound by the caller of `solve`.
+    reached: Vec<(usize, DefId)>,
+    bound: FxHashSet<(usize, DefId)>,
+}
+
+impl ConstraintGraph {
+    pub fn node(&mut self, base: Base) -> NodeId {
+        if let Some(node) = self.bases.get(&base) {
+            return *node;
+        }
+        let node = self.nodes.len();
+        self.nodes.push(Node::new(base, Vec::new()));
+        self.bases.insert(base, node);
+        node
+    }
+
+    /// Return the node of a base if it has been created.
+    pub fn find(&self, base: Base) -> Option<NodeId> {
+        self.bases.get(&base).copied()
+    }
+
+    pub fn temp(&mut self) -> NodeId {
+        self.temps += 1;
+        self.node(Base::Temp(self.temps))
+    }
+
+    pub fn field(&mut self, node: NodeId, field: usize) -> NodeId {
+        if self.nodes[node].fields.len() >= FIELD_DEPTH {
+            return node;
+        }
+        if let Some(child) = self.nodes[node].children.get(&field) {
+            return *child;
+        }
+        let child = self.nodes.len();
+        let mut fields = self.nodes[node].fields.clone();
+        fields.push(field);
+        self.nodes.push(Node::new(self.nodes[node].base, fields));
+        self.nodes[node].children.insert(field, child);
+        // the node is copied field by field, including the new one
+        for dst in self.nodes[node].copy_to.clone() {
+            let dst_field = self.field(dst, field);
+            self.add_copy(child, dst_field);
+        }
+        child
+    }
+
+    pub fn path(&mut self, node: NodeId, fields: &[usize]) -> NodeId {
+        fields
+            .iter()
+            .fold(node, |node, field| self.field(node, *field))
+    }
+
+    /// dst ∋ target
+    pub fn add_addr(&mut self, dst: NodeId, target: NodeId) {
+        self.propagate(dst, &[target]);
+    }
+
+    /// dst ⊇ src, and so are their fields.
+    pub fn add_copy(&mut self, src: NodeId, dst: NodeId) {
+        if src == dst || !self.nodes[src].copy_to.insert(dst) {
+            return;
+        }
+        let children: Vec<_> = self.nodes[src].children.clone().into_iter().collect();
+        for (field, child) in children {
+            let dst_field = self.field(dst, field);
+            self.add_copy(child, dst_field);
+        }
+        let pts: Vec<_> = self.nodes[src].pts.iter().copied().collect();
+        self.propagate(dst, &pts);
+    }
+
+    /// dst ⊇ *(ptr).fields
+    pub fn add_load(&mut self, ptr: NodeId, fields: Vec<usize>, dst: NodeId) {
+        for target in self.targets(ptr) {
+            let src = self.path(target, &fields);
+            self.add_copy(src, dst);
+        }
+        self.nodes[ptr].loads.push((fields, dst));
+    }
+
+    /// *(ptr).fields ⊇ src
+    pub fn add_store(&mut self, src: NodeId, ptr: NodeId, fields: Vec<usize>) {
+        for target in self.targets(ptr) {
+            let dst = self.path(target, &fields);
+            self.add_copy(src, dst);
+        }
+        self.nodes[ptr].stores.push((src, fields));
+    }
+
+    /// dst ∋ &(*ptr).fields
+    pub fn add_field_addr(&mut self, ptr: NodeId, fields: Vec<usize>, dst: NodeId) {
+        for target in self.targets(ptr) {
+            let target = self.path(target, &fields);
+            self.add_addr(dst, target);
+        }
+        self.nodes[ptr].addrs.push((fields, dst));
+    }
+
+    /// Bind the call to the functions the fn pointer may point to.
+    pub fn add_call(&mut self, ptr: NodeId, call: usize) {
+        for target in self.targets(ptr) {
+            self.reach(call, target);
+        }
+        self.nodes[ptr].calls.push(call);
+    }
+
+    /// Solve the constraints. `bind` adds the constraints of a call to a function reaching its fn
+    /// pointer, which may reach further functions in turn.
+    pub fn solve(&mut self, mut bind: impl FnMut(&mut Self, usize, DefId)) {
+        loop {
+            while let Some(node) = self.worklist.pop_front() {
+                self.in_worklist.remove(&node);
+                self.process(node);
+            }
+            if self.reached.is_empty() {
+                break;
+            }
+            for (call, callee) in std::mem::take(&mut self.reached) {
+                bind(self, call, callee);
+            }
+        }
+    }
+
+    fn process(&mut self, node: NodeId) {
+        let mut delta: Vec<_> = std::mem::take(&mut self.nodes[node].pending)
+            .into_iter()
+            .collect();
+        delta.sort_unstable();
+        let loads = self.nodes[node].loads.clone();
+        let stores = self.nodes[node].stores.clone();
+        let addrs = self.nodes[node].addrs.clone();
+        let calls = self.nodes[node].calls.clone();
+        for target in delta.iter().copied() {
+            for (fields, dst) in loads.iter() {
+                let src = self.path(target, fields);
+                self.add_copy(src, *dst);
+            }
+            for (src, fields) in stores.iter() {
+                let dst = self.path(target, fields);
+                self.add_copy(*src, dst);
+            }
+            for (fields, dst) in addrs.iter() {
+                let target = self.path(target, fields);
+                self.add_addr(*dst, target);
+            }
+            for call in calls.iter() {
+                self.reach(*call, target);
+            }
+        }
+        for dst in self.nodes[node].copy_to.clone() {
+            self.propagate(dst, &delta);
+        }
+    }
+
+    fn propagate(&mut self, dst: NodeId, targets: &[NodeId]) {
+        let node = &mut self.nodes[dst];
+        for target in targets {
+            if node.pts.insert(*target) {
+                node.pending.insert(*target);
+            }
+        }
+        if !node.pending.is_empty() && self.in_worklist.insert(dst) {
+            self.worklist.push_back(dst);
+        }
+    }
+
+    fn reach(&mut self, call: usize, target: NodeId) {
+        let Base::Fn(callee) = self.nodes[target].base else {
+            return;
+        };
+        if self.nodes[target].fields.is_empty() && self.bound.insert((call, callee)) {
+            self.reached.push((call, callee));
+        }
+    }
+
+    fn targets(&self, ptr: NodeId) -> Vec<NodeId> {
+        let mut targets: Vec<_> = self.nodes[ptr].pts.iter().copied().collect();
+        targets.sort_unstable();
+        targets
+    }
+}
@@ -0,0 +1,208 @@
+//! A flow-insensitive and inclusion-based (Andersen-style) points-to analysis of the local crate,
+//! chosen by `-alias=andersen`.
+//!
+//! Each local of each function is a node, and so are the objects allocated by the calls to `Box`,
+//! `Vec` and the allocators; the fields are nodes of their own up to a depth. The assignments
+//! generate inclusion constraints among the points-to sets of the nodes, and the arguments and the
+//! return values of the calls are bound to the locals of the callees. The calls through fn
+//! pointers are bound on the fly to the functions reaching the pointers, and the ones through
+//! trait objects and generic closures to the callees found by the call graph. The callees out of
+//! the crate are not analyzed: they may return the arguments and the values behind them.
+//!
+//! Unlike MoP, the whole crate is solved at once without any path to visit, so it scales to large
+//! crates at the cost of the precision: the aliases of all paths and all calling contexts are
+//! merged, and the program points are not distinguished.
+
+pub mod constraint;
+pub mod graph;
+
+use super::{AAFact, AAPlace, AAResult, AAResultMap, AliasAnalysis};
+use crate::{analysis::Analysis, rap_debug, utils::scope};
+use constraint::CallSite;
+use graph::{Base, ConstraintGraph, NodeId};
+use rustc_data_structures::fx::FxHashMap;
+use rustc_hir::def_id::DefId;
+use rustc_middle::{
+    mir::{Body, Location, Place},
+    ty::TyCtxt,
+};
+
+pub struct AndersenAnalyzer<'tcx> {
+    pub tcx: TyCtxt<'tcx>,
+    bodies: FxHashMap<DefId, &'tcx Body<'tcx>>,
+    graph: ConstraintGraph,
+    fn_map: AAResultMap,
+}
+
+impl<'tcx> Analysis for AndersenAnalyzer<'tcx> {
+    fn name(&self) -> &'static str {
+        "Alias Analysis (Andersen)"
+    }
+
+    fn run(&mut self) {
+        rap_debug!("Start alias analysis via Andersen.");
+        for local_def_id in self.tcx.mir_keys(()) {
+            let def_id = local_def_id.to_def_id();
+            if self.tcx.hir_body_const_context(*local_def_id).is_none()
+                && self.tcx.is_mir_available(def_id)
+                && scope::needs(self.tcx, def_id)
+            {
+                self.bodies.insert(def_id, self.tcx.optimized_mir(def_id));
+            }
+        }
+        let mut graph = ConstraintGraph::default();
+        let mut calls: Vec<CallSite<'tcx>> = Vec::new();
+        let mut bodies: Vec<_> = self.bodies.iter().map(|(k, v)| (*k, *v)).collect();
+        bodies.sort_by_key(|(def_id, _)| self.tcx.def_path_hash(*def_id));
+        for (def_id, body) in bodies.iter() {
+            self.build_fn(&mut graph, &mut calls, *def_id, body);
+        }
+        graph.solve(|graph, call, callee| self.bind(graph, &calls[call], callee));
+        rap_debug!(
+            "Solved {} nodes of {} functions.",
+            graph.nodes.len(),
+            bodies.len()
+        );
+        self.graph = graph;
+        self.fn_map = bodies
+            .iter()
+            .map(|(def_id, body)| (*def_id, self.summarize(*def_id, body.arg_count)))
+            .collect();
+    }
+
+    fn reset(&mut self) {
+        self.bodies.clear();
+        self.graph = ConstraintGraph::default();
+        self.fn_map.clear();
+    }
+}
+
+impl<'tcx> AliasAnalysis for AndersenAnalyzer<'tcx> {
+    fn get_fn_alias(&self, def_id: DefId) -> Option<AAResult> {
+        self.fn_map.get(&def_id).cloned()
+    }
+
+    fn get_all_fn_alias(&self) -> AAResultMap {
+        self.fn_map.clone()
+    }
+
+    /// The program points are not distinguished: the location is ignored. A pointer also aliases
+    /// the places it points to.
+    fn may_alias(
+        &self,
+        def_id: DefId,
+        _location: Location,
+        lhs: Place<'_>,
+        rhs: Place<'_>,
+    ) -> bool {
+        let (Some(lhs), Some(rhs)) = (
+            self.resolve(def_id, &AAPlace::from_place(lhs)),
+            self.resolve(def_id, &AAPlace::from_place(rhs)),
+        ) else {
+            return false;
+        };
+        let lhs_pts = &self.graph.nodes[lhs].pts;
+        let rhs_pts = &self.graph.nodes[rhs].pts;
+        lhs == rhs
+            || lhs_pts.contains(&rhs)
+            || rhs_pts.contains(&lhs)
+            || lhs_pts.iter().any(|target| rhs_pts.contains(target))
+    }
+
+    /// Return the locals of the function the place may point to, whatever the location; the
+    /// objects the arguments point to on entry are given as the arguments themselves.
+    fn points_to(&self, def_id: DefId, _location: Location, place: Place<'_>) -> Vec<AAPlace> {
+        let Some(node) = self.resolve(def_id, &AAPlace::from_place(place)) else {
+            return Vec::new();
+        };
+        let mut pointees: Vec<AAPlace> = self.graph.nodes[node]
+            .pts
+            .iter()
+            .filter_map(|target| {
+                let target = &self.graph.nodes[*target];
+                match target.base {
+                    Base::Local(owner, local) | Base::Param(owner, local) if owner == def_id => {
+                        Some(AAPlace::new(local, target.fields.clone()))
+                    }
+                    _ => None,
+                }
+            })
+            .collect();
+        pointees.sort();
+        pointees.dedup();
+        pointees
+    }
+}
+
+impl<'tcx> AndersenAnalyzer<'tcx> {
+    pub fn new(tcx: TyCtxt<'tcx>) -> Self {
+        Self {
+            tcx,
+            bodies: FxHashMap::default(),
+            graph: ConstraintGraph::default(),
+            fn_map: FxHashMap::default(),
+        }
+    }
+
+    /// Return the node of a place, or the one of its closest parent if the field has no node.
+    fn resolve(&self, def_id: DefId, place: &AAPlace) -> Option<NodeId> {
+        let mut node = self.graph.find(Base::Local(def_id, place.local))?;
+        for field in place.fields.iter() {
+            match self.graph.nodes[node].children.get(field) {
+                Some(child) => node = *child,
+                None => break,
+            }
+        }
+        Some(node)
+    }
+
+    /// The aliases among the arguments and the return value: two of them alias if they may point
+    /// to the same object, or if one points to the object another argument points to on entry.
+    fn summarize(&self, def_id: DefId, arg_count: usize) -> AAResult {
+        let mut result = AAResult::new(arg_count);
+        let interface: Vec<NodeId> = (0..self.graph.nodes.len())
+            .filter(|node| {
+                matches!(self.graph.nodes[*node].base,
+                    Base::Local(owner, local) if owner == def_id && local <= arg_count)
+            })
+            .collect();
+        let local_of = |node: NodeId| match self.graph.nodes[node].base {
+            Base::Local(_, local) => local,
+            _ => unreachable!(),
+        };
+        for (idx, lhs) in interface.iter().enumerate() {
+            let lhs_node = &self.graph.nodes[*lhs];
+            for rhs in interface[idx + 1..].iter() {
+                let rhs_node = &self.graph.nodes[*rhs];
+                if local_of(*lhs) != local_of(*rhs)
+                    && lhs_node
+                        .pts
+                        .iter()
+                        .any(|target| rhs_node.pts.contains(target))
+                {
+                    result.add_alias(AAFact {
+                        lhs_no: local_of(*lhs),
+                        lhs_fields: lhs_node.fields.clone(),
+                        rhs_no: local_of(*rhs),
+                        rhs_fields: rhs_node.fields.clone(),
+                    });
+                }
+            }
+            for target in lhs_node.pts.iter() {
+                let target = &self.graph.nodes[*target];
+                if let Base::Param(owner, arg) = target.base {
+                    if owner == def_id && arg != local_of(*lhs) {
+                        result.add_alias(AAFact {
+                            lhs_no: local_of(*lhs),
+                            lhs_fields: lhs_node.fields.clone(),
+                            rhs_no: arg,
+                            rhs_fields: target.fields.clone(),
+                        });
+                    }
+                }
+            }
+        }
+        result.sort_alias_index();
+        result
+    }
+}
@@ -1,14 +1,20 @@
 use super::{graph::*, types::*};
 use crate::{
-    analysis::core::alias_analysis::default::{MopAAFact, MopAAResultMap},
+    analysis::core::{
+        alias_analysis::default::{MopAAFact, MopAAResultMap},
+        callgraph::indirect::{indirect_callees, is_fn_trait_method},
+    },
     def_id::*,
     rap_debug,
+    utils::precision,
 };
+use rustc_abi::FieldIdx;
 use rustc_hir::def_id::DefId;
 use rustc_middle::{
-    mir::{Operand, Place, ProjectionElem, TerminatorKind},
-    ty,
+    mir::{BasicBlock, Operand, Place, ProjectionElem, TerminatorKind},
+    ty::{self, TyCtxt},
 };
+use rustc_span::source_map::Spanned;
 use std::collections::HashSet;
 
 impl<'tcx> MopGraph<'tcx> {
@@ -18,7 +24,9 @@ impl<'tcx> MopGraph<'tcx> {
             self.constant.insert(stmt.0, stmt.1);
         }
         let cur_block = self.blocks[bb_index].clone();
-        for assign in cur_block.assignments {
+        self.record_state(bb_index, 0);
+        for assign in cur_block.assignments.iter() {
+            self.record_state(bb_index, assign.statement);
             let mut lv_aliaset_idx = self.projection(false, assign.lv);
             let rv_aliaset_idx = self.projection(true, assign.rv);
             rap_debug!("{:?} = {:?}", lv_aliaset_idx, rv_aliaset_idx);
@@ -36,6 +44,9 @@ impl<'tcx> MopGraph<'tcx> {
                 self.merge_alias(lv_aliaset_idx, rv_aliaset_idx, 0);
             }
         }
+        if let Some(last) = cur_block.assignments.last() {
+            self.record_state(bb_index, last.statement + 1);
+        }
     }
 
     /* Check the aliases introduced by the terminators (function call) of a scc block */
@@ -48,7 +59,7 @@ impl<'tcx> MopGraph<'tcx> {
         let cur_block = self.blocks[bb_index].clone();
         for call in cur_block.calls {
             if let TerminatorKind::Call {
-                func: Operand::Constant(ref constant),
+                ref func,
                 ref args,
                 ref destination,
                 target: _,
@@ -85,39 +96,40 @@ impl<'tcx> MopGraph<'tcx> {
                         }
                     }
                 }
-                if let &ty::FnDef(target_id, _) = constant.const_.ty().kind() {
-                    //if may_drop_flag > 1 || Self::should_check(target_id.clone()) == false {
-                    if may_drop_flag > 0 {
-                        if self.tcx.is_mir_available(target_id) {
-                            rap_debug!("target_id {:?}", target_id);
-                            if fn_map.contains_key(&target_id) {
-                                let assignments = fn_map.get(&target_id).unwrap();
-                                for assign in assignments.aliases().iter() {
-                                    if !assign.valuable() {
-                                        continue;
-                                    }
-                                    self.merge(assign, &merge_vec);
-                                }
-                            } else {
-                                /* Fixed-point iteration: this is not perfect */
-                                if recursion_set.contains(&target_id) {
-                                    continue;
-                                }
-                                recursion_set.insert(target_id);
-                                let mut mop_graph = MopGraph::new(self.tcx, target_id);
-                                mop_graph.solve_scc();
-                                mop_graph.check(0, fn_map, recursion_set);
-                                let ret_alias = mop_graph.ret_alias.clone();
-                                for assign in ret_alias.aliases().iter() {
-                                    if !assign.valuable() {
-                                        continue;
-                                    }
-                                    self.merge(assign, &merge_vec);
-                                }
-                                fn_map.insert(target_id, ret_alias);
-                                recursion_set.remove(&target_id);
+                if may_drop_flag == 0 || self.alias_provenance(func, args, lv) {
+                    continue;
+                }
+                if let Some((context, merge_vec)) = self.call_context(func, args, &merge_vec) {
+                    if self.alias_context(&context, &merge_vec, fn_map, recursion_set) {
+                        continue;
+                    }
+                }
+                // calls through fn pointers, trait objects and closures
+                let callees = indirect_callees(self.tcx, self.def_id, BasicBlock::from(bb_index));
+                if !callees.is_empty() {
+                    let merge_vec = match func {
+                        Operand::Constant(constant) => match constant.const_.ty().kind() {
+                            &ty::FnDef(method, _) if is_fn_trait_method(self.tcx, method) => {
+                                spread_args(self.tcx, self.def_id, args, &merge_vec, |place| {
+                                    self.projection(true, place)
+                                })
                             }
-                        } else if self.values[lv].may_drop {
+                            _ => merge_vec,
+                        },
+                        _ => merge_vec,
+                    };
+                    for callee in callees {
+                        self.alias_callee(*callee, &merge_vec, fn_map, recursion_set);
+                    }
+                    continue;
+                }
+                if let Operand::Constant(ref constant) = func {
+                    if let &ty::FnDef(target_id, _) = constant.const_.ty().kind() {
+                        //if may_drop_flag > 1 || Self::should_check(target_id.clone()) == false {
+                        if self.alias_callee(target_id, &merge_vec, fn_map, recursion_set) {
+                            continue;
+                        }
+                        if self.values[lv].may_drop {
                             if target_id == call_mut() {
                                 continue;
                             }
@@ -141,6 +153,51 @@ impl<'tcx> MopGraph<'tcx> {
         }
     }
 
+    /* Merge the aliases of a callee into the caller, which returns false if there is neither a
+     * summary nor MIR of the callee. */
+    pub fn alias_callee(
+        &mut self,
+        target_id: DefId,
+        merge_vec: &[usize],
+        fn_map: &mut MopAAResultMap,
+        recursion_set: &mut HashSet<DefId>,
+    ) -> bool {
+        // callees in dependency crates may have a summary without available MIR
+        if !fn_map.contains_key(&target_id) && !self.tcx.is_mir_available(target_id) {
+            return false;
+        }
+        rap_debug!("target_id {:?}", target_id);
+        if fn_map.contains_key(&target_id) {
+            let assignments = fn_map.get(&target_id).unwrap();
+            for assign in assignments.aliases().iter() {
+                if !assign.valuable() {
+                    continue;
+                }
+                self.merge(assign, merge_vec);
+            }
+        } else {
+            /* Fixed-point iteration: this is not perfect */
+            if recursion_set.contains(&target_id) {
+                return true;
+            }
+            recursion_set.insert(target_id);
+            let mut mop_graph = MopGraph::new(self.tcx, target_id);
+            mop_graph.solve_scc();
+            mop_graph.enter(None, self.contexts.clone());
+            mop_graph.check_within_budget(fn_map, recursion_set);
+            let ret_alias = mop_graph.ret_alias.clone();
+            for assign in ret_alias.aliases().iter() {
+                if !assign.valuable() {
+                    continue;
+                }
+                self.merge(assign, merge_vec);
+            }
+            fn_map.insert(target_id, ret_alias);
+            recursion_set.remove(&target_id);
+        }
+        true
+    }
+
     /*
      * This is the function for field sensitivity
      * If the projection is a deref, we directly return its head alias or alias[0].
@@ -200,13 +257,7 @@ impl<'tcx> MopGraph<'tcx> {
             self.alias_set
         );
 
-        let max_field_depth = match std::env::var_os("MOP") {
-            Some(val) if val == "0" => 10,
-            Some(val) if val == "1" => 20,
-            Some(val) if val == "2" => 30,
-            Some(val) if val == "3" => 50,
-            _ => 15,
-        };
+        let max_field_depth = precision::get().mop_field_depth();
 
         if depth > max_field_depth {
             return;
@@ -393,3 +444,27 @@ impl<'tcx> MopGraph<'tcx> {
         f1 == f2
     }
 }
+
+/// The closures called through the `Fn*` traits take the arguments in the tuple one by one, so the
+/// tuple is replaced by its fields; `projection` returns the value of a field in the graph of the
+/// caller. It is shared by MoP and SafeDrop.
+pub fn spread_args<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    def_id: DefId,
+    args: &[Spanned<Operand<'tcx>>],
+    merge_vec: &[usize],
+    mut projection: impl FnMut(Place<'tcx>) -> usize,
+) -> Vec<usize> {
+    let mut spread_vec = merge_vec[..merge_vec.len() - 1].to_vec();
+    if let Some(Operand::Copy(place) | Operand::Move(place)) = args.last().map(|arg| &arg.node) {
+        let body = tcx.optimized_mir(def_id);
+        if let ty::Tuple(tys) = place.ty(body, tcx).ty.kind() {
+            for (idx, ty) in tys.iter().enumerate() {
+                let field = tcx.mk_place_field(*place, FieldIdx::from_usize(idx), ty);
+                spread_vec.push(projection(field));
+            }
+            return spread_vec;
+        }
+    }
+    merge_vec.to_vec()
+}
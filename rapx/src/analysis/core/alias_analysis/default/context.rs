@@ -0,0 +1,327 @@
+//! The summaries of the callees in their calling contexts, enabled by `-context-depth=<k>`.
+//!
+//! By default, the summary of a callee is computed once and merged into all its calls, so the
+//! callers of a helper get the aliases of all its paths, and a generic helper gets the aliases of
+//! all the closures and impls it is called with. With a context depth k > 0, the callees of the
+//! local crate are analyzed again for the calls of the functions analyzed, and so on up to k calls
+//! deep. A context is what the call tells about the callee: its generic args, which resolve the
+//! calls to the closures and the trait methods inside, the constant arguments, which prune the
+//! paths switching on them, and the arguments that already alias. The summaries are keyed by the
+//! context rather than by the call site, so the calls passing the same values share them.
+
+use super::{alias::spread_args, graph::MopGraph, MopAAResult, MopAAResultMap};
+use crate::{
+    rap_debug,
+    utils::{fn_summary, precision},
+};
+use rustc_data_structures::fx::FxHashMap;
+use rustc_hir::def_id::DefId;
+use rustc_middle::{
+    mir::{Const, ConstOperand, Local, Operand, Rvalue, StatementKind},
+    ty::{
+        self, EarlyBinder, GenericArgs, GenericArgsRef, Instance, InstanceKind, TyCtxt, TypingEnv,
+    },
+};
+use rustc_span::source_map::Spanned;
+use std::{cell::RefCell, collections::HashSet, rc::Rc};
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct CallContext<'tcx> {
+    pub callee: DefId,
+    /// The generic args of the callee, with the ones of the caller substituted.
+    pub generic_args: GenericArgsRef<'tcx>,
+    /// The values of the constant arguments, from the first one.
+    pub constants: Vec<Option<usize>>,
+    /// The pairs of arguments aliasing at the call.
+    pub aliased_args: Vec<(usize, usize)>,
+    /// The depth of the contexts left for the calls of the callee.
+    pub depth: usize,
+}
+
+impl<'tcx> CallContext<'tcx> {
+    /// Return the context of a call to `func` in a function analyzed with the generic args
+    /// `caller_args`, without the arguments and the depth yet. It is None if the callee is merged
+    /// with its summary: it cannot be resolved or is out of the crate.
+    pub fn resolve(
+        tcx: TyCtxt<'tcx>,
+        caller: DefId,
+        caller_args: GenericArgsRef<'tcx>,
+        func: &Operand<'tcx>,
+    ) -> Option<Self> {
+        let Operand::Constant(constant) = func else {
+            return None;
+        };
+        let &ty::FnDef(callee, args) = constant.const_.ty().kind() else {
+            return None;
+        };
+        let args = EarlyBinder::bind(args).instantiate(tcx, caller_args);
+        let typing_env = TypingEnv::post_analysis(tcx, caller);
+        let instance = match Instance::try_resolve(tcx, typing_env, callee, args) {
+            Ok(Some(instance)) if matches!(instance.def, InstanceKind::Item(_)) => instance,
+            _ => return None,
+        };
+        let callee = instance.def_id();
+        if !callee.is_local()
+            || !tcx.is_mir_available(callee)
+            || fn_summary::get(callee).is_some()
+            || tcx.hir_body_const_context(callee.expect_local()).is_some()
+        {
+            return None;
+        }
+        Some(CallContext {
+            callee,
+            generic_args: instance.args,
+            constants: Vec::new(),
+            aliased_args: Vec::new(),
+            depth: 0,
+        })
+    }
+
+    /// Fill the arguments of the context from the values `merge_vec` passed to the callee, where the
+    /// tuple of a closure call may be spread. `constant` holds the constants of the locals, and
+    /// `aliased` tests if two values alias. It is None if the call tells nothing more about the
+    /// callee than its summary assumes.
+    pub fn with_args(
+        mut self,
+        args: &[Spanned<Operand<'tcx>>],
+        merge_vec: &[usize],
+        constant: &FxHashMap<usize, usize>,
+        mut aliased: impl FnMut(usize, usize) -> bool,
+    ) -> Option<Self> {
+        // the spread arguments are places
+        let operands: Vec<Option<&Operand<'tcx>>> = (1..merge_vec.len())
+            .map(|idx| match args.get(idx - 1) {
+                Some(arg) if merge_vec.len() == args.len() + 1 => Some(&arg.node),
+                _ => None,
+            })
+            .collect();
+        self.constants = operands
+            .iter()
+            .map(|operand| match operand {
+                Some(Operand::Constant(constant)) => constant_value(constant),
+                Some(Operand::Copy(place) | Operand::Move(place))
+                    if place.projection.is_empty() =>
+                {
+                    constant.get(&place.local.as_usize()).copied()
+                }
+                _ => None,
+            })
+            .map(|value| value.filter(|value| *value != usize::MAX))
+            .collect();
+        for lhs in 1..merge_vec.len() {
+            for rhs in lhs + 1..merge_vec.len() {
+                if !matches!(operands[lhs - 1], Some(Operand::Constant(_)))
+                    && !matches!(operands[rhs - 1], Some(Operand::Constant(_)))
+                    && aliased(merge_vec[lhs], merge_vec[rhs])
+                {
+                    self.aliased_args.push((lhs, rhs));
+                }
+            }
+        }
+        self.is_specific().then_some(self)
+    }
+
+    /// Test if the call tells more about the callee than its summary assumes.
+    fn is_specific(&self) -> bool {
+        self.generic_args.types().next().is_some()
+            || self.generic_args.consts().next().is_some()
+            || self.constants.iter().any(Option::is_some)
+            || !self.aliased_args.is_empty()
+    }
+}
+
+/// The value of a constant argument, read as the constants assigned to the locals.
+pub fn constant_value(constant: &ConstOperand<'_>) -> Option<usize> {
+    match constant.const_ {
+        Const::Val(value, _) => value
+            .try_to_scalar_int()
+            .map(|scalar| scalar.to_uint(scalar.size()) as usize),
+        _ => None,
+    }
+}
+
+/// The summaries computed in the contexts so far, shared by the graphs of a run.
+#[derive(Debug, Clone, Default)]
+pub struct ContextSummaries<'tcx>(Rc<RefCell<FxHashMap<CallContext<'tcx>, MopAAResult>>>);
+
+impl<'tcx> ContextSummaries<'tcx> {
+    /// Return the summary of the callee in the context. It is None for a recursive call, which is
+    /// skipped as in the summaries without a context.
+    pub fn summary(
+        &self,
+        tcx: TyCtxt<'tcx>,
+        context: &CallContext<'tcx>,
+        fn_map: &mut MopAAResultMap,
+        recursion_set: &mut HashSet<DefId>,
+    ) -> Option<MopAAResult> {
+        if let Some(summary) = self.0.borrow().get(context) {
+            return Some(summary.clone());
+        }
+        if !recursion_set.insert(context.callee) {
+            return None;
+        }
+        rap_debug!("Analyze {:?} in context {:?}", context.callee, context);
+        let mut mop_graph = MopGraph::new(tcx, context.callee);
+        mop_graph.solve_scc();
+        mop_graph.enter(Some(context.clone()), self.clone());
+        mop_graph.check_within_budget(fn_map, recursion_set);
+        recursion_set.remove(&context.callee);
+        self.0
+            .borrow_mut()
+            .insert(context.clone(), mop_graph.ret_alias.clone());
+        Some(mop_graph.ret_alias)
+    }
+
+    /// Return the summary of the callee in the context for a client holding the summaries of the
+    /// run, e.g., SafeDrop.
+    pub fn summary_of(
+        &self,
+        tcx: TyCtxt<'tcx>,
+        context: &CallContext<'tcx>,
+        fn_map: &MopAAResultMap,
+    ) -> Option<MopAAResult> {
+        if let Some(summary) = self.0.borrow().get(context) {
+            return Some(summary.clone());
+        }
+        self.summary(tcx, context, &mut fn_map.clone(), &mut HashSet::default())
+    }
+
+    pub fn clear(&self) {
+        self.0.borrow_mut().clear();
+    }
+}
+
+impl<'tcx> MopGraph<'tcx> {
+    /// Enter the calling context the function is analyzed in: the arguments aliasing at the call
+    /// alias from the entry, and the constant arguments are known. The summaries of the contexts
+    /// are shared with the graph.
+    pub fn enter(&mut self, context: Option<CallContext<'tcx>>, contexts: ContextSummaries<'tcx>) {
+        self.contexts = contexts;
+        self.context = context;
+        let Some(context) = self.context.clone() else {
+            return;
+        };
+        for (lhs, rhs) in context.aliased_args {
+            if lhs <= self.arg_size && rhs <= self.arg_size {
+                self.merge_alias(lhs, rhs, 0);
+            }
+        }
+        // the constant arguments never assigned, and the locals only assigned once with their copy
+        let body = self.tcx.optimized_mir(self.def_id);
+        let mut assigned: FxHashMap<Local, usize> = FxHashMap::default();
+        let mut copies = Vec::new();
+        for data in body.basic_blocks.iter() {
+            for stmt in data.statements.iter() {
+                let StatementKind::Assign(assign) = &stmt.kind else {
+                    continue;
+                };
+                *assigned.entry(assign.0.local).or_default() += 1;
+                if let Rvalue::Use(Operand::Copy(src) | Operand::Move(src)) = &assign.1 {
+                    if assign.0.projection.is_empty() && src.projection.is_empty() {
+                        copies.push((assign.0.local, src.local));
+                    }
+                }
+            }
+        }
+        for (idx, constant) in context.constants.iter().enumerate() {
+            let local = Local::from_usize(idx + 1);
+            if let (Some(constant), false) = (constant, assigned.contains_key(&local)) {
+                self.arg_constants.insert(local.as_usize(), *constant);
+            }
+        }
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (dst, src) in copies.iter() {
+                if assigned[dst] != 1 || self.arg_constants.contains_key(&dst.as_usize()) {
+                    continue;
+                }
+                if let Some(constant) = self.arg_constants.get(&src.as_usize()).copied() {
+                    self.arg_constants.insert(dst.as_usize(), constant);
+                    changed = true;
+                }
+            }
+        }
+    }
+
+    /// The generic args the function is analyzed with.
+    pub fn generic_args(&self) -> GenericArgsRef<'tcx> {
+        match &self.context {
+            Some(context) => context.generic_args,
+            None => GenericArgs::identity_for_item(self.tcx, self.def_id),
+        }
+    }
+
+    /// The depth of the contexts left for the calls of the function.
+    pub fn context_depth(&self) -> usize {
+        match &self.context {
+            Some(context) => context.depth,
+            None => precision::get().context_depth,
+        }
+    }
+
+    /// The constant value of a local given by the calling context.
+    pub fn arg_constant(&self, value: usize) -> Option<usize> {
+        if self.values[value].local != value {
+            return None;
+        }
+        self.arg_constants.get(&value).copied()
+    }
+
+    /// Return the context of a call whose destination and arguments are `merge_vec`, and the
+    /// values to merge the summary of the callee with: the arguments of a closure are spread out of
+    /// their tuple. Once the depth is exhausted, the context only resolves the callee with the
+    /// generic args of the function.
+    pub fn call_context(
+        &mut self,
+        func: &Operand<'tcx>,
+        args: &[Spanned<Operand<'tcx>>],
+        merge_vec: &[usize],
+    ) -> Option<(CallContext<'tcx>, Vec<usize>)> {
+        let depth = self.context_depth();
+        if depth == 0 && self.context.is_none() {
+            return None;
+        }
+        let mut context = CallContext::resolve(self.tcx, self.def_id, self.generic_args(), func)?;
+        let merge_vec = match self.tcx.is_closure_like(context.callee) {
+            true => spread_args(self.tcx, self.def_id, args, merge_vec, |place| {
+                self.projection(true, place)
+            }),
+            false => merge_vec.to_vec(),
+        };
+        if depth == 0 {
+            return Some((context, merge_vec));
+        }
+        context.depth = depth - 1;
+        let mut constant = self.constant.clone();
+        constant.extend(self.arg_constants.iter().map(|(k, v)| (*k, *v)));
+        let context = context.with_args(args, &merge_vec, &constant, |lhs, rhs| {
+            self.union_is_same(lhs, rhs)
+        })?;
+        Some((context, merge_vec))
+    }
+
+    /* Merge the aliases of a callee in its calling context into the caller, which returns false if
+     * the call is recursive. */
+    pub fn alias_context(
+        &mut self,
+        context: &CallContext<'tcx>,
+        merge_vec: &[usize],
+        fn_map: &mut MopAAResultMap,
+        recursion_set: &mut HashSet<DefId>,
+    ) -> bool {
+        if self.context_depth() == 0 {
+            return self.alias_callee(context.callee, merge_vec, fn_map, recursion_set);
+        }
+        let contexts = self.contexts.clone();
+        let Some(summary) = contexts.summary(self.tcx, context, fn_map, recursion_set) else {
+            return false;
+        };
+        for assign in summary.aliases().iter() {
+            if assign.valuable() {
+                self.merge(assign, merge_vec);
+            }
+        }
+        true
+    }
+}
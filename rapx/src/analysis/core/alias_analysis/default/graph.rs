@@ -1,13 +1,19 @@
 use crate::{
-    analysis::core::alias_analysis::default::{types::*, MopAAResult},
+    analysis::core::alias_analysis::default::{
+        context::{CallContext, ContextSummaries},
+        provenance::exposed_places,
+        state::AliasStates,
+        types::*,
+        MopAAResult,
+    },
     rap_debug,
-    utils::source::*,
+    utils::{budget::FnBudget, source::*},
 };
 use rustc_data_structures::fx::{FxHashMap, FxHashSet};
 use rustc_middle::{
     mir::{
-        BasicBlock, Const, Operand, Place, Rvalue, StatementKind, Terminator, TerminatorKind,
-        UnwindAction,
+        BasicBlock, BinOp, CastKind, Const, Operand, Place, Rvalue, StatementKind, Terminator,
+        TerminatorKind, UnwindAction,
     },
     ty::{TyCtxt, TypingEnv},
 };
@@ -29,6 +35,8 @@ pub struct Assignment<'tcx> {
     pub rv: Place<'tcx>,
     pub atype: AssignType,
     pub span: Span,
+    // the index of the statement in its basic block.
+    pub statement: usize,
 }
 
 impl<'tcx> Assignment<'tcx> {
@@ -37,12 +45,14 @@ impl<'tcx> Assignment<'tcx> {
         rv: Place<'tcx>,
         atype: AssignType,
         span: Span,
+        statement: usize,
     ) -> Assignment<'tcx> {
         Assignment {
             lv,
             rv,
             atype,
             span,
+            statement,
         }
     }
 }
@@ -149,6 +159,7 @@ pub struct MopGraph<'tcx> {
     pub ret_alias: MopAAResult,
     // a threhold to avoid path explosion.
     pub visit_times: usize,
+    pub budget: FnBudget,
     pub alias_set: Vec<usize>,
     pub child_scc: FxHashMap<
         usize,
@@ -160,6 +171,15 @@ pub struct MopGraph<'tcx> {
     >,
     pub disc_map: FxHashMap<usize, usize>,
     pub terms: Vec<TerminatorKind<'tcx>>,
+    // the aliases at each program point, only recorded on request.
+    pub states: Option<AliasStates>,
+    // the calling context the function is analyzed in, see `context`.
+    pub context: Option<CallContext<'tcx>>,
+    pub contexts: ContextSummaries<'tcx>,
+    // the locals holding a constant argument of the context.
+    pub arg_constants: FxHashMap<usize, usize>,
+    // the pointers whose addresses are exposed, see `provenance`.
+    pub exposed: Vec<Place<'tcx>>,
 }
 
 impl<'tcx> MopGraph<'tcx> {
@@ -192,6 +212,7 @@ impl<'tcx> MopGraph<'tcx> {
         let mut scc_indices = Vec::<usize>::new();
         let mut disc_map = FxHashMap::default();
         let mut terms = Vec::new();
+        let exposed = exposed_places(body);
 
         // handle each basicblock
         for i in 0..basicblocks.len() {
@@ -201,7 +222,7 @@ impl<'tcx> MopGraph<'tcx> {
             let mut cur_bb = BlockNode::new(i);
 
             // handle general statements
-            for stmt in &basicblocks[iter].statements {
+            for (statement, stmt) in basicblocks[iter].statements.iter().enumerate() {
                 /* Assign is a tuple defined as Assign(Box<(Place<'tcx>, Rvalue<'tcx>)>) */
                 let span = stmt.source_info.span;
                 if let StatementKind::Assign(ref assign) = stmt.kind {
@@ -216,8 +237,13 @@ impl<'tcx> MopGraph<'tcx> {
                                     let rv_local = p.local.as_usize();
                                     if values[lv_local].may_drop && values[rv_local].may_drop {
                                         let rv = *p;
-                                        let assign =
-                                            Assignment::new(lv, rv, AssignType::Copy, span);
+                                        let assign = Assignment::new(
+                                            lv,
+                                            rv,
+                                            AssignType::Copy,
+                                            span,
+                                            statement,
+                                        );
                                         cur_bb.assignments.push(assign);
                                     }
                                 }
@@ -225,8 +251,13 @@ impl<'tcx> MopGraph<'tcx> {
                                     let rv_local = p.local.as_usize();
                                     if values[lv_local].may_drop && values[rv_local].may_drop {
                                         let rv = *p;
-                                        let assign =
-                                            Assignment::new(lv, rv, AssignType::Move, span);
+                                        let assign = Assignment::new(
+                                            lv,
+                                            rv,
+                                            AssignType::Move,
+                                            span,
+                                            statement,
+                                        );
                                         cur_bb.assignments.push(assign);
                                     }
                                 }
@@ -259,7 +290,8 @@ impl<'tcx> MopGraph<'tcx> {
                             let rv_local = p.local.as_usize();
                             if values[lv_local].may_drop && values[rv_local].may_drop {
                                 let rv = *p;
-                                let assign = Assignment::new(lv, rv, AssignType::Copy, span);
+                                let assign =
+                                    Assignment::new(lv, rv, AssignType::Copy, span, statement);
                                 cur_bb.assignments.push(assign);
                             }
                         }
@@ -281,20 +313,50 @@ impl<'tcx> MopGraph<'tcx> {
                                     let rv_local = p.local.as_usize();
                                     if values[lv_local].may_drop && values[rv_local].may_drop {
                                         let rv = *p;
-                                        let assign =
-                                            Assignment::new(lv, rv, AssignType::InitBox, span);
+                                        let assign = Assignment::new(
+                                            lv,
+                                            rv,
+                                            AssignType::InitBox,
+                                            span,
+                                            statement,
+                                        );
                                         cur_bb.assignments.push(assign);
                                     }
                                 }
                                 Operand::Constant(_) => {}
                             }
                         }
+                        // the pointer keeps the allocation of its base at an unknown offset
+                        Rvalue::BinaryOp(BinOp::Offset, ref operands) => match operands.0 {
+                            Operand::Copy(ref p) | Operand::Move(ref p) => {
+                                let rv_local = p.local.as_usize();
+                                if values[lv_local].may_drop && values[rv_local].may_drop {
+                                    let rv = *p;
+                                    let assign =
+                                        Assignment::new(lv, rv, AssignType::Copy, span, statement);
+                                    cur_bb.assignments.push(assign);
+                                }
+                            }
+                            Operand::Constant(_) => {}
+                        },
+                        // the pointer may point to any allocation exposed in the function
+                        Rvalue::Cast(CastKind::PointerWithExposedProvenance, _, _) => {
+                            for rv in exposed.iter() {
+                                let rv_local = rv.local.as_usize();
+                                if values[lv_local].may_drop && values[rv_local].may_drop {
+                                    let assign =
+                                        Assignment::new(lv, *rv, AssignType::Copy, span, statement);
+                                    cur_bb.assignments.push(assign);
+                                }
+                            }
+                        }
                         Rvalue::Cast(_, ref x, _) => match x {
                             Operand::Copy(ref p) => {
                                 let rv_local = p.local.as_usize();
                                 if values[lv_local].may_drop && values[rv_local].may_drop {
                                     let rv = *p;
-                                    let assign = Assignment::new(lv, rv, AssignType::Copy, span);
+                                    let assign =
+                                        Assignment::new(lv, rv, AssignType::Copy, span, statement);
                                     cur_bb.assignments.push(assign);
                                 }
                             }
@@ -302,7 +364,8 @@ impl<'tcx> MopGraph<'tcx> {
                                 let rv_local = p.local.as_usize();
                                 if values[lv_local].may_drop && values[rv_local].may_drop {
                                     let rv = *p;
-                                    let assign = Assignment::new(lv, rv, AssignType::Move, span);
+                                    let assign =
+                                        Assignment::new(lv, rv, AssignType::Move, span, statement);
                                     cur_bb.assignments.push(assign);
                                 }
                             }
@@ -315,8 +378,13 @@ impl<'tcx> MopGraph<'tcx> {
                                         let rv_local = p.local.as_usize();
                                         if values[lv_local].may_drop && values[rv_local].may_drop {
                                             let rv = *p;
-                                            let assign =
-                                                Assignment::new(lv, rv, AssignType::Copy, span);
+                                            let assign = Assignment::new(
+                                                lv,
+                                                rv,
+                                                AssignType::Copy,
+                                                span,
+                                                statement,
+                                            );
                                             cur_bb.assignments.push(assign);
                                         }
                                     }
@@ -326,7 +394,8 @@ impl<'tcx> MopGraph<'tcx> {
                         }
                         Rvalue::Discriminant(ref p) => {
                             let rv = *p;
-                            let assign = Assignment::new(lv, rv, AssignType::Variant, span);
+                            let assign =
+                                Assignment::new(lv, rv, AssignType::Variant, span, statement);
                             cur_bb.assignments.push(assign);
                             disc_map.insert(lv_local, p.local.as_usize());
                         }
@@ -454,9 +523,15 @@ impl<'tcx> MopGraph<'tcx> {
             constant: FxHashMap::default(),
             ret_alias: MopAAResult::new(arg_size),
             visit_times: 0,
+            budget: FnBudget::start(),
             child_scc: FxHashMap::default(),
             disc_map,
             terms,
+            states: None,
+            context: None,
+            contexts: ContextSummaries::default(),
+            arg_constants: FxHashMap::default(),
+            exposed,
         }
     }
 
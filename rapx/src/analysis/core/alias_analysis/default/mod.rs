@@ -1,19 +1,35 @@
 pub mod alias;
+pub mod context;
 pub mod graph;
 pub mod mop;
+pub mod provenance;
+pub mod state;
+pub mod summary;
 pub mod types;
 
-use super::{AAFact, AAResult, AAResultMap, AliasAnalysis};
-use crate::{analysis::Analysis, def_id::*, rap_debug, rap_trace, utils::source::*};
+use super::{AAFact, AAPlace, AAResult, AAResultMap, AliasAnalysis};
+use crate::{
+    analysis::Analysis,
+    def_id::*,
+    rap_debug, rap_trace,
+    utils::{
+        budget,
+        cache::{def_id_from_key, def_id_key, fn_fingerprints, SummaryCache},
+        fn_summary, precision, profile, scope,
+        source::*,
+    },
+};
+use context::ContextSummaries;
 use graph::MopGraph;
-use rustc_data_structures::fx::FxHashMap;
+use rustc_data_structures::{fingerprint::Fingerprint, fx::FxHashMap};
 use rustc_hir::def_id::DefId;
+use rustc_middle::mir::{Location, Place};
 use rustc_middle::ty::TyCtxt;
-use std::{collections::HashSet, convert::From, fmt};
+use serde::{Deserialize, Serialize};
+use state::AliasStates;
+use std::{cell::RefCell, collections::HashSet, convert::From, fmt, rc::Rc, time::Instant};
 
-pub const VISIT_LIMIT: usize = 1000;
-
-#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+#[derive(Debug, Clone, Hash, PartialEq, Eq, Serialize, Deserialize)]
 pub struct MopAAFact {
     pub fact: AAFact,
     pub lhs_may_drop: bool,
@@ -83,7 +99,7 @@ impl From<MopAAResult> for AAResult {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MopAAResult {
     arg_size: usize,
     alias_set: HashSet<MopAAFact>,
@@ -147,6 +163,10 @@ pub type MopAAResultMap = FxHashMap<DefId, MopAAResult>;
 pub struct AliasAnalyzer<'tcx> {
     pub tcx: TyCtxt<'tcx>,
     pub fn_map: FxHashMap<DefId, MopAAResult>,
+    // the aliases at the program points of the functions queried so far.
+    states: RefCell<FxHashMap<DefId, Rc<AliasStates>>>,
+    // the summaries of the callees in their calling contexts, with `-context-depth`.
+    pub contexts: ContextSummaries<'tcx>,
 }
 
 impl<'tcx> Analysis for AliasAnalyzer<'tcx> {
@@ -156,9 +176,67 @@ impl<'tcx> Analysis for AliasAnalyzer<'tcx> {
 
     fn run(&mut self) {
         rap_debug!("Start alias analysis via MoP.");
+        self.fn_map.extend(summary::load_dep_summaries(self.tcx));
+        let mop = precision::get().mop_fingerprint();
+        let mut cache = SummaryCache::load(
+            self.tcx,
+            "alias",
+            &[&mop, fn_summary::fingerprint(), &budget::fingerprint()],
+        );
+        let fingerprints = if cache.is_enabled() {
+            fn_fingerprints(self.tcx)
+        } else {
+            FxHashMap::default()
+        };
+        // Summaries restored from the cache are in place before the analysis starts, so they are
+        // also reused by the callers that need to be analyzed again. The summaries of external
+        // functions are computed on demand and only depend on the dependency crates.
+        if let Some(summaries) =
+            cache.get::<Vec<(String, MopAAResult)>>("extern", Fingerprint::ZERO)
+        {
+            for (key, summary) in summaries {
+                if let Some(def_id) = def_id_from_key(self.tcx, &key) {
+                    self.fn_map.entry(def_id).or_insert(summary);
+                }
+            }
+        }
+        // The summaries supplied by users override the ones computed from MIR.
+        for (def_id, summary) in fn_summary::summaries() {
+            let mut result = MopAAResult::new(
+                self.tcx
+                    .fn_sig(def_id)
+                    .skip_binder()
+                    .inputs()
+                    .skip_binder()
+                    .len(),
+            );
+            for alias in &summary.aliases {
+                result.add_alias(MopAAFact {
+                    fact: alias.clone(),
+                    lhs_may_drop: true,
+                    lhs_need_drop: true,
+                    rhs_may_drop: true,
+                    rhs_need_drop: true,
+                });
+            }
+            self.fn_map.insert(def_id, result);
+        }
+        let mut cached = HashSet::new();
+        for (def_id, fingerprint) in &fingerprints {
+            if let Some(summary) = cache.get_fn::<MopAAResult>(*def_id, *fingerprint) {
+                self.fn_map.insert(*def_id, summary);
+                cached.insert(*def_id);
+            }
+        }
         let mir_keys = self.tcx.mir_keys(());
         for local_def_id in mir_keys {
-            self.query_mop(local_def_id.to_def_id());
+            let def_id = local_def_id.to_def_id();
+            if !cached.contains(&def_id)
+                && fn_summary::get(def_id).is_none()
+                && scope::needs(self.tcx, def_id)
+            {
+                self.query_mop(def_id);
+            }
         }
         // Meaning of output: 0 for ret value; 1,2,3,... for corresponding args.
         for (fn_id, fn_alias) in &mut self.fn_map {
@@ -169,10 +247,31 @@ impl<'tcx> Analysis for AliasAnalyzer<'tcx> {
             }
         }
         self.handle_conor_cases();
+        if cache.is_enabled() {
+            for (def_id, fingerprint) in &fingerprints {
+                // the truncated functions are analyzed again to report them
+                if budget::is_truncated(self.tcx, "alias", *def_id) {
+                    continue;
+                }
+                if let Some(summary) = self.fn_map.get(def_id) {
+                    cache.insert_fn(*def_id, *fingerprint, summary);
+                }
+            }
+            let summaries: Vec<_> = self
+                .fn_map
+                .iter()
+                .filter(|(def_id, _)| !def_id.is_local())
+                .map(|(def_id, summary)| (def_id_key(self.tcx, *def_id), summary))
+                .collect();
+            cache.insert("extern", Fingerprint::ZERO, &summaries);
+        }
+        cache.store();
     }
 
     fn reset(&mut self) {
-        todo!();
+        self.fn_map.clear();
+        self.states.borrow_mut().clear();
+        self.contexts.clear();
     }
 }
 
@@ -187,6 +286,22 @@ impl<'tcx> AliasAnalysis for AliasAnalyzer<'tcx> {
             .map(|(k, v)| (*k, AAResult::from(v.clone())))
             .collect()
     }
+
+    fn may_alias(&self, def_id: DefId, location: Location, lhs: Place<'_>, rhs: Place<'_>) -> bool {
+        self.alias_states(def_id).is_some_and(|states| {
+            states.may_alias(
+                location,
+                &AAPlace::from_place(lhs),
+                &AAPlace::from_place(rhs),
+            )
+        })
+    }
+
+    fn points_to(&self, def_id: DefId, location: Location, place: Place<'_>) -> Vec<AAPlace> {
+        self.alias_states(def_id)
+            .map(|states| states.points_to(location, &AAPlace::from_place(place)))
+            .unwrap_or_default()
+    }
 }
 
 impl<'tcx> AliasAnalyzer<'tcx> {
@@ -194,6 +309,8 @@ impl<'tcx> AliasAnalyzer<'tcx> {
         Self {
             tcx,
             fn_map: FxHashMap::default(),
+            states: RefCell::new(FxHashMap::default()),
+            contexts: ContextSummaries::default(),
         }
     }
 
@@ -222,19 +339,44 @@ impl<'tcx> AliasAnalyzer<'tcx> {
         }
 
         if self.tcx.is_mir_available(def_id) {
+            let start = Instant::now();
             let mut mop_graph = MopGraph::new(self.tcx, def_id);
             mop_graph.solve_scc();
+            mop_graph.enter(None, self.contexts.clone());
             let mut recursion_set = HashSet::default();
-            mop_graph.check(0, &mut self.fn_map, &mut recursion_set);
-            if mop_graph.visit_times > VISIT_LIMIT {
-                rap_trace!("Over visited: {:?}", def_id);
-            }
+            mop_graph.check_within_budget(&mut self.fn_map, &mut recursion_set);
+            profile::record_time("alias", def_id, start.elapsed());
+            profile::record_count("alias", def_id, "visits", mop_graph.visit_times);
             self.fn_map.insert(def_id, mop_graph.ret_alias);
         } else {
             rap_trace!("Mir is not available at {}", self.tcx.def_path_str(def_id));
         }
     }
 
+    /// Return the aliases at the program points of a function. The paths of the function are
+    /// visited again at the first request, with the summaries of its callees computed by the run.
+    pub fn alias_states(&self, def_id: DefId) -> Option<Rc<AliasStates>> {
+        if let Some(states) = self.states.borrow().get(&def_id) {
+            return Some(states.clone());
+        }
+        if !self.tcx.is_mir_available(def_id)
+            || def_id
+                .as_local()
+                .is_some_and(|local| self.tcx.hir_body_const_context(local).is_some())
+        {
+            return None;
+        }
+        let mut mop_graph = MopGraph::new(self.tcx, def_id);
+        mop_graph.states = Some(AliasStates::default());
+        mop_graph.solve_scc();
+        mop_graph.enter(None, self.contexts.clone());
+        let mut fn_map = self.fn_map.clone();
+        mop_graph.check_within_budget(&mut fn_map, &mut HashSet::default());
+        let states = Rc::new(mop_graph.states.unwrap_or_default());
+        self.states.borrow_mut().insert(def_id, states.clone());
+        Some(states)
+    }
+
     pub fn get_all_fn_alias_raw(&mut self) -> MopAAResultMap {
         self.fn_map.clone()
     }
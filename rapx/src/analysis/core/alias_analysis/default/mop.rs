@@ -1,4 +1,5 @@
 use super::{graph::*, *};
+use crate::utils::{budget, precision};
 use rustc_data_structures::fx::FxHashSet;
 use rustc_hir::def_id::DefId;
 use rustc_middle::{
@@ -9,10 +10,7 @@ use rustc_middle::{
     ty::TypingEnv,
 };
 
-use std::{
-    collections::{HashMap, HashSet},
-    env,
-};
+use std::collections::{HashMap, HashSet};
 
 impl<'tcx> MopGraph<'tcx> {
     pub fn split_check(
@@ -52,6 +50,53 @@ impl<'tcx> MopGraph<'tcx> {
         self.constant = backup_constant;
     }
 
+    /// Visit the paths from the entry within the budget of the function. Once it runs out, the
+    /// aliases found on the visited paths are kept and the rest of the function is analyzed
+    /// path-insensitively.
+    pub fn check_within_budget(
+        &mut self,
+        fn_map: &mut MopAAResultMap,
+        recursion_set: &mut HashSet<DefId>,
+    ) {
+        self.check(0, fn_map, recursion_set);
+        let Some(reason) = self.budget.exhausted() else {
+            return;
+        };
+        rap_debug!("Over budget ({}): {:?}", reason, self.def_id);
+        budget::truncate(self.tcx, "alias", self.def_id, reason);
+        // restart from the initial state of the function
+        let mut graph = MopGraph::new(self.tcx, self.def_id);
+        graph.solve_scc();
+        graph.enter(self.context.clone(), self.contexts.clone());
+        graph.ret_alias = self.ret_alias.clone();
+        graph.states = self.states.take();
+        graph.check_merged(fn_map, recursion_set);
+        self.ret_alias = graph.ret_alias;
+        self.states = graph.states;
+    }
+
+    /// The path-insensitive fallback: each SCC is visited once in topological order without
+    /// restoring the states between the branches, so the aliases of all paths are merged.
+    fn check_merged(&mut self, fn_map: &mut MopAAResultMap, recursion_set: &mut HashSet<DefId>) {
+        for scc in self.topo_sccs() {
+            let block = self.blocks[scc].clone();
+            self.alias_bb(scc);
+            self.alias_bbcall(scc, fn_map, recursion_set);
+            for idx in block.scc_sub_blocks {
+                self.alias_bb(idx);
+                self.alias_bbcall(idx, fn_map, recursion_set);
+            }
+            if block.next.is_empty() {
+                let results_nodes = self.values.clone();
+                self.merge_results(results_nodes);
+            }
+        }
+    }
+
+    fn topo_sccs(&self) -> Vec<usize> {
+        topo_sccs(&self.scc_indices, |block| &self.blocks[block].next)
+    }
+
     // the core function of the safedrop.
     pub fn check(
         &mut self,
@@ -60,7 +105,7 @@ impl<'tcx> MopGraph<'tcx> {
         recursion_set: &mut HashSet<DefId>,
     ) {
         self.visit_times += 1;
-        if self.visit_times > VISIT_LIMIT {
+        if self.budget.is_exhausted(self.visit_times) {
             return;
         }
         let cur_block = self.blocks[self.scc_indices[bb_index]].clone();
@@ -205,11 +250,11 @@ impl<'tcx> MopGraph<'tcx> {
 
         /* Handle cases if the current block is a merged scc block with sub block */
         if !cur_block.scc_sub_blocks.is_empty() {
-            match env::var_os("MOP") {
-                Some(val) if val == "0" => {
+            match precision::get().mop_merges_scc() {
+                true => {
                     order.push(cur_block.scc_sub_blocks.clone());
                 }
-                _ => {
+                false => {
                     self.calculate_scc_order(
                         &mut cur_block.scc_sub_blocks.clone(),
                         &mut vec![],
@@ -290,6 +335,10 @@ impl<'tcx> MopGraph<'tcx> {
                                     path_discr_id = *father;
                                     sw_targets = Some(targets.clone());
                                 }
+                            } else if let Some(constant) = self.arg_constant(place) {
+                                // an argument given by the calling context
+                                single_target = true;
+                                sw_val = constant;
                             }
                         }
                         Constant(c) => {
@@ -330,7 +379,7 @@ impl<'tcx> MopGraph<'tcx> {
                 // Other cases in switchInt terminators
                 if let Some(targets) = sw_targets {
                     for iter in targets.iter() {
-                        if self.visit_times > VISIT_LIMIT {
+                        if self.budget.is_exhausted(self.visit_times) {
                             continue;
                         }
                         let next_index = iter.1.as_usize();
@@ -355,7 +404,7 @@ impl<'tcx> MopGraph<'tcx> {
                     );
                 } else {
                     for i in cur_block.next {
-                        if self.visit_times > VISIT_LIMIT {
+                        if self.budget.is_exhausted(self.visit_times) {
                             continue;
                         }
                         let next_index = i;
@@ -490,3 +539,45 @@ impl<'tcx> MopGraph<'tcx> {
         visit.remove(&idx);
     }
 }
+
+/// Return the SCCs reachable from the entry block in reverse postorder, given the SCC of each block
+/// and the successors of the SCCs. It is shared by the path-insensitive fallbacks of MoP and
+/// SafeDrop.
+pub fn topo_sccs<'a>(
+    scc_indices: &[usize],
+    next: impl Fn(usize) -> &'a FxHashSet<usize>,
+) -> Vec<usize> {
+    let entry = scc_indices[0];
+    let mut visited = FxHashSet::default();
+    let mut postorder = Vec::new();
+    // the SCC and its successors yet to be visited
+    let mut stack = vec![(entry, scc_successors(scc_indices, next(entry), entry))];
+    visited.insert(entry);
+    while let Some((scc, pending)) = stack.last_mut() {
+        match pending.pop() {
+            Some(succ) => {
+                if visited.insert(succ) {
+                    stack.push((succ, scc_successors(scc_indices, next(succ), succ)));
+                }
+            }
+            None => {
+                postorder.push(*scc);
+                stack.pop();
+            }
+        }
+    }
+    postorder.reverse();
+    postorder
+}
+
+/// Return the SCCs following an SCC, given the blocks following its blocks.
+pub fn scc_successors(scc_indices: &[usize], next: &FxHashSet<usize>, scc: usize) -> Vec<usize> {
+    let mut successors: Vec<usize> = next
+        .iter()
+        .map(|next| scc_indices[*next])
+        .filter(|next| *next != scc)
+        .collect();
+    successors.sort_unstable();
+    successors.dedup();
+    successors
+}
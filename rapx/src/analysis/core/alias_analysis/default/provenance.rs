@@ -0,0 +1,111 @@
+//! The provenance of the raw pointers derived by arithmetic and by casts, shared by MoP and
+//! SafeDrop.
+//!
+//! A pointer offset from another one, by `Offset` or by the `arith_offset` intrinsic behind the
+//! `wrapping_*` methods, keeps the allocation of its base at an unknown offset, so it aliases its
+//! base as a whole; the casts among pointers, including `transmute`, are copies. A pointer cast
+//! from an integer has no base to keep: it may point to any allocation whose address has been
+//! exposed in the function, by `as usize` or `expose_provenance`, so it aliases all of them. The
+//! addresses exposed by the callers and the callees are not tracked.
+
+use super::graph::MopGraph;
+use crate::def_id::*;
+use rustc_hir::def_id::DefId;
+use rustc_middle::{
+    mir::{Body, CastKind, Operand, Place, Rvalue, StatementKind, TerminatorKind},
+    ty,
+};
+use rustc_span::source_map::Spanned;
+
+/// Where a call to a std function takes its returned pointer from, which its summary misses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PtrSource {
+    /// The pointer is offset from the first argument.
+    Base,
+    /// The pointer is cast from an integer.
+    Exposed,
+}
+
+pub fn ptr_source(func: &Operand<'_>) -> Option<PtrSource> {
+    let def_id = fn_def(func)?;
+    if Some(def_id) == arith_offset_opt() {
+        Some(PtrSource::Base)
+    } else if Some(def_id) == with_exposed_provenance_opt()
+        || Some(def_id) == with_exposed_provenance_mut_opt()
+    {
+        Some(PtrSource::Exposed)
+    } else {
+        None
+    }
+}
+
+/// The pointers whose addresses are exposed in the body.
+pub fn exposed_places<'tcx>(body: &Body<'tcx>) -> Vec<Place<'tcx>> {
+    let mut exposed = Vec::new();
+    for data in body.basic_blocks.iter() {
+        for stmt in data.statements.iter() {
+            if let StatementKind::Assign(assign) = &stmt.kind {
+                if let Rvalue::Cast(
+                    CastKind::PointerExposeProvenance,
+                    Operand::Copy(place) | Operand::Move(place),
+                    _,
+                ) = &assign.1
+                {
+                    exposed.push(*place);
+                }
+            }
+        }
+        if let TerminatorKind::Call { func, args, .. } = &data.terminator().kind {
+            let exposes = fn_def(func).is_some_and(|def_id| {
+                Some(def_id) == const_expose_provenance_opt()
+                    || Some(def_id) == mut_expose_provenance_opt()
+            });
+            if exposes {
+                if let Some(Operand::Copy(place) | Operand::Move(place)) =
+                    args.first().map(|arg| &arg.node)
+                {
+                    exposed.push(*place);
+                }
+            }
+        }
+    }
+    exposed
+}
+
+fn fn_def(func: &Operand<'_>) -> Option<DefId> {
+    match func.constant()?.const_.ty().kind() {
+        &ty::FnDef(def_id, _) => Some(def_id),
+        _ => None,
+    }
+}
+
+impl<'tcx> MopGraph<'tcx> {
+    /* Merge the pointer returned by a call to a std function deriving it from another pointer or
+     * from an integer, which returns false for the other calls. */
+    pub fn alias_provenance(
+        &mut self,
+        func: &Operand<'tcx>,
+        args: &[Spanned<Operand<'tcx>>],
+        lv: usize,
+    ) -> bool {
+        match ptr_source(func) {
+            Some(PtrSource::Base) => {
+                if let Some(Operand::Copy(place) | Operand::Move(place)) =
+                    args.first().map(|arg| &arg.node)
+                {
+                    let rv = self.projection(true, *place);
+                    self.merge_alias(lv, rv, 0);
+                }
+                true
+            }
+            Some(PtrSource::Exposed) => {
+                for place in self.exposed.clone() {
+                    let rv = self.projection(true, place);
+                    self.merge_alias(lv, rv, 0);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+}
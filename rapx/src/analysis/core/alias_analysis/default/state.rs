@@ -0,0 +1,172 @@
+//! The aliases at the program points of a function, which answer the queries of
+//! [`crate::analysis::core::alias_analysis::AliasAnalysis::may_alias`] and `points_to`.
+//!
+//! MoP only keeps the alias sets of the path being visited. When the states of a function are
+//! requested, its paths are visited again and the partition of the tracked places into alias sets
+//! is recorded before each assignment, so the state before a statement is the one after the last
+//! assignment ahead of it in the block. Recording a partition is linear in the places, and the
+//! aliases are only looked up in it by the queries. The states of all paths reaching a location are
+//! kept: two places may alias there if they do on one of the paths.
+
+use super::graph::MopGraph;
+use crate::analysis::core::alias_analysis::AAPlace;
+use rustc_data_structures::fx::{FxHashMap, FxHashSet};
+use rustc_middle::mir::Location;
+use std::collections::BTreeMap;
+
+/// The alias set of each place by its index, labelled with the smallest index of the set, or
+/// `usize::MAX` for the places not tracked on the path; the trailing ones are left out.
+type Partition = Vec<usize>;
+
+#[derive(Debug, Clone, Default)]
+pub struct AliasStates {
+    // block -> statement -> the partitions of the paths before the statement.
+    states: FxHashMap<usize, BTreeMap<usize, FxHashSet<Partition>>>,
+    // the places tracked by the graph on some path and whether they are pointers.
+    places: Vec<(AAPlace, bool)>,
+    indices: FxHashMap<AAPlace, usize>,
+}
+
+impl AliasStates {
+    /// Test if two places may alias before the statement at the location.
+    pub fn may_alias(&self, location: Location, lhs: &AAPlace, rhs: &AAPlace) -> bool {
+        let lhs = self.resolve(lhs);
+        let rhs = self.resolve(rhs);
+        if lhs == rhs {
+            return true;
+        }
+        let (Some(lhs), Some(rhs)) = (self.indices.get(&lhs), self.indices.get(&rhs)) else {
+            return false;
+        };
+        self.partitions(location).any(|partition| {
+            let set = alias_set(partition, *lhs);
+            set != usize::MAX && set == alias_set(partition, *rhs)
+        })
+    }
+
+    /// Return the places the place may point to before the statement at the location, i.e., the
+    /// places it may alias which are not pointers themselves.
+    pub fn points_to(&self, location: Location, place: &AAPlace) -> Vec<AAPlace> {
+        let Some(place) = self.indices.get(&self.resolve(place)) else {
+            return Vec::new();
+        };
+        let mut pointees = FxHashSet::default();
+        for partition in self.partitions(location) {
+            let set = alias_set(partition, *place);
+            if set == usize::MAX {
+                continue;
+            }
+            pointees.extend(
+                partition
+                    .iter()
+                    .enumerate()
+                    .filter(|(idx, other)| **other == set && idx != place && !self.places[*idx].1)
+                    .map(|(idx, _)| idx),
+            );
+        }
+        let mut pointees: Vec<AAPlace> = pointees
+            .into_iter()
+            .map(|idx| self.places[idx].0.clone())
+            .collect();
+        pointees.sort();
+        pointees
+    }
+
+    fn partitions(&self, location: Location) -> impl Iterator<Item = &Partition> {
+        self.states
+            .get(&location.block.as_usize())
+            .and_then(|block| {
+                block
+                    .range(..=location.statement_index)
+                    .next_back()
+                    .map(|(_, partitions)| partitions)
+            })
+            .into_iter()
+            .flatten()
+    }
+
+    /// The fields not tracked by the graph share the aliases of their closest tracked parent.
+    fn resolve(&self, place: &AAPlace) -> AAPlace {
+        let mut place = place.clone();
+        while !place.fields.is_empty() && !self.indices.contains_key(&place) {
+            place.fields.pop();
+        }
+        place
+    }
+
+    fn intern(&mut self, place: AAPlace, is_ptr: bool) -> usize {
+        match self.indices.get(&place) {
+            Some(idx) => {
+                self.places[*idx].1 |= is_ptr;
+                *idx
+            }
+            None => {
+                let idx = self.places.len();
+                self.indices.insert(place.clone(), idx);
+                self.places.push((place, is_ptr));
+                idx
+            }
+        }
+    }
+}
+
+fn alias_set(partition: &Partition, place: usize) -> usize {
+    partition.get(place).copied().unwrap_or(usize::MAX)
+}
+
+impl<'tcx> MopGraph<'tcx> {
+    /// Record the alias sets of the current path before a statement of the block.
+    pub fn record_state(&mut self, block: usize, statement: usize) {
+        if self.states.is_none() {
+            return;
+        }
+        // the places of the value nodes, from the locals down to their fields
+        let mut places: Vec<Option<AAPlace>> = vec![None; self.values.len()];
+        let mut stack = Vec::new();
+        for (idx, value) in self.values.iter().enumerate() {
+            if value.field_id == usize::MAX {
+                places[idx] = Some(AAPlace::new(value.local, Vec::new()));
+                stack.push(idx);
+            }
+        }
+        while let Some(idx) = stack.pop() {
+            let place = places[idx].clone().unwrap();
+            for (field, child) in self.values[idx].fields.iter() {
+                if places[*child].is_none() {
+                    places[*child] = Some(place.field(*field));
+                    stack.push(*child);
+                }
+            }
+        }
+        let roots: Vec<usize> = (0..places.len()).map(|idx| self.union_find(idx)).collect();
+
+        let states = self.states.as_mut().unwrap();
+        let mut members = Vec::new();
+        for (idx, place) in places.into_iter().enumerate() {
+            if let Some(place) = place {
+                members.push((states.intern(place, self.values[idx].is_ptr()), roots[idx]));
+            }
+        }
+        // label each alias set with its smallest place, so that the same partition is recorded
+        // once for all paths
+        let mut labels: FxHashMap<usize, usize> = FxHashMap::default();
+        for (place, root) in members.iter() {
+            let label = labels.entry(*root).or_insert(*place);
+            *label = (*label).min(*place);
+        }
+        let mut partition = vec![usize::MAX; states.places.len()];
+        for (place, root) in members {
+            partition[place] = labels[&root];
+        }
+        while partition.last() == Some(&usize::MAX) {
+            partition.pop();
+        }
+        states
+            .states
+            .entry(block)
+            .or_default()
+            .entry(statement)
+            .or_default()
+            .insert(partition);
+    }
+}
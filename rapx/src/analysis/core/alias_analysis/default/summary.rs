@@ -0,0 +1,100 @@
+//! Alias summaries of dependency crates.
+//!
+//! The MIR of most functions is not encoded in the metadata of a dependency crate, so the alias
+//! analysis cannot follow the calls into it. Therefore, `cargo rapx` also runs rapx on the library
+//! crates it compiles as dependencies (with `-dep-summary`), which computes the MoP summaries of
+//! their functions and writes them beside the rlib or rmeta file as
+//! `lib<crate>-<hash>.rapx-alias.json`. The summaries are loaded when analyzing the crates that
+//! depend on them.
+
+use super::{AliasAnalyzer, MopAAResult, MopAAResultMap};
+use crate::{
+    analysis::Analysis,
+    rap_debug, rap_warn,
+    utils::cache::{def_id_from_key, def_id_key},
+};
+use rustc_hir::def_id::LOCAL_CRATE;
+use rustc_middle::ty::TyCtxt;
+use rustc_session::{config::CrateType, output::filename_for_input};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+const SUMMARY_EXT: &str = "rapx-alias.json";
+
+#[derive(Serialize, Deserialize)]
+struct CrateSummary {
+    crate_name: String,
+    /// The summaries keyed by the def path hashes of functions.
+    summaries: BTreeMap<String, MopAAResult>,
+}
+
+/// Compute the alias summaries of the functions in the local crate, and write them beside the
+/// library file of the crate. Return the path of the summary file.
+pub fn dump_crate_summary(tcx: TyCtxt<'_>) -> io::Result<PathBuf> {
+    let mut analyzer = AliasAnalyzer::new(tcx);
+    analyzer.run();
+    let summaries = analyzer
+        .fn_map
+        .iter()
+        .filter(|(def_id, _)| def_id.is_local())
+        .map(|(def_id, result)| (def_id_key(tcx, *def_id), result.clone()))
+        .collect();
+    let summary = CrateSummary {
+        crate_name: tcx.crate_name(LOCAL_CRATE).to_string(),
+        summaries,
+    };
+    let lib = filename_for_input(
+        tcx.sess,
+        CrateType::Rlib,
+        tcx.crate_name(LOCAL_CRATE),
+        tcx.output_filenames(()),
+    );
+    let path = summary_path(lib.as_path());
+    fs::write(&path, serde_json::to_vec(&summary)?)?;
+    Ok(path)
+}
+
+/// Load the alias summaries of all the dependency crates that have one.
+pub fn load_dep_summaries(tcx: TyCtxt<'_>) -> MopAAResultMap {
+    let mut fn_map = MopAAResultMap::default();
+    for &cnum in tcx.crates(()) {
+        let source = tcx.used_crate_source(cnum);
+        let Some(path) = source
+            .paths()
+            .find(|path| !path.as_os_str().is_empty())
+            .map(|lib| summary_path(lib))
+        else {
+            continue;
+        };
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(_) => continue,
+        };
+        let summary: CrateSummary = match serde_json::from_str(&content) {
+            Ok(summary) => summary,
+            Err(err) => {
+                rap_warn!("Ignore the broken alias summary {}: {err}", path.display());
+                continue;
+            }
+        };
+        rap_debug!(
+            "Load {} alias summaries of crate {}.",
+            summary.summaries.len(),
+            summary.crate_name
+        );
+        for (key, result) in summary.summaries {
+            if let Some(def_id) = def_id_from_key(tcx, &key) {
+                fn_map.insert(def_id, result);
+            }
+        }
+    }
+    fn_map
+}
+
+fn summary_path(lib: &Path) -> PathBuf {
+    lib.with_extension(SUMMARY_EXT)
+}
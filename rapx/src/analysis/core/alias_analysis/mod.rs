@@ -1,10 +1,14 @@
+pub mod andersen;
 pub mod default;
+pub mod serialize;
 use crate::utils::source::get_fn_name_byid;
 
 use super::super::Analysis;
 use rustc_data_structures::fx::FxHashMap;
 use rustc_hir::def_id::DefId;
+use rustc_middle::mir::{Location, Place, ProjectionElem};
 use rustc_span::def_id::LOCAL_CRATE;
+use serde::{Deserialize, Serialize};
 use std::{collections::HashSet, fmt};
 
 /// The data structure to store aliases for a set of functions.
@@ -13,6 +17,16 @@ pub type AAResultMap = FxHashMap<DefId, AAResult>;
 /// This is a wrapper struct for displaying AAResultMap.
 pub struct AAResultMapWrapper(pub AAResultMap);
 
+/// The alias analysis answering `-alias` and its clients.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Default)]
+pub enum AliasBackend {
+    /// The meet-over-paths analysis of each function, see [`default`].
+    #[default]
+    Mop,
+    /// The whole-program points-to analysis, see [`andersen`].
+    Andersen,
+}
+
 /// This trait provides features related to alias analysis.
 pub trait AliasAnalysis: Analysis {
     /// Return the aliases among the function arguments and return value of a specific function.
@@ -28,6 +42,22 @@ pub trait AliasAnalysis: Analysis {
             .map(|(k, v)| (*k, v.clone()))
             .collect()
     }
+    /// Test if two places of a function may alias before the statement at the location, i.e., on
+    /// some path reaching it. The analyses without the aliases at program points answer `true`.
+    fn may_alias(
+        &self,
+        _def_id: DefId,
+        _location: Location,
+        _lhs: Place<'_>,
+        _rhs: Place<'_>,
+    ) -> bool {
+        true
+    }
+    /// Return the places a place of a function may point to before the statement at the location.
+    /// The analyses without the aliases at program points return no place.
+    fn points_to(&self, _def_id: DefId, _location: Location, _place: Place<'_>) -> Vec<AAPlace> {
+        Vec::new()
+    }
 }
 
 /// To store the alias relationships among arguments and return values.
@@ -81,8 +111,10 @@ impl fmt::Display for AAResult {
         if self.aliases().is_empty() {
             write!(f, "null")?;
         } else {
-            let joined = self
-                .aliases()
+            // sorted, as the facts are stored in a hash set
+            let mut facts: Vec<&AAFact> = self.aliases().iter().collect();
+            facts.sort();
+            let joined = facts
                 .iter()
                 .map(|fact| format!("{}", fact))
                 .collect::<Vec<_>>()
@@ -104,9 +136,47 @@ impl fmt::Display for AAResultMapWrapper {
     }
 }
 
+/// A place of a function as seen by the alias analyses: a local and the fields projected from it.
+/// The dereferences are not distinguished, i.e., a reference aliases the value it refers to.
+#[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct AAPlace {
+    pub local: usize,
+    pub fields: Vec<usize>,
+}
+
+impl AAPlace {
+    pub fn new(local: usize, fields: Vec<usize>) -> AAPlace {
+        AAPlace { local, fields }
+    }
+
+    pub fn from_place(place: Place<'_>) -> AAPlace {
+        let fields = place
+            .projection
+            .iter()
+            .filter_map(|proj| match proj {
+                ProjectionElem::Field(field, _) => Some(field.as_usize()),
+                _ => None,
+            })
+            .collect();
+        AAPlace::new(place.local.as_usize(), fields)
+    }
+
+    pub fn field(&self, field: usize) -> AAPlace {
+        let mut fields = self.fields.clone();
+        fields.push(field);
+        AAPlace::new(self.local, fields)
+    }
+}
+
+impl fmt::Display for AAPlace {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", aa_place_desc_str(self.local, &self.fields, true))
+    }
+}
+
 /// AAFact is used to store the alias relationships between two places.
 /// The result is field-sensitive.
-#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+#[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct AAFact {
     pub lhs_no: usize,
     pub lhs_fields: Vec<usize>,
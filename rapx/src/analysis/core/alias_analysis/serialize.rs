@@ -0,0 +1,57 @@
+use super::{AAFact, AAResult, AAResultMap};
+use rustc_middle::ty::TyCtxt;
+use serde::{ser::SerializeMap, Serialize};
+use std::collections::BTreeMap;
+
+/// This is a wrapper struct for serializing AAResultMap, keyed by the def paths of functions.
+pub struct AAResultMapJson<'a, 'tcx> {
+    pub result: &'a AAResultMap,
+    pub tcx: TyCtxt<'tcx>,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct AAFactInfo<'a> {
+    lhs: usize,
+    lhs_fields: &'a [usize],
+    rhs: usize,
+    rhs_fields: &'a [usize],
+}
+
+impl<'a> From<&'a AAFact> for AAFactInfo<'a> {
+    fn from(fact: &'a AAFact) -> Self {
+        AAFactInfo {
+            lhs: fact.lhs_no(),
+            lhs_fields: fact.lhs_fields(),
+            rhs: fact.rhs_no(),
+            rhs_fields: fact.rhs_fields(),
+        }
+    }
+}
+
+impl Serialize for AAResult {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut aliases: Vec<AAFactInfo> = self.aliases().iter().map(Into::into).collect();
+        aliases.sort();
+        let mut map = serializer.serialize_map(Some(2))?;
+        map.serialize_entry("arg_size", &self.arg_size())?;
+        map.serialize_entry("aliases", &aliases)?;
+        map.end()
+    }
+}
+
+impl<'a, 'tcx> Serialize for AAResultMapJson<'a, 'tcx> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let sorted: BTreeMap<String, &AAResult> = self
+            .result
+            .iter()
+            .map(|(def_id, result)| (self.tcx.def_path_str(*def_id), result))
+            .collect();
+        sorted.serialize(serializer)
+    }
+}
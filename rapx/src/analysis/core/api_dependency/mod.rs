@@ -100,10 +100,11 @@ impl<'tcx> Analysis for ApiDependencyAnalyzer<'tcx> {
             .dump_to_json(&json_path)
             .expect("failed to dump API graph to JSON");
         rap_info!("Dump API dependency graph to {}", json_path);
+        self.api_graph = api_graph;
     }
 
     fn reset(&mut self) {
-        todo!();
+        self.api_graph = ApiDependencyGraph::new(self.tcx);
     }
 }
 
@@ -8,7 +8,7 @@ use std::{collections::HashMap, hash::Hash};
 
 use super::visitor::CallGraphVisitor;
 use crate::{
-    analysis::core::callgraph::{CallGraph, CallGraphAnalysis},
+    analysis::core::callgraph::{CallGraph, CallGraphAnalysis, CallSite},
     rap_debug, rap_info, Analysis,
 };
 
@@ -23,44 +23,47 @@ impl<'tcx> Analysis for CallGraphAnalyzer<'tcx> {
     }
 
     fn run(&mut self) {
-        let mut analysis = CallGraphAnalyzer::new(self.tcx);
-        analysis.start();
+        self.start();
     }
 
     fn reset(&mut self) {
-        todo!();
+        self.graph = CallGraphInfo::new();
     }
 }
 
 impl<'tcx> CallGraphAnalysis for CallGraphAnalyzer<'tcx> {
     fn get_callgraph(&mut self) -> CallGraph {
-        let fn_calls: HashMap<DefId, Vec<DefId>> = self
+        let def_id_of = |id: &usize| {
+            self.graph
+                .functions
+                .get(id)
+                .expect("Key must exist in functions map")
+                .def_id
+        };
+        let mut fn_calls: HashMap<DefId, Vec<DefId>> = HashMap::new();
+        let mut call_sites: HashMap<DefId, Vec<CallSite>> = HashMap::new();
+        for (caller, callees) in &self.graph.fn_calls {
+            let caller_id = def_id_of(caller);
+            for (callee, terminator) in callees {
+                let callee_id = def_id_of(callee);
+                fn_calls.entry(caller_id).or_default().push(callee_id);
+                call_sites.entry(caller_id).or_default().push(CallSite {
+                    callee: callee_id,
+                    span: terminator.source_info.span,
+                });
+            }
+        }
+        let functions = self
             .graph
-            .fn_calls
-            .clone()
-            .into_iter()
-            .map(|(caller, callees)| {
-                let caller_id = self
-                    .graph
-                    .functions
-                    .get(&caller)
-                    .expect("Key must exist in functions map")
-                    .def_id;
-
-                let callees_id = callees
-                    .into_iter()
-                    .map(|(callee, _)| {
-                        self.graph
-                            .functions
-                            .get(&callee)
-                            .expect("Value must exist in functions map")
-                            .def_id
-                    })
-                    .collect::<Vec<_>>();
-                (caller_id, callees_id)
-            })
+            .functions
+            .values()
+            .map(Node::get_def_id)
             .collect();
-        CallGraph { fn_calls }
+        CallGraph {
+            fn_calls,
+            call_sites,
+            functions,
+        }
     }
 }
 
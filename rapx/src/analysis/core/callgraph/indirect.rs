@@ -0,0 +1,307 @@
+//! Resolution of indirect calls, i.e., calls through `fn` pointers, `dyn Trait` objects and the
+//! closures bound to generic `Fn*` parameters.
+//!
+//! The resolver runs a flow-insensitive and field-insensitive points-to analysis over the local
+//! crate, which tracks the types of the values reaching each local: fn items, closures and the
+//! concrete types that may hide behind a trait object. The values flow through assignments,
+//! references, casts and aggregates, and from the arguments of direct calls to the parameters of
+//! the callees (and back through the return values), so that a closure passed to a generic
+//! function is known inside it. A call is then resolved against the values reaching its callee
+//! operand or its receiver. A virtual call without any known receiver falls back to the impls of
+//! the trait if there are only a few of them.
+//!
+//! The results are computed once per crate and looked up by [`indirect_callees`].
+
+use crate::rap_debug;
+use rustc_data_structures::fx::{FxHashMap, FxHashSet};
+use rustc_hir::def_id::DefId;
+use rustc_middle::{
+    mir::{BasicBlock, Body, Local, Operand, Rvalue, StatementKind, TerminatorKind},
+    ty::{self, Instance, InstanceKind, Ty, TyCtxt, TypeVisitableExt, TypingEnv},
+};
+use std::sync::OnceLock;
+
+/// A virtual call without any known receiver is resolved to the impls of the trait only if there
+/// are no more of them than this.
+pub const IMPL_FALLBACK_LIMIT: usize = 16;
+
+static INDIRECT_CALLS: OnceLock<FxHashMap<(DefId, BasicBlock), Vec<DefId>>> = OnceLock::new();
+
+/// Return the possible callees of the indirect call terminating the basic block `bb` of the local
+/// function `caller`. It is empty for direct calls and for the calls that cannot be resolved.
+pub fn indirect_callees(tcx: TyCtxt<'_>, caller: DefId, bb: BasicBlock) -> &'static [DefId] {
+    INDIRECT_CALLS
+        .get_or_init(|| IndirectCallResolver::new(tcx).resolve_all())
+        .get(&(caller, bb))
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+}
+
+/// Test if the function is the method of `Fn`, `FnMut` or `FnOnce`, whose arguments are passed in a
+/// tuple instead of one by one.
+pub fn is_fn_trait_method(tcx: TyCtxt<'_>, def_id: DefId) -> bool {
+    tcx.trait_of_assoc(def_id)
+        .is_some_and(|trait_id| tcx.fn_trait_kind_from_def_id(trait_id).is_some())
+}
+
+struct IndirectCallResolver<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    bodies: FxHashMap<DefId, &'tcx Body<'tcx>>,
+    // The types of the values that may reach each local of each function.
+    pts: FxHashMap<DefId, Vec<FxHashSet<Ty<'tcx>>>>,
+}
+
+impl<'tcx> IndirectCallResolver<'tcx> {
+    fn new(tcx: TyCtxt<'tcx>) -> Self {
+        let mut bodies = FxHashMap::default();
+        for local_def_id in tcx.mir_keys(()) {
+            let def_id = local_def_id.to_def_id();
+            if tcx.hir_body_const_context(*local_def_id).is_some() || !tcx.is_mir_available(def_id)
+            {
+                continue;
+            }
+            bodies.insert(def_id, tcx.optimized_mir(def_id));
+        }
+        let pts = bodies
+            .iter()
+            .map(|(def_id, body)| {
+                let seeds = body
+                    .local_decls
+                    .iter()
+                    .map(|decl| Self::value_ty(decl.ty).into_iter().collect())
+                    .collect();
+                (*def_id, seeds)
+            })
+            .collect();
+        let mut resolver = IndirectCallResolver { tcx, bodies, pts };
+        resolver.solve();
+        resolver
+    }
+
+    /// The type of the value a local of type `ty` holds or points to, if it is a fn item, a closure
+    /// or another concrete type that may implement a trait.
+    fn value_ty(mut ty: Ty<'tcx>) -> Option<Ty<'tcx>> {
+        loop {
+            ty = match ty.kind() {
+                ty::Ref(_, inner, _) | ty::RawPtr(inner, _) => *inner,
+                ty::Adt(adt_def, _) if adt_def.is_box() => ty.expect_boxed_ty(),
+                _ => break,
+            };
+        }
+        let is_value = matches!(ty.kind(), ty::FnDef(..) | ty::Closure(..) | ty::Adt(..));
+        (is_value && !ty.has_param() && !ty.has_escaping_bound_vars()).then_some(ty)
+    }
+
+    fn operand_values(&self, def_id: DefId, operand: &Operand<'tcx>) -> Vec<Ty<'tcx>> {
+        match operand {
+            Operand::Copy(place) | Operand::Move(place) => self.pts[&def_id]
+                [place.local.as_usize()]
+            .iter()
+            .copied()
+            .collect(),
+            Operand::Constant(constant) => Self::value_ty(constant.ty()).into_iter().collect(),
+        }
+    }
+
+    fn add_values(&mut self, def_id: DefId, local: Local, values: Vec<Ty<'tcx>>) -> bool {
+        let set = &mut self.pts.get_mut(&def_id).unwrap()[local.as_usize()];
+        let len = set.len();
+        set.extend(values);
+        set.len() != len
+    }
+
+    /// Propagate the values until a fixed point is reached.
+    fn solve(&mut self) {
+        let def_ids: Vec<DefId> = self.bodies.keys().copied().collect();
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for def_id in &def_ids {
+                let body = self.bodies[def_id];
+                for data in body.basic_blocks.iter() {
+                    for stmt in &data.statements {
+                        let StatementKind::Assign(box (place, rvalue)) = &stmt.kind else {
+                            continue;
+                        };
+                        let values = match rvalue {
+                            Rvalue::Use(op)
+                            | Rvalue::Cast(_, op, _)
+                            | Rvalue::Repeat(op, _)
+                            | Rvalue::ShallowInitBox(op, _) => self.operand_values(*def_id, op),
+                            Rvalue::Ref(_, _, p)
+                            | Rvalue::RawPtr(_, p)
+                            | Rvalue::CopyForDeref(p) => self.pts[def_id][p.local.as_usize()]
+                                .iter()
+                                .copied()
+                                .collect(),
+                            Rvalue::Aggregate(_, ops) => ops
+                                .iter()
+                                .flat_map(|op| self.operand_values(*def_id, op))
+                                .collect(),
+                            _ => continue,
+                        };
+                        changed |= self.add_values(*def_id, place.local, values);
+                    }
+                    let TerminatorKind::Call {
+                        func,
+                        args,
+                        destination,
+                        ..
+                    } = &data.terminator().kind
+                    else {
+                        continue;
+                    };
+                    let (callees, spread) = match self.resolve_call(*def_id, func, args) {
+                        Some(callees) => (callees, is_fn_callee(self.tcx, func)),
+                        None => (
+                            self.direct_callee(*def_id, func).into_iter().collect(),
+                            false,
+                        ),
+                    };
+                    for callee in callees {
+                        let Some(callee_body) = self.bodies.get(&callee).copied() else {
+                            continue;
+                        };
+                        // the closures called through `Fn*` traits take the spread arguments
+                        if !spread && callee_body.arg_count == args.len() {
+                            for (idx, arg) in args.iter().enumerate() {
+                                let values = self.operand_values(*def_id, &arg.node);
+                                changed |=
+                                    self.add_values(callee, Local::from_usize(idx + 1), values);
+                            }
+                        }
+                        let values: Vec<_> = self.pts[&callee][0].iter().copied().collect();
+                        changed |= self.add_values(*def_id, destination.local, values);
+                    }
+                }
+            }
+        }
+    }
+
+    fn direct_callee(&self, caller: DefId, func: &Operand<'tcx>) -> Option<DefId> {
+        let &ty::FnDef(callee, args) = func.constant()?.ty().kind() else {
+            return None;
+        };
+        let typing_env = TypingEnv::post_analysis(self.tcx, caller);
+        match Instance::try_resolve(self.tcx, typing_env, callee, args) {
+            Ok(Some(instance)) => Some(instance.def_id()),
+            _ => Some(callee),
+        }
+    }
+
+    /// Resolve a call if it is indirect, or return `None` for a direct call.
+    fn resolve_call(
+        &self,
+        caller: DefId,
+        func: &Operand<'tcx>,
+        args: &[rustc_span::source_map::Spanned<Operand<'tcx>>],
+    ) -> Option<Vec<DefId>> {
+        let tcx = self.tcx;
+        let typing_env = TypingEnv::post_analysis(tcx, caller);
+        let (method, generic_args) = match func {
+            // a call through a `fn` pointer
+            Operand::Copy(_) | Operand::Move(_) => {
+                let callees = self
+                    .operand_values(caller, func)
+                    .into_iter()
+                    .filter_map(|ty| self.fn_value(typing_env, ty))
+                    .collect();
+                return Some(callees);
+            }
+            Operand::Constant(constant) => match constant.ty().kind() {
+                ty::FnDef(def_id, generic_args) => (*def_id, *generic_args),
+                _ => return None,
+            },
+        };
+        let is_virtual = match Instance::try_resolve(tcx, typing_env, method, generic_args) {
+            Ok(Some(instance)) => match instance.def {
+                InstanceKind::Virtual(..) => true,
+                _ => return None,
+            },
+            // e.g., a method called on a type parameter
+            Ok(None) => false,
+            Err(_) => return None,
+        };
+        let trait_id = tcx.trait_of_assoc(method)?;
+        let receiver = args.first()?;
+        let mut callees: Vec<DefId> = Vec::new();
+        for ty in self.operand_values(caller, &receiver.node) {
+            let callee = match ty.kind() {
+                ty::FnDef(..) | ty::Closure(..) if is_fn_trait_method(tcx, method) => {
+                    self.fn_value(typing_env, ty)
+                }
+                _ => {
+                    let generic_args = tcx.mk_args_from_iter(
+                        std::iter::once(ty.into()).chain(generic_args.iter().skip(1)),
+                    );
+                    match Instance::try_resolve(tcx, typing_env, method, generic_args) {
+                        Ok(Some(instance)) if matches!(instance.def, InstanceKind::Item(_)) => {
+                            Some(instance.def_id())
+                        }
+                        _ => None,
+                    }
+                }
+            };
+            if let Some(callee) = callee {
+                if !callees.contains(&callee) {
+                    callees.push(callee);
+                }
+            }
+        }
+        if callees.is_empty() && is_virtual {
+            let impls: Vec<DefId> = tcx.all_impls(trait_id).collect();
+            if impls.len() <= IMPL_FALLBACK_LIMIT {
+                callees = impls
+                    .into_iter()
+                    .filter_map(|impl_id| tcx.impl_item_implementor_ids(impl_id).get(&method))
+                    .copied()
+                    .collect();
+            }
+        }
+        Some(callees)
+    }
+
+    /// The function called through a value of a fn item or closure type.
+    fn fn_value(&self, typing_env: TypingEnv<'tcx>, ty: Ty<'tcx>) -> Option<DefId> {
+        match ty.kind() {
+            ty::FnDef(def_id, args) => {
+                match Instance::try_resolve(self.tcx, typing_env, *def_id, args) {
+                    Ok(Some(instance)) => Some(instance.def_id()),
+                    _ => Some(*def_id),
+                }
+            }
+            ty::Closure(def_id, _) => Some(*def_id),
+            _ => None,
+        }
+    }
+
+    fn resolve_all(&self) -> FxHashMap<(DefId, BasicBlock), Vec<DefId>> {
+        let mut calls = FxHashMap::default();
+        for (def_id, body) in &self.bodies {
+            for (bb, data) in body.basic_blocks.iter_enumerated() {
+                if let TerminatorKind::Call { func, args, .. } = &data.terminator().kind {
+                    if let Some(callees) = self.resolve_call(*def_id, func, args) {
+                        if !callees.is_empty() {
+                            rap_debug!(
+                                "Indirect call in {:?} {:?}: {:?}",
+                                self.tcx.def_path_str(*def_id),
+                                bb,
+                                callees
+                            );
+                            calls.insert((*def_id, bb), callees);
+                        }
+                    }
+                }
+            }
+        }
+        calls
+    }
+}
+
+/// Test if the callee operand is a method of the `Fn*` traits.
+fn is_fn_callee(tcx: TyCtxt<'_>, func: &Operand<'_>) -> bool {
+    match func.constant().map(|constant| constant.ty().kind()) {
+        Some(ty::FnDef(def_id, _)) => is_fn_trait_method(tcx, *def_id),
+        _ => false,
+    }
+}
@@ -1,15 +1,34 @@
 pub mod default;
+pub mod indirect;
+pub mod query;
+pub mod serialize;
 pub mod visitor;
 
 use crate::Analysis;
 use rustc_hir::def_id::DefId;
 use rustc_middle::ty::TyCtxt;
-use std::{collections::HashMap, fmt};
+use rustc_span::Span;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+};
 
 /// This is the data structure used to store function calls.
 /// It contains a HashMap that records the callees of all functions.
+/// The queries on the graph, e.g., reachability and SCCs, are implemented in [`query`].
 pub struct CallGraph {
     pub fn_calls: HashMap<DefId, Vec<DefId>>, // caller_id -> Vec<(callee_id)>
+    /// The calls of each caller along with their spans; there is one entry per call terminator.
+    pub call_sites: HashMap<DefId, Vec<CallSite>>,
+    /// All the functions in the graph, including those neither calling nor called by others.
+    pub functions: HashSet<DefId>,
+}
+
+/// A call from a caller to `callee`, located by the span of the call terminator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CallSite {
+    pub callee: DefId,
+    pub span: Span,
 }
 
 pub struct CallGraphDisplay<'a, 'tcx> {
@@ -28,6 +47,17 @@ impl<'a, 'tcx> fmt::Display for CallGraphDisplay<'a, 'tcx> {
                 writeln!(f, "    -> {}", callee_name)?;
             }
         }
+        let groups = self.graph.recursion_groups();
+        if !groups.is_empty() {
+            writeln!(f, "Recursion groups:")?;
+            for group in groups {
+                let names: Vec<String> = group
+                    .iter()
+                    .map(|def_id| self.tcx.def_path_str(*def_id))
+                    .collect();
+                writeln!(f, "  {{{}}}", names.join(", "))?;
+            }
+        }
         Ok(())
     }
 }
@@ -36,4 +66,30 @@ impl<'a, 'tcx> fmt::Display for CallGraphDisplay<'a, 'tcx> {
 pub trait CallGraphAnalysis: Analysis {
     /// Return the call graph.
     fn get_callgraph(&mut self) -> CallGraph;
+
+    /// Return the functions that call `def_id` directly.
+    fn get_callers(&mut self, def_id: DefId) -> Vec<DefId> {
+        self.get_callgraph().callers_of(def_id)
+    }
+
+    /// Return the functions that `def_id` may call directly or transitively.
+    fn get_transitive_callees(&mut self, def_id: DefId) -> HashSet<DefId> {
+        self.get_callgraph().transitive_callees(def_id)
+    }
+
+    /// Return the functions reachable from the entry points, including the entry points.
+    fn get_reachable(&mut self, entries: &[DefId]) -> HashSet<DefId> {
+        self.get_callgraph().reachable_from(entries.iter().copied())
+    }
+
+    /// Return the strongly connected components of the call graph in bottom-up order.
+    fn get_sccs(&mut self) -> Vec<Vec<DefId>> {
+        self.get_callgraph().sccs()
+    }
+
+    /// Return all the functions in bottom-up order, i.e., callees before their callers except for
+    /// the functions in the same recursion group.
+    fn get_bottom_up_order(&mut self) -> Vec<DefId> {
+        self.get_callgraph().bottom_up_order()
+    }
 }
@@ -0,0 +1,205 @@
+//! Queries on the call graph: the callers and callees of a function, the reachability from a set
+//! of entry points, the strongly connected components (i.e., the groups of mutually recursive
+//! functions) and a bottom-up order of the functions.
+
+use super::{CallGraph, CallSite};
+use rustc_hir::def_id::DefId;
+use rustc_middle::ty::TyCtxt;
+use std::collections::{HashMap, HashSet};
+
+impl CallGraph {
+    /// Return the functions called by `def_id` directly, without duplicates.
+    pub fn callees_of(&self, def_id: DefId) -> Vec<DefId> {
+        let mut callees = Vec::new();
+        for callee in self.fn_calls.get(&def_id).into_iter().flatten() {
+            if !callees.contains(callee) {
+                callees.push(*callee);
+            }
+        }
+        callees
+    }
+
+    /// Return the functions calling `def_id` directly.
+    pub fn callers_of(&self, def_id: DefId) -> Vec<DefId> {
+        let mut callers: Vec<DefId> = self
+            .fn_calls
+            .iter()
+            .filter(|(_, callees)| callees.contains(&def_id))
+            .map(|(caller, _)| *caller)
+            .collect();
+        sort_def_ids(&mut callers);
+        callers
+    }
+
+    /// Return the call sites in `caller`.
+    pub fn call_sites_of(&self, caller: DefId) -> &[CallSite] {
+        self.call_sites
+            .get(&caller)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Return the functions that `def_id` may call directly or transitively. It contains `def_id`
+    /// only if the function is recursive.
+    pub fn transitive_callees(&self, def_id: DefId) -> HashSet<DefId> {
+        self.reachable_from(self.callees_of(def_id))
+    }
+
+    /// Return the functions reachable from the entry points, including the entry points.
+    pub fn reachable_from(&self, entries: impl IntoIterator<Item = DefId>) -> HashSet<DefId> {
+        traverse(entries, |def_id| self.callees_of(def_id))
+    }
+
+    /// Return the functions that may reach any of the targets, including the targets themselves.
+    pub fn reaching(&self, targets: impl IntoIterator<Item = DefId>) -> HashSet<DefId> {
+        let callers = self.callers_map();
+        traverse(targets, |def_id| {
+            callers.get(&def_id).cloned().unwrap_or_default()
+        })
+    }
+
+    /// Return the public functions of the local crate that may reach `target`, e.g., to tell which
+    /// APIs can trigger the unsafe code in it.
+    pub fn pub_apis_reaching(&self, tcx: TyCtxt<'_>, target: DefId) -> Vec<DefId> {
+        let mut apis: Vec<DefId> = self
+            .reaching([target])
+            .into_iter()
+            .filter(|def_id| def_id.is_local() && tcx.visibility(*def_id).is_public())
+            .collect();
+        sort_def_ids(&mut apis);
+        apis
+    }
+
+    /// Return the strongly connected components of the graph with Tarjan's algorithm. The
+    /// components come in bottom-up order: a component is listed after all the components it
+    /// calls into.
+    pub fn sccs(&self) -> Vec<Vec<DefId>> {
+        let mut nodes: Vec<DefId> = self
+            .functions
+            .iter()
+            .chain(self.fn_calls.keys())
+            .chain(self.fn_calls.values().flatten())
+            .copied()
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        sort_def_ids(&mut nodes);
+
+        let mut tarjan = Tarjan {
+            graph: self,
+            index: HashMap::new(),
+            lowlink: HashMap::new(),
+            stack: Vec::new(),
+            on_stack: HashSet::new(),
+            sccs: Vec::new(),
+        };
+        for node in nodes {
+            if !tarjan.index.contains_key(&node) {
+                tarjan.visit(node);
+            }
+        }
+        tarjan.sccs
+    }
+
+    /// Return the groups of mutually recursive functions, including the functions calling
+    /// themselves.
+    pub fn recursion_groups(&self) -> Vec<Vec<DefId>> {
+        self.sccs()
+            .into_iter()
+            .filter(|scc| scc.len() > 1 || self.callees_of(scc[0]).contains(&scc[0]))
+            .collect()
+    }
+
+    /// Return all the functions in bottom-up order, i.e., callees before their callers except for
+    /// the functions in the same recursion group.
+    pub fn bottom_up_order(&self) -> Vec<DefId> {
+        self.sccs().into_iter().flatten().collect()
+    }
+
+    fn callers_map(&self) -> HashMap<DefId, Vec<DefId>> {
+        let mut callers: HashMap<DefId, Vec<DefId>> = HashMap::new();
+        for (caller, callees) in &self.fn_calls {
+            for callee in callees {
+                callers.entry(*callee).or_default().push(*caller);
+            }
+        }
+        callers
+    }
+}
+
+struct Tarjan<'a> {
+    graph: &'a CallGraph,
+    index: HashMap<DefId, usize>,
+    lowlink: HashMap<DefId, usize>,
+    stack: Vec<DefId>,
+    on_stack: HashSet<DefId>,
+    sccs: Vec<Vec<DefId>>,
+}
+
+impl<'a> Tarjan<'a> {
+    /// Visit the nodes from `root` with an explicit stack, since call chains may be deep.
+    fn visit(&mut self, root: DefId) {
+        // the node and the callees yet to be visited
+        let mut work: Vec<(DefId, Vec<DefId>)> = Vec::new();
+        self.enter(root, &mut work);
+        while let Some((node, callees)) = work.last_mut() {
+            let node = *node;
+            if let Some(callee) = callees.pop() {
+                if !self.index.contains_key(&callee) {
+                    self.enter(callee, &mut work);
+                } else if self.on_stack.contains(&callee) {
+                    let low = self.lowlink[&node].min(self.index[&callee]);
+                    self.lowlink.insert(node, low);
+                }
+                continue;
+            }
+            work.pop();
+            if let Some((parent, _)) = work.last() {
+                let low = self.lowlink[parent].min(self.lowlink[&node]);
+                self.lowlink.insert(*parent, low);
+            }
+            if self.lowlink[&node] == self.index[&node] {
+                let mut scc = Vec::new();
+                while let Some(member) = self.stack.pop() {
+                    self.on_stack.remove(&member);
+                    scc.push(member);
+                    if member == node {
+                        break;
+                    }
+                }
+                sort_def_ids(&mut scc);
+                self.sccs.push(scc);
+            }
+        }
+    }
+
+    fn enter(&mut self, node: DefId, work: &mut Vec<(DefId, Vec<DefId>)>) {
+        let index = self.index.len();
+        self.index.insert(node, index);
+        self.lowlink.insert(node, index);
+        self.stack.push(node);
+        self.on_stack.insert(node);
+        let mut callees = self.graph.callees_of(node);
+        callees.reverse();
+        work.push((node, callees));
+    }
+}
+
+fn traverse(
+    roots: impl IntoIterator<Item = DefId>,
+    next: impl Fn(DefId) -> Vec<DefId>,
+) -> HashSet<DefId> {
+    let mut visited = HashSet::new();
+    let mut worklist: Vec<DefId> = roots.into_iter().collect();
+    while let Some(def_id) = worklist.pop() {
+        if visited.insert(def_id) {
+            worklist.extend(next(def_id));
+        }
+    }
+    visited
+}
+
+/// Sort the def ids to keep the results stable across runs.
+fn sort_def_ids(def_ids: &mut [DefId]) {
+    def_ids.sort_by_key(|def_id| (def_id.krate.as_u32(), def_id.index.as_u32()));
+}
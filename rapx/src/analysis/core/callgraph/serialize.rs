@@ -0,0 +1,104 @@
+use super::CallGraph;
+use crate::utils::diagnostic::DiagSpan;
+use rustc_middle::ty::TyCtxt;
+use serde::{ser::SerializeMap, Serialize};
+use std::{collections::BTreeMap, fmt};
+
+/// This is a wrapper struct for serializing CallGraph. The calls of each caller are keyed by its
+/// def path and carry the callee and the location of each call site (outside of macros), followed
+/// by the recursion groups and the bottom-up order of the functions.
+pub struct CallGraphJson<'a, 'tcx> {
+    pub graph: &'a CallGraph,
+    pub tcx: TyCtxt<'tcx>,
+}
+
+#[derive(Serialize, PartialEq, Eq, PartialOrd, Ord)]
+struct CallSiteJson {
+    callee: String,
+    span: String,
+}
+
+impl<'a, 'tcx> Serialize for CallGraphJson<'a, 'tcx> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let path = |def_id| self.tcx.def_path_str(def_id);
+        let calls: BTreeMap<String, Vec<CallSiteJson>> = self
+            .graph
+            .call_sites
+            .iter()
+            .map(|(caller, sites)| {
+                let mut sites: Vec<CallSiteJson> = sites
+                    .iter()
+                    .map(|site| CallSiteJson {
+                        callee: path(site.callee),
+                        span: DiagSpan::from_span(site.span.source_callsite()).to_string(),
+                    })
+                    .collect();
+                sites.sort();
+                sites.dedup();
+                (path(*caller), sites)
+            })
+            .collect();
+        let recursion_groups: Vec<Vec<String>> = self
+            .graph
+            .recursion_groups()
+            .into_iter()
+            .map(|group| group.into_iter().map(path).collect())
+            .collect();
+        let bottom_up_order: Vec<String> =
+            self.graph.bottom_up_order().into_iter().map(path).collect();
+
+        let mut map = serializer.serialize_map(Some(3))?;
+        map.serialize_entry("calls", &calls)?;
+        map.serialize_entry("recursion_groups", &recursion_groups)?;
+        map.serialize_entry("bottom_up_order", &bottom_up_order)?;
+        map.end()
+    }
+}
+
+/// This is a wrapper struct for printing CallGraph in the DOT format of Graphviz. Each edge is
+/// labeled with the location of its call site; the functions outside the local crate are dashed.
+pub struct CallGraphDot<'a, 'tcx> {
+    pub graph: &'a CallGraph,
+    pub tcx: TyCtxt<'tcx>,
+}
+
+impl<'a, 'tcx> fmt::Display for CallGraphDot<'a, 'tcx> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "digraph CallGraph {{")?;
+        writeln!(f, "    node [shape=box];")?;
+        let mut nodes: Vec<(String, bool)> = self
+            .graph
+            .bottom_up_order()
+            .into_iter()
+            .map(|def_id| (self.tcx.def_path_str(def_id), def_id.is_local()))
+            .collect();
+        nodes.sort();
+        for (name, is_local) in nodes {
+            let style = if is_local { "solid" } else { "dashed" };
+            writeln!(f, "    {:?} [style={}];", name, style)?;
+        }
+        let mut edges: Vec<(String, String, String)> = self
+            .graph
+            .call_sites
+            .iter()
+            .flat_map(|(caller, sites)| {
+                sites.iter().map(|site| {
+                    (
+                        self.tcx.def_path_str(*caller),
+                        self.tcx.def_path_str(site.callee),
+                        DiagSpan::from_span(site.span.source_callsite()).to_string(),
+                    )
+                })
+            })
+            .collect();
+        edges.sort();
+        edges.dedup();
+        for (caller, callee, span) in edges {
+            writeln!(f, "    {:?} -> {:?} [label={:?}];", caller, callee, span)?;
+        }
+        writeln!(f, "}}")
+    }
+}
@@ -1,4 +1,4 @@
-use super::default::CallGraphInfo;
+use super::{default::CallGraphInfo, indirect::indirect_callees};
 use regex::Regex;
 use rustc_hir::def_id::DefId;
 use rustc_middle::mir;
@@ -51,9 +51,13 @@ impl<'b, 'tcx> CallGraphVisitor<'b, 'tcx> {
     pub fn visit(&mut self) {
         let caller_path_str = self.tcx.def_path_str(self.def_id);
         self.call_graph_info.add_node(self.def_id, &caller_path_str);
-        for (_, data) in self.body.basic_blocks.iter().enumerate() {
+        for (bb, data) in self.body.basic_blocks.iter_enumerated() {
             let terminator = data.terminator();
             self.visit_terminator(&terminator);
+            // the possible targets of calls through fn pointers, trait objects and closures
+            for callee_def_id in indirect_callees(self.tcx, self.def_id, bb) {
+                self.add_to_call_graph(*callee_def_id, None, terminator);
+            }
         }
     }
 
@@ -1,9 +1,17 @@
 use super::graph::*;
-use crate::analysis::core::dataflow::*;
+use crate::{
+    analysis::core::dataflow::*,
+    utils::{
+        cache::{def_id_from_key, def_id_key, fn_fingerprints, SummaryCache},
+        scope,
+    },
+};
 
 pub struct DataFlowAnalyzer<'tcx> {
     pub tcx: TyCtxt<'tcx>,
     pub graphs: HashMap<DefId, Graph>,
+    /// The arg2ret summaries restored from the persistent cache; no graph is built for them.
+    pub arg2ret: Arg2RetMap,
     pub debug: bool,
 }
 
@@ -30,12 +38,15 @@ impl<'tcx> DataFlowAnalysis for DataFlowAnalyzer<'tcx> {
     }
 
     fn get_fn_arg2ret(&self, def_id: DefId) -> Arg2Ret {
+        if let Some(deps) = self.arg2ret.get(&def_id) {
+            return deps.clone();
+        }
         let graph = self.graphs.get(&def_id).unwrap();
         graph.param_return_deps()
     }
 
     fn get_all_arg2ret(&self) -> Arg2RetMap {
-        let mut result = HashMap::new();
+        let mut result = self.arg2ret.clone();
         for (def_id, graph) in &self.graphs {
             let deps = graph.param_return_deps();
             result.insert(*def_id, deps);
@@ -50,14 +61,17 @@ impl<'tcx> Analysis for DataFlowAnalyzer<'tcx> {
     }
 
     fn run(&mut self) {
-        self.build_graphs();
         if self.debug {
+            self.build_graphs();
             self.draw_graphs();
+        } else {
+            self.build_graphs_with_cache();
         }
     }
 
     fn reset(&mut self) {
         self.graphs.clear();
+        self.arg2ret.clear();
     }
 }
 
@@ -66,6 +80,7 @@ impl<'tcx> DataFlowAnalyzer<'tcx> {
         Self {
             tcx: tcx,
             graphs: HashMap::new(),
+            arg2ret: HashMap::new(),
             debug,
         }
     }
@@ -81,14 +96,66 @@ impl<'tcx> DataFlowAnalyzer<'tcx> {
         for local_def_id in self.tcx.iter_local_def_id() {
             let def_kind = self.tcx.def_kind(local_def_id);
             if matches!(def_kind, DefKind::Fn) || matches!(def_kind, DefKind::AssocFn) {
-                if self.tcx.hir_maybe_body_owned_by(local_def_id).is_some() {
-                    let def_id = local_def_id.to_def_id();
+                let def_id = local_def_id.to_def_id();
+                if self.tcx.hir_maybe_body_owned_by(local_def_id).is_some()
+                    && scope::needs(self.tcx, def_id)
+                {
                     self.build_graph(def_id);
                 }
             }
         }
     }
 
+    /// Build the graphs as `build_graphs` does, except for the functions whose arg2ret summaries
+    /// are in the persistent cache. The summary of a function is cached together with the ones of
+    /// the closures defined in it.
+    pub fn build_graphs_with_cache(&mut self) {
+        let mut cache = SummaryCache::load(self.tcx, "arg2ret", &[]);
+        if !cache.is_enabled() {
+            self.build_graphs();
+            return;
+        }
+        let fingerprints = fn_fingerprints(self.tcx);
+        for local_def_id in self.tcx.iter_local_def_id() {
+            let def_kind = self.tcx.def_kind(local_def_id);
+            let def_id = local_def_id.to_def_id();
+            if !matches!(def_kind, DefKind::Fn | DefKind::AssocFn)
+                || self.tcx.hir_maybe_body_owned_by(local_def_id).is_none()
+                || !scope::needs(self.tcx, def_id)
+            {
+                continue;
+            }
+            let Some(fingerprint) = fingerprints.get(&def_id) else {
+                self.build_graph(def_id);
+                continue;
+            };
+            let cached = cache
+                .get_fn::<Vec<(String, Vec<bool>)>>(def_id, *fingerprint)
+                .and_then(|summaries| {
+                    summaries
+                        .into_iter()
+                        .map(|(key, deps)| {
+                            Some((def_id_from_key(self.tcx, &key)?, IndexVec::from_raw(deps)))
+                        })
+                        .collect::<Option<Vec<_>>>()
+                });
+            if let Some(summaries) = cached {
+                self.arg2ret.extend(summaries);
+                continue;
+            }
+            self.build_graph(def_id);
+            let mut summaries = Vec::new();
+            let mut worklist = vec![def_id];
+            while let Some(id) = worklist.pop() {
+                let graph = &self.graphs[&id];
+                summaries.push((def_id_key(self.tcx, id), graph.param_return_deps().raw));
+                worklist.extend(graph.closures.iter().copied());
+            }
+            cache.insert_fn(def_id, *fingerprint, &summaries);
+        }
+        cache.store();
+    }
+
     pub fn build_graph(&mut self, def_id: DefId) {
         if self.graphs.contains_key(&def_id) {
             return;
@@ -1,6 +1,7 @@
 pub mod debug;
 pub mod default;
 pub mod graph;
+pub mod serialize;
 
 use std::{
     collections::{HashMap, HashSet},
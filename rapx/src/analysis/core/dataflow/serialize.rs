@@ -0,0 +1,119 @@
+use super::{AggKind, Arg2Ret, Arg2RetMap, DataFlowGraph, DataFlowGraphMap, NodeOp};
+use crate::utils::diagnostic::DiagSpan;
+use rustc_middle::ty::TyCtxt;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// This is a wrapper struct for serializing Arg2RetMap, keyed by the def paths of functions.
+/// Each function is mapped to the arguments that the return value depends on.
+pub struct Arg2RetMapJson<'a, 'tcx> {
+    pub result: &'a Arg2RetMap,
+    pub tcx: TyCtxt<'tcx>,
+}
+
+/// This is a wrapper struct for serializing DataFlowGraphMap, keyed by the def paths of functions.
+pub struct DataFlowGraphMapJson<'a, 'tcx> {
+    pub result: &'a DataFlowGraphMap,
+    pub tcx: TyCtxt<'tcx>,
+}
+
+#[derive(Serialize, Debug)]
+struct NodeInfo {
+    local: usize,
+    ops: Vec<String>,
+    span: String,
+}
+
+#[derive(Serialize, Debug)]
+struct EdgeInfo {
+    src: usize,
+    dst: usize,
+    op: String,
+    seq: usize,
+}
+
+#[derive(Serialize, Debug)]
+struct GraphInfo {
+    return_deps: Vec<usize>,
+    nodes: Vec<NodeInfo>,
+    edges: Vec<EdgeInfo>,
+}
+
+fn arg2ret_deps(arg2ret: &Arg2Ret) -> Vec<usize> {
+    arg2ret
+        .iter_enumerated()
+        .filter(|(local, depends)| local.as_usize() > 0 && **depends)
+        .map(|(local, _)| local.as_usize())
+        .collect()
+}
+
+fn node_op_str(op: &NodeOp, tcx: TyCtxt<'_>) -> String {
+    match op {
+        NodeOp::Call(def_id) => format!("Call({})", tcx.def_path_str(*def_id)),
+        NodeOp::Aggregate(AggKind::Adt(def_id)) => {
+            format!("Aggregate(Adt({}))", tcx.def_path_str(*def_id))
+        }
+        NodeOp::Aggregate(AggKind::Closure(def_id)) => {
+            format!("Aggregate(Closure({}))", tcx.def_path_str(*def_id))
+        }
+        NodeOp::Aggregate(AggKind::Coroutine(def_id)) => {
+            format!("Aggregate(Coroutine({}))", tcx.def_path_str(*def_id))
+        }
+        _ => format!("{:?}", op),
+    }
+}
+
+fn graph_info(graph: &DataFlowGraph, tcx: TyCtxt<'_>) -> GraphInfo {
+    let nodes = graph
+        .nodes
+        .iter_enumerated()
+        .map(|(local, node)| NodeInfo {
+            local: local.as_usize(),
+            ops: node.ops.iter().map(|op| node_op_str(op, tcx)).collect(),
+            span: DiagSpan::from_span(node.span).to_string(),
+        })
+        .collect();
+    let edges = graph
+        .edges
+        .iter()
+        .map(|edge| EdgeInfo {
+            src: edge.src.as_usize(),
+            dst: edge.dst.as_usize(),
+            op: format!("{:?}", edge.op),
+            seq: edge.seq,
+        })
+        .collect();
+    GraphInfo {
+        return_deps: arg2ret_deps(&graph.param_ret_deps),
+        nodes,
+        edges,
+    }
+}
+
+impl<'a, 'tcx> Serialize for Arg2RetMapJson<'a, 'tcx> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let sorted: BTreeMap<String, Vec<usize>> = self
+            .result
+            .iter()
+            .map(|(def_id, arg2ret)| (self.tcx.def_path_str(*def_id), arg2ret_deps(arg2ret)))
+            .collect();
+        sorted.serialize(serializer)
+    }
+}
+
+impl<'a, 'tcx> Serialize for DataFlowGraphMapJson<'a, 'tcx> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let sorted: BTreeMap<String, GraphInfo> = self
+            .result
+            .iter()
+            .map(|(def_id, graph)| (self.tcx.def_path_str(*def_id), graph_info(graph, self.tcx)))
+            .collect();
+        sorted.serialize(serializer)
+    }
+}
@@ -13,7 +13,10 @@ use rustc_span::def_id::DefId;
 use std::{collections::HashMap, ops::ControlFlow};
 
 use super::*;
-use crate::rap_debug;
+use crate::{
+    rap_debug,
+    utils::cache::{crate_fingerprint, def_id_from_key, def_id_key, fn_fingerprints, SummaryCache},
+};
 
 pub struct OwnedHeapAnalyzer<'tcx> {
     tcx: TyCtxt<'tcx>,
@@ -31,7 +34,10 @@ impl<'tcx> Analysis for OwnedHeapAnalyzer<'tcx> {
         self.start();
     }
     fn reset(&mut self) {
-        todo!();
+        self.adt_heap.clear();
+        self.fn_set.clear();
+        self.ty_map.clear();
+        self.adt_recorder.clear();
     }
 }
 
@@ -157,6 +163,30 @@ impl<'tcx> OwnedHeapAnalyzer<'tcx> {
         let tcx = self.tcx;
         let mir_keys = tcx.mir_keys(());
 
+        // The results cover the types used anywhere in the crate, so they are cached as a whole.
+        let mut cache = SummaryCache::load(tcx, "ownedheap", &[]);
+        let fingerprint = cache
+            .is_enabled()
+            .then(|| crate_fingerprint(&fn_fingerprints(tcx)));
+        if let Some(fingerprint) = fingerprint {
+            if let Some(adt_heap) = cache
+                .get::<Vec<(String, Vec<(OwnedHeap, Vec<bool>)>)>>("crate", fingerprint)
+                .and_then(|units| {
+                    units
+                        .into_iter()
+                        .map(|(key, unit)| Some((def_id_from_key(tcx, &key)?, unit)))
+                        .collect::<Option<OHAResultMap>>()
+                })
+            {
+                self.adt_recorder = adt_heap.keys().copied().collect();
+                self.fn_set = mir_keys.iter().map(|did| did.to_def_id()).collect();
+                self.adt_heap = adt_heap;
+                cache.store();
+                show_heap(self);
+                return;
+            }
+        }
+
         for each_mir in mir_keys {
             // Get the defid of current crate and get mir Body through this id
             let def_id = each_mir.to_def_id();
@@ -177,6 +207,16 @@ impl<'tcx> OwnedHeapAnalyzer<'tcx> {
         start_channel(|did| self.extract_phantom_unit(did), &dids);
         start_channel(|did| self.extract_heap_prop(did), &dids);
 
+        if let Some(fingerprint) = fingerprint {
+            let units: Vec<_> = self
+                .adt_heap
+                .iter()
+                .map(|(did, unit)| (def_id_key(tcx, *did), unit.clone()))
+                .collect();
+            cache.insert("crate", fingerprint, &units);
+            cache.store();
+        }
+
         show_heap(self);
     }
 
@@ -1,7 +1,9 @@
 pub mod default;
+pub mod serialize;
 
 use rustc_middle::ty::{Ty, TyKind};
 use rustc_span::def_id::DefId;
+use serde::{Deserialize, Serialize};
 
 use std::{
     collections::{HashMap, HashSet},
@@ -12,7 +14,7 @@ use std::{
 use crate::{rap_info, utils::source::get_fn_name_byid, Analysis};
 
 #[repr(u8)]
-#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
 pub enum OwnedHeap {
     False = 0,
     True = 1,
@@ -0,0 +1,44 @@
+use super::{OHAResultMap, OwnedHeap};
+use rustc_middle::ty::TyCtxt;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// This is a wrapper struct for serializing OHAResultMap, keyed by the def paths of types.
+pub struct OHAResultMapJson<'a, 'tcx> {
+    pub result: &'a OHAResultMap,
+    pub tcx: TyCtxt<'tcx>,
+}
+
+/// The owned heap information of a variant.
+#[derive(Serialize, Debug)]
+struct HeapUnit<'a> {
+    heap: &'static str,
+    params: &'a [bool],
+}
+
+impl<'a, 'tcx> Serialize for OHAResultMapJson<'a, 'tcx> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let sorted: BTreeMap<String, Vec<HeapUnit>> = self
+            .result
+            .iter()
+            .map(|(def_id, units)| {
+                let units = units
+                    .iter()
+                    .map(|(heap, params)| HeapUnit {
+                        heap: match heap {
+                            OwnedHeap::False => "false",
+                            OwnedHeap::True => "true",
+                            OwnedHeap::Unknown => "unknown",
+                        },
+                        params,
+                    })
+                    .collect();
+                (self.tcx.def_path_str(*def_id), units)
+            })
+            .collect();
+        sorted.serialize(serializer)
+    }
+}
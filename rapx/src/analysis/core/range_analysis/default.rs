@@ -17,6 +17,7 @@ use crate::{
         Analysis,
     },
     rap_debug, rap_info,
+    utils::{profile, scope},
 };
 
 use rustc_data_structures::fx::FxHashMap;
@@ -30,6 +31,7 @@ use std::{
     collections::{HashMap, HashSet},
     fmt::Debug,
     rc::Rc,
+    time::Instant,
 };
 
 use super::{PathConstraint, PathConstraintMap, RAResult, RAResultMap, RAVecResultMap};
@@ -151,10 +153,14 @@ where
     fn build_constraintgraph(&mut self, body_mut_ref: &'tcx Body<'tcx>, def_id: DefId) {
         let ssa_def_id = self.ssa_def_id.expect("SSA definition ID is not set");
         let essa_def_id = self.essa_def_id.expect("ESSA definition ID is not set");
+        let start = Instant::now();
         let mut cg: ConstraintGraph<'tcx, T> =
             ConstraintGraph::new(def_id, essa_def_id, ssa_def_id);
         cg.build_graph(body_mut_ref);
         cg.build_nuutila(false);
+        profile::record_time("range", def_id, start.elapsed());
+        profile::record_count("range", def_id, "vars", cg.vars.len());
+        profile::record_count("range", def_id, "ops", cg.oprs.len());
         // cg.rap_print_vars();
         // cg.rap_print_final_vars();
         let vars_map = cg.get_vars().clone();
@@ -176,7 +182,7 @@ where
             if matches!(self.tcx.def_kind(local_def_id), DefKind::Fn) {
                 let def_id = local_def_id.to_def_id();
 
-                if self.tcx.is_mir_available(def_id) {
+                if self.tcx.is_mir_available(def_id) && scope::needs(self.tcx, def_id) {
                     let mut body = self.tcx.optimized_mir(def_id).clone();
                     let body_mut_ref = unsafe { &mut *(&mut body as *mut Body<'tcx>) };
                     // Run SSA/ESSA passes
@@ -323,7 +329,7 @@ where
             if matches!(self.tcx.def_kind(local_def_id), DefKind::Fn) {
                 let def_id = local_def_id.to_def_id();
 
-                if self.tcx.is_mir_available(def_id) {
+                if self.tcx.is_mir_available(def_id) && scope::needs(self.tcx, def_id) {
                     let mut body = self.tcx.optimized_mir(def_id).clone();
                     let body_mut_ref = unsafe { &mut *(&mut body as *mut Body<'tcx>) };
 
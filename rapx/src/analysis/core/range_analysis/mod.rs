@@ -3,6 +3,7 @@
 #![allow(dead_code)]
 pub mod default;
 pub mod domain;
+pub mod serialize;
 use crate::{
     analysis::{
         core::range_analysis::domain::domain::{ConstConvert, IntervalArithmetic},
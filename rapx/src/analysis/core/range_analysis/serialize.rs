@@ -0,0 +1,132 @@
+use super::{domain::domain::IntervalArithmetic, PathConstraintMap, RAResultMap, Range};
+use rustc_middle::ty::TyCtxt;
+use serde::{ser::SerializeMap, Serialize};
+use std::collections::BTreeMap;
+
+/// This is a wrapper struct for serializing RAResultMap, keyed by the def paths of functions.
+/// The ranges of each function are keyed by the debug strings of places, e.g., `_1`.
+pub struct RAResultMapJson<'a, 'tcx, T: Clone + PartialOrd> {
+    pub result: &'a RAResultMap<'tcx, T>,
+    pub tcx: TyCtxt<'tcx>,
+}
+
+/// This is a wrapper struct for serializing PathConstraintMap, keyed by the def paths of functions.
+pub struct PathConstraintMapJson<'a, 'tcx> {
+    pub result: &'a PathConstraintMap<'tcx>,
+    pub tcx: TyCtxt<'tcx>,
+}
+
+#[derive(Serialize, Debug)]
+struct ConstraintInfo {
+    lhs: String,
+    op: String,
+    rhs: String,
+}
+
+#[derive(Serialize, Debug)]
+struct PathInfo<'a> {
+    path: &'a [usize],
+    constraints: Vec<ConstraintInfo>,
+}
+
+/// A bound of a range. The minimum and maximum values of the type are written as `"Min"` and
+/// `"Max"`, the same as the `Display` implementation of `Range`. The integers out of the range of
+/// `i64` and `u64`, which JSON numbers cannot hold, are written as decimal strings, e.g., the
+/// bounds of `u128` constants with `RangeAnalyzer::<i128>`.
+struct Bound<'a, T>(&'a T);
+
+impl<'a, T> Serialize for Bound<'a, T>
+where
+    T: IntervalArithmetic + Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        if *self.0 == T::min_value() {
+            serializer.serialize_str("Min")
+        } else if *self.0 == T::max_value() {
+            serializer.serialize_str("Max")
+        } else {
+            let decimal = self.0.to_string();
+            let out_of_json = decimal.parse::<i128>().is_ok()
+                && decimal.parse::<i64>().is_err()
+                && decimal.parse::<u64>().is_err();
+            if out_of_json {
+                serializer.serialize_str(&decimal)
+            } else {
+                self.0.serialize(serializer)
+            }
+        }
+    }
+}
+
+impl<T> Serialize for Range<T>
+where
+    T: IntervalArithmetic + Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(3))?;
+        map.serialize_entry("type", &self.rtype.to_string())?;
+        map.serialize_entry("lower", &Bound(&self.range.left.0))?;
+        map.serialize_entry("upper", &Bound(&self.range.right.0))?;
+        map.end()
+    }
+}
+
+impl<'a, 'tcx, T> Serialize for RAResultMapJson<'a, 'tcx, T>
+where
+    T: IntervalArithmetic + Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let sorted: BTreeMap<String, BTreeMap<String, &Range<T>>> = self
+            .result
+            .iter()
+            .map(|(def_id, ranges)| {
+                let ranges = ranges
+                    .iter()
+                    .map(|(place, range)| (format!("{:?}", place), range))
+                    .collect();
+                (self.tcx.def_path_str(*def_id), ranges)
+            })
+            .collect();
+        sorted.serialize(serializer)
+    }
+}
+
+impl<'a, 'tcx> Serialize for PathConstraintMapJson<'a, 'tcx> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let sorted: BTreeMap<String, Vec<PathInfo>> = self
+            .result
+            .iter()
+            .map(|(def_id, pc)| {
+                let mut paths: Vec<PathInfo> = pc
+                    .iter()
+                    .map(|(path, constraints)| PathInfo {
+                        path,
+                        constraints: constraints
+                            .iter()
+                            .map(|(lhs, rhs, op)| ConstraintInfo {
+                                lhs: format!("{:?}", lhs),
+                                op: format!("{:?}", op),
+                                rhs: format!("{:?}", rhs),
+                            })
+                            .collect(),
+                    })
+                    .collect();
+                paths.sort_by(|a, b| a.path.cmp(b.path));
+                (self.tcx.def_path_str(*def_id), paths)
+            })
+            .collect();
+        sorted.serialize(serializer)
+    }
+}
@@ -1,3 +1,4 @@
+pub mod context;
 pub mod core;
 pub mod opt;
 pub mod rcanary;
@@ -16,6 +17,6 @@ pub trait Analysis {
     /// Execute the analysis.
     fn run(&mut self);
 
-    /// Reset the analysis and cleanup the memory.
+    /// Reset the analysis and cleanup the memory; the analysis is as new and may run again.
     fn reset(&mut self);
 }
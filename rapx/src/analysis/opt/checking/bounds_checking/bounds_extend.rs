@@ -3,6 +3,8 @@ use once_cell::sync::OnceCell;
 use rustc_middle::ty::TyCtxt;
 use rustc_span::Span;
 
+use crate::utils::diagnostic::{print_report, Diagnostic, Rule};
+use crate::utils::source::get_fn_name_byid;
 use crate::{
     analysis::{
         core::dataflow::{graph::*, *},
@@ -82,6 +84,17 @@ impl OptCheck for BoundsExtendCheck {
 }
 
 fn report_extend_bug(graph: &Graph, span: Span) {
+    let diagnostic = Diagnostic::new(
+        Rule::BoundsChecking,
+        "Unnecessary bound checkings detected.",
+        span,
+    )
+    .function(get_fn_name_byid(&graph.def_id))
+    .def_id(graph.def_id)
+    .help("Manipulate memory directly.");
+    if diagnostic.is_baselined() {
+        return;
+    }
     let code_source = span_to_source_code(graph.span);
     let filename = span_to_filename(graph.span);
     let snippet = Snippet::source(&code_source)
@@ -98,5 +111,6 @@ fn report_extend_bug(graph: &Graph, span: Span) {
         .snippet(snippet)
         .footer(Level::Help.title("Manipulate memory directly."));
     let renderer = Renderer::styled();
-    println!("{}", renderer.render(message));
+    print_report(graph.def_id, renderer.render(message));
+    diagnostic.emit();
 }
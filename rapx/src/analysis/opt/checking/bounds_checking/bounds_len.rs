@@ -7,6 +7,8 @@ use rustc_hir::{intravisit, Expr, ExprKind};
 use rustc_middle::{mir::Local, ty::TyCtxt};
 use rustc_span::Span;
 
+use crate::utils::diagnostic::{print_report, Diagnostic, Rule};
+use crate::utils::source::get_fn_name_byid;
 use crate::{
     analysis::{
         core::dataflow::{graph::*, *},
@@ -283,8 +285,26 @@ fn find_downside_index_node(graph: &Graph, node_idx: Local) -> Vec<Local> {
     index_node_idxs
 }
 
-fn report_upperbound_bug(graph: &Graph, upperbound_node_idx: Local, index_record: &Vec<Local>) {
+fn report_upperbound_bug(graph: &Graph, upperbound_node_idx: Local, index_record: &[Local]) {
     let upperbound_span = graph.nodes[upperbound_node_idx].span;
+    let diagnostics: Vec<Diagnostic> = index_record
+        .iter()
+        .map(|node_idx| {
+            Diagnostic::new(
+                Rule::BoundsChecking,
+                "Unnecessary bounds checkings detected.",
+                graph.nodes[*node_idx].span,
+            )
+            .function(get_fn_name_byid(&graph.def_id))
+            .def_id(graph.def_id)
+            .related(upperbound_span, "Index is upperbounded.")
+            .help("Use unsafe APIs instead.")
+        })
+        .filter(|diagnostic| !diagnostic.is_baselined())
+        .collect();
+    if diagnostics.is_empty() {
+        return;
+    }
     let code_source = span_to_source_code(graph.span);
     let filename = span_to_filename(upperbound_span);
     let mut snippet = Snippet::source(&code_source)
@@ -296,11 +316,10 @@ fn report_upperbound_bug(graph: &Graph, upperbound_node_idx: Local, index_record
                 .span(relative_pos_range(graph.span, upperbound_span))
                 .label("Index is upperbounded."),
         );
-    for node_idx in index_record {
-        let index_span = graph.nodes[*node_idx].span;
+    for diagnostic in diagnostics.iter() {
         snippet = snippet.annotation(
             Level::Error
-                .span(relative_pos_range(graph.span, index_span))
+                .span(relative_pos_range(graph.span, diagnostic.span))
                 .label("Checked here."),
         );
     }
@@ -309,5 +328,8 @@ fn report_upperbound_bug(graph: &Graph, upperbound_node_idx: Local, index_record
         .snippet(snippet)
         .footer(Level::Help.title("Use unsafe APIs instead."));
     let renderer = Renderer::styled();
-    println!("{}", renderer.render(message));
+    print_report(graph.def_id, renderer.render(message));
+    for diagnostic in diagnostics {
+        diagnostic.emit();
+    }
 }
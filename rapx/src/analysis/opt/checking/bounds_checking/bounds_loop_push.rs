@@ -7,10 +7,12 @@ use rustc_span::Span;
 
 use crate::analysis::core::dataflow::graph::Graph;
 use crate::analysis::utils::def_path::DefPath;
+use crate::utils::diagnostic::{print_report, Diagnostic, Rule};
 use crate::utils::log::{
     relative_pos_range, span_to_filename, span_to_first_line, span_to_line_number,
     span_to_source_code, span_to_trimmed_span,
 };
+use crate::utils::source::get_fn_name_byid;
 use annotate_snippets::{Level, Renderer, Snippet};
 
 use super::super::super::LEVEL;
@@ -109,9 +111,9 @@ impl OptCheck for BoundsLoopPushCheck {
         }
     }
 
-    fn report(&self, _: &Graph) {
+    fn report(&self, graph: &Graph) {
         for (loop_span, push_record) in self.record.iter() {
-            report_loop_push_bug(*loop_span, push_record);
+            report_loop_push_bug(graph, *loop_span, push_record);
         }
     }
 
@@ -120,7 +122,27 @@ impl OptCheck for BoundsLoopPushCheck {
     }
 }
 
-fn report_loop_push_bug(loop_span: Span, push_record: &Vec<Span>) {
+fn report_loop_push_bug(graph: &Graph, loop_span: Span, push_record: &[Span]) {
+    let diagnostics: Vec<Diagnostic> = push_record
+        .iter()
+        .map(|push_span| {
+            Diagnostic::new(
+                Rule::BoundsChecking,
+                "Unnecessary bounds checkings detected.",
+                *push_span,
+            )
+            .function(get_fn_name_byid(&graph.def_id))
+            .def_id(graph.def_id)
+            .related(
+                span_to_trimmed_span(span_to_first_line(loop_span)),
+                "A loop operation.",
+            )
+        })
+        .filter(|diagnostic| !diagnostic.is_baselined())
+        .collect();
+    if diagnostics.is_empty() {
+        return;
+    }
     let code_source = span_to_source_code(loop_span);
     let filename = span_to_filename(loop_span);
     let mut snippet = Snippet::source(&code_source)
@@ -135,10 +157,10 @@ fn report_loop_push_bug(loop_span: Span, push_record: &Vec<Span>) {
                 ))
                 .label("A loop operation."),
         );
-    for push_span in push_record {
+    for diagnostic in diagnostics.iter() {
         snippet = snippet.annotation(
             Level::Error
-                .span(relative_pos_range(loop_span, *push_span))
+                .span(relative_pos_range(loop_span, diagnostic.span))
                 .label("Push happens here."),
         );
     }
@@ -146,5 +168,8 @@ fn report_loop_push_bug(loop_span: Span, push_record: &Vec<Span>) {
         .title("Unnecessary bounds checkings detected")
         .snippet(snippet);
     let renderer = Renderer::styled();
-    println!("{}", renderer.render(message));
+    print_report(graph.def_id, renderer.render(message));
+    for diagnostic in diagnostics {
+        diagnostic.emit();
+    }
 }
@@ -5,6 +5,8 @@ pub mod vec_encoding;
 
 use std::collections::HashSet;
 
+use crate::utils::diagnostic::{print_report, Diagnostic, Rule};
+use crate::utils::source::get_fn_name_byid;
 use crate::{
     analysis::{
         core::dataflow::{graph::*, *},
@@ -62,6 +64,17 @@ impl OptCheck for EncodingCheck {
 }
 
 fn report_encoding_bug(graph: &Graph, span: Span) {
+    let diagnostic = Diagnostic::new(
+        Rule::EncodingChecking,
+        "Unnecessary encoding checkings detected.",
+        span,
+    )
+    .function(get_fn_name_byid(&graph.def_id))
+    .def_id(graph.def_id)
+    .help("Use unsafe APIs.");
+    if diagnostic.is_baselined() {
+        return;
+    }
     let code_source = span_to_source_code(graph.span);
     let filename = span_to_filename(graph.span);
     let snippet = Snippet::source(&code_source)
@@ -78,7 +91,8 @@ fn report_encoding_bug(graph: &Graph, span: Span) {
         .snippet(snippet)
         .footer(Level::Help.title("Use unsafe APIs."));
     let renderer = Renderer::styled();
-    println!("{}", renderer.render(message));
+    print_report(graph.def_id, renderer.render(message));
+    diagnostic.emit();
 }
 
 // Warning: WE APPROXIMATELY VIEW CONST U8s AS SAFE INPUT
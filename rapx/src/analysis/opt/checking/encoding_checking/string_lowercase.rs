@@ -10,6 +10,8 @@ use rustc_span::Span;
 use crate::analysis::core::dataflow::graph::Graph;
 use crate::analysis::opt::OptCheck;
 use crate::analysis::utils::def_path::DefPath;
+use crate::utils::diagnostic::{print_report, Diagnostic, Rule};
+use crate::utils::source::get_fn_name_byid;
 
 use crate::utils::log::{
     relative_pos_range, span_to_filename, span_to_line_number, span_to_source_code,
@@ -84,6 +86,17 @@ impl OptCheck for StringLowercaseCheck {
 }
 
 fn report_string_ascii_bug(graph: &Graph, contains_span: Span) {
+    let diagnostic = Diagnostic::new(
+        Rule::EncodingChecking,
+        "Unnecessary encoding checkings detected.",
+        contains_span,
+    )
+    .function(get_fn_name_byid(&graph.def_id))
+    .def_id(graph.def_id)
+    .help("Use to_ascii_lowercase istead.");
+    if diagnostic.is_baselined() {
+        return;
+    }
     let code_source = span_to_source_code(graph.span);
     let filename = span_to_filename(graph.span);
     let snippet = Snippet::source(&code_source)
@@ -100,5 +113,6 @@ fn report_string_ascii_bug(graph: &Graph, contains_span: Span) {
         .snippet(snippet)
         .footer(Level::Help.title("Use to_ascii_lowercase istead."));
     let renderer = Renderer::styled();
-    println!("{}", renderer.render(message));
+    print_report(graph.def_id, renderer.render(message));
+    diagnostic.emit();
 }
@@ -6,6 +6,8 @@ use rustc_middle::{mir::Local, ty::TyCtxt};
 use rustc_span::Span;
 
 use super::value_is_from_const;
+use crate::utils::diagnostic::{print_report, Diagnostic, Rule};
+use crate::utils::source::get_fn_name_byid;
 use crate::{
     analysis::{
         core::dataflow::{graph::*, *},
@@ -108,17 +110,34 @@ impl OptCheck for StringPushCheck {
     }
 }
 
-fn report_string_push_bug(graph: &Graph, spans: &Vec<Span>) {
+fn report_string_push_bug(graph: &Graph, spans: &[Span]) {
+    let diagnostics: Vec<Diagnostic> = spans
+        .iter()
+        .map(|span| {
+            Diagnostic::new(
+                Rule::EncodingChecking,
+                "Unnecessary encoding checkings detected.",
+                *span,
+            )
+            .function(get_fn_name_byid(&graph.def_id))
+            .def_id(graph.def_id)
+            .help("Use unsafe APIs instead.")
+        })
+        .filter(|diagnostic| !diagnostic.is_baselined())
+        .collect();
+    if diagnostics.is_empty() {
+        return;
+    }
     let code_source = span_to_source_code(graph.span);
     let filename = span_to_filename(graph.span);
     let mut snippet = Snippet::source(&code_source)
         .line_start(span_to_line_number(graph.span))
         .origin(&filename)
         .fold(true);
-    for span in spans.iter() {
+    for diagnostic in diagnostics.iter() {
         snippet = snippet.annotation(
             Level::Error
-                .span(relative_pos_range(graph.span, *span))
+                .span(relative_pos_range(graph.span, diagnostic.span))
                 .label("Checked here."),
         )
     }
@@ -127,5 +146,8 @@ fn report_string_push_bug(graph: &Graph, spans: &Vec<Span>) {
         .snippet(snippet)
         .footer(Level::Help.title("Use unsafe APIs instead."));
     let renderer = Renderer::styled();
-    println!("{}", renderer.render(message));
+    print_report(graph.def_id, renderer.render(message));
+    for diagnostic in diagnostics {
+        diagnostic.emit();
+    }
 }
@@ -2,6 +2,8 @@ use annotate_snippets::{Level, Renderer, Snippet};
 
 use once_cell::sync::OnceCell;
 
+use crate::utils::diagnostic::{print_report, Diagnostic, Rule};
+use crate::utils::source::get_fn_name_byid;
 use crate::{
     analysis::{
         core::dataflow::{graph::*, *},
@@ -83,6 +85,17 @@ impl OptCheck for LocalSetCheck {
 }
 
 fn report_local_set(graph: &Graph, span: Span) {
+    let diagnostic = Diagnostic::new(
+        Rule::CollectionInitialization,
+        "Unnecessary data collection initialization detected.",
+        span,
+    )
+    .function(get_fn_name_byid(&graph.def_id))
+    .def_id(graph.def_id)
+    .help("Move it into parameter list and use hash table to save allocation.");
+    if diagnostic.is_baselined() {
+        return;
+    }
     let code_source = span_to_source_code(graph.span);
     let filename = span_to_filename(span);
     let snippet = Snippet::source(&code_source)
@@ -101,5 +114,6 @@ fn report_local_set(graph: &Graph, span: Span) {
             Level::Help.title("Move it into parameter list and use hash table to save allocation."),
         );
     let renderer = Renderer::styled();
-    println!("{}", renderer.render(message));
+    print_report(graph.def_id, renderer.render(message));
+    diagnostic.emit();
 }
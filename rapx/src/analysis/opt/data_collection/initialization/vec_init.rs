@@ -6,6 +6,8 @@ use rustc_hir::def_id::DefId;
 use rustc_middle::ty::TyCtxt;
 use rustc_span::Span;
 
+use crate::utils::diagnostic::{print_report, Diagnostic, Rule};
+use crate::utils::source::get_fn_name_byid;
 use crate::{
     analysis::{
         core::dataflow::{graph::*, *},
@@ -67,6 +69,17 @@ impl OptCheck for VecInitCheck {
 }
 
 fn report_vec_init(graph: &Graph, span: Span) {
+    let diagnostic = Diagnostic::new(
+        Rule::CollectionInitialization,
+        "Unnecessary data collection initialization detected.",
+        span,
+    )
+    .function(get_fn_name_byid(&graph.def_id))
+    .def_id(graph.def_id)
+    .help("Use unsafe APIs to skip initialization.");
+    if diagnostic.is_baselined() {
+        return;
+    }
     let code_source = span_to_source_code(graph.span);
     let filename = span_to_filename(span);
     let snippet = Snippet::source(&code_source)
@@ -83,5 +96,6 @@ fn report_vec_init(graph: &Graph, span: Span) {
         .snippet(snippet)
         .footer(Level::Help.title("Use unsafe APIs to skip initialization."));
     let renderer = Renderer::styled();
-    println!("{}", renderer.render(message));
+    print_report(graph.def_id, renderer.render(message));
+    diagnostic.emit();
 }
@@ -2,6 +2,8 @@ use once_cell::sync::OnceCell;
 
 use rustc_middle::ty::TyCtxt;
 
+use crate::utils::diagnostic::{print_report, Diagnostic, Rule};
+use crate::utils::source::get_fn_name_byid;
 use crate::{
     analysis::{
         core::dataflow::{graph::Graph, *},
@@ -93,6 +95,17 @@ impl OptCheck for FlattenCollectCheck {
 }
 
 fn report_flatten_collect(graph: &Graph, span: Span) {
+    let diagnostic = Diagnostic::new(
+        Rule::Reallocation,
+        "Data collection inefficiency detected: flatten then collect.",
+        span,
+    )
+    .function(get_fn_name_byid(&graph.def_id))
+    .def_id(graph.def_id)
+    .help("Use extend manually.");
+    if diagnostic.is_baselined() {
+        return;
+    }
     let code_source = span_to_source_code(graph.span);
     let filename = span_to_filename(span);
     let snippet: Snippet<'_> = Snippet::source(&code_source)
@@ -110,5 +123,6 @@ fn report_flatten_collect(graph: &Graph, span: Span) {
         .snippet(snippet)
         .footer(Level::Help.title("Use extend manually."));
     let renderer = Renderer::styled();
-    println!("{}", renderer.render(message));
+    print_report(graph.def_id, renderer.render(message));
+    diagnostic.emit();
 }
@@ -1,5 +1,7 @@
 use std::collections::HashSet;
 
+use crate::utils::diagnostic::{print_report, Diagnostic, Rule};
+use crate::utils::source::get_fn_name_byid;
 use crate::{
     analysis::{
         core::dataflow::{graph::*, *},
@@ -113,6 +115,18 @@ impl OptCheck for UnreservedHashCheck {
 }
 
 fn report_unreserved_hash_bug(graph: &Graph, hash_span: Span, insert_span: Span) {
+    let diagnostic = Diagnostic::new(
+        Rule::Reallocation,
+        "Improper data collection detected: space unreserved.",
+        hash_span,
+    )
+    .function(get_fn_name_byid(&graph.def_id))
+    .def_id(graph.def_id)
+    .related(insert_span, "Insertion happens here.")
+    .help("Reserve enough space.");
+    if diagnostic.is_baselined() {
+        return;
+    }
     let code_source = span_to_source_code(graph.span);
     let filename = span_to_filename(hash_span);
     let snippet: Snippet<'_> = Snippet::source(&code_source)
@@ -134,5 +148,6 @@ fn report_unreserved_hash_bug(graph: &Graph, hash_span: Span, insert_span: Span)
         .snippet(snippet)
         .footer(Level::Help.title("Reserve enough space."));
     let renderer = Renderer::styled();
-    println!("{}", renderer.render(message));
+    print_report(graph.def_id, renderer.render(message));
+    diagnostic.emit();
 }
@@ -1,5 +1,7 @@
 use std::collections::HashSet;
 
+use crate::utils::diagnostic::{print_report, Diagnostic, Rule};
+use crate::utils::source::get_fn_name_byid;
 use crate::{
     analysis::{
         core::dataflow::{graph::*, *},
@@ -193,6 +195,17 @@ impl OptCheck for UnreservedVecCheck {
 }
 
 fn report_unreserved_vec_bug(graph: &Graph, span: Span) {
+    let diagnostic = Diagnostic::new(
+        Rule::Reallocation,
+        "Improper data collection detected: space unreserved.",
+        span,
+    )
+    .function(get_fn_name_byid(&graph.def_id))
+    .def_id(graph.def_id)
+    .help("Reserve enough space.");
+    if diagnostic.is_baselined() {
+        return;
+    }
     let code_source = span_to_source_code(graph.span);
     let filename = span_to_filename(span);
     let snippet: Snippet<'_> = Snippet::source(&code_source)
@@ -209,5 +222,6 @@ fn report_unreserved_vec_bug(graph: &Graph, span: Span) {
         .snippet(snippet)
         .footer(Level::Help.title("Reserve enough space."));
     let renderer = Renderer::styled();
-    println!("{}", renderer.render(message));
+    print_report(graph.def_id, renderer.render(message));
+    diagnostic.emit();
 }
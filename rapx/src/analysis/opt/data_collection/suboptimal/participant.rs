@@ -2,6 +2,8 @@ use annotate_snippets::{Level, Renderer, Snippet};
 
 use once_cell::sync::OnceCell;
 
+use crate::utils::diagnostic::{print_report, Diagnostic, Rule};
+use crate::utils::source::get_fn_name_byid;
 use crate::{
     analysis::{
         core::dataflow::{graph::*, *},
@@ -80,6 +82,19 @@ impl OptCheck for ParticipantCheck {
 }
 
 fn report_participant(graph: &Graph, span: Span) {
+    let diagnostic = Diagnostic::new(
+        Rule::SuboptimalCollection,
+        "Suboptimal data collection detected.",
+        span,
+    )
+    .function(get_fn_name_byid(&graph.def_id))
+    .def_id(graph.def_id)
+    .help(
+        "Use faster data collection or hash operators instead. Static container is also a choice",
+    );
+    if diagnostic.is_baselined() {
+        return;
+    }
     let code_source = span_to_source_code(graph.span);
     let filename = span_to_filename(span);
     let snippet = Snippet::source(&code_source)
@@ -96,5 +111,6 @@ fn report_participant(graph: &Graph, span: Span) {
         .snippet(snippet)
         .footer(Level::Help.title("Use faster data collection or hash operators instead. Static container is also a choice"));
     let renderer = Renderer::styled();
-    println!("{}", renderer.render(message));
+    print_report(graph.def_id, renderer.render(message));
+    diagnostic.emit();
 }
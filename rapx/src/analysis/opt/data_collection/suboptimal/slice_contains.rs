@@ -2,6 +2,8 @@ use annotate_snippets::{Level, Renderer, Snippet};
 
 use once_cell::sync::OnceCell;
 
+use crate::utils::diagnostic::{print_report, Diagnostic, Rule};
+use crate::utils::source::get_fn_name_byid;
 use crate::{
     analysis::{core::dataflow::graph::*, opt::OptCheck, utils::def_path::DefPath},
     utils::log::{relative_pos_range, span_to_filename, span_to_line_number, span_to_source_code},
@@ -79,6 +81,17 @@ impl OptCheck for SliceContainsCheck {
 }
 
 fn report_slice_contains_bug(graph: &Graph, contains_span: Span) {
+    let diagnostic = Diagnostic::new(
+        Rule::SuboptimalCollection,
+        "Improper data collection detected: slice contains.",
+        contains_span,
+    )
+    .function(get_fn_name_byid(&graph.def_id))
+    .def_id(graph.def_id)
+    .help("Use Set instead of Slice.");
+    if diagnostic.is_baselined() {
+        return;
+    }
     let code_source = span_to_source_code(graph.span);
     let filename = span_to_filename(graph.span);
     let snippet = Snippet::source(&code_source)
@@ -95,5 +108,6 @@ fn report_slice_contains_bug(graph: &Graph, contains_span: Span) {
         .snippet(snippet)
         .footer(Level::Help.title("Use Set instead of Slice."));
     let renderer = Renderer::styled();
-    println!("{}", renderer.render(message));
+    print_report(graph.def_id, renderer.render(message));
+    diagnostic.emit();
 }
@@ -2,6 +2,8 @@ use annotate_snippets::{Level, Renderer, Snippet};
 
 use once_cell::sync::OnceCell;
 
+use crate::utils::diagnostic::{print_report, Diagnostic, Rule};
+use crate::utils::source::get_fn_name_byid;
 use crate::{
     analysis::{
         core::dataflow::{graph::*, *},
@@ -88,6 +90,17 @@ impl OptCheck for VecRemoveCheck {
 }
 
 fn report_vec_remove_bug(graph: &Graph, span: Span) {
+    let diagnostic = Diagnostic::new(
+        Rule::SuboptimalCollection,
+        "Improper data collection detected: Vec increasement / decreasement.",
+        span,
+    )
+    .function(get_fn_name_byid(&graph.def_id))
+    .def_id(graph.def_id)
+    .help("Use VecQueue instead of Vec.");
+    if diagnostic.is_baselined() {
+        return;
+    }
     let code_source = span_to_source_code(graph.span);
     let filename = span_to_filename(graph.span);
     let snippet = Snippet::source(&code_source)
@@ -104,5 +117,6 @@ fn report_vec_remove_bug(graph: &Graph, span: Span) {
         .snippet(snippet)
         .footer(Level::Help.title("Use VecQueue instead of Vec."));
     let renderer = Renderer::styled();
-    println!("{}", renderer.render(message));
+    print_report(graph.def_id, renderer.render(message));
+    diagnostic.emit();
 }
@@ -8,9 +8,11 @@ use rustc_span::Span;
 use crate::analysis::core::dataflow::graph::Graph;
 use crate::analysis::opt::OptCheck;
 use crate::analysis::utils::def_path::DefPath;
+use crate::utils::diagnostic::{print_report, Diagnostic, Rule};
 use crate::utils::log::{
     relative_pos_range, span_to_filename, span_to_line_number, span_to_source_code,
 };
+use crate::utils::source::get_fn_name_byid;
 use annotate_snippets::{Level, Renderer, Snippet};
 
 static DEFPATHS: OnceCell<DefPaths> = OnceCell::new();
@@ -91,7 +93,7 @@ impl OptCheck for NextIteratorCheck {
     }
 
     fn report(&self, graph: &Graph) {
-        report_next_iterator_bug(&self.next_record, &self.chain_record, graph.span);
+        report_next_iterator_bug(&self.next_record, &self.chain_record, graph);
     }
 
     fn cnt(&self) -> usize {
@@ -99,23 +101,43 @@ impl OptCheck for NextIteratorCheck {
     }
 }
 
-fn report_next_iterator_bug(next_record: &Vec<Span>, chain_record: &Vec<Span>, graph_span: Span) {
+fn report_next_iterator_bug(next_record: &[Span], chain_record: &[Span], graph: &Graph) {
+    let diagnostics: Vec<Diagnostic> = next_record
+        .iter()
+        .chain(chain_record.iter())
+        .map(|span| {
+            Diagnostic::new(
+                Rule::InefficientIterator,
+                "Inefficient iterators detected.",
+                *span,
+            )
+            .function(get_fn_name_byid(&graph.def_id))
+            .def_id(graph.def_id)
+            .help("Use chunk iterators.")
+        })
+        .filter(|diagnostic| !diagnostic.is_baselined())
+        .collect();
+    if diagnostics.is_empty() {
+        return;
+    }
+    let graph_span = graph.span;
     let code_source = span_to_source_code(graph_span);
     let filename = span_to_filename(graph_span);
     let mut snippet = Snippet::source(&code_source)
         .line_start(span_to_line_number(graph_span))
         .origin(&filename)
         .fold(true);
-    for next_span in next_record {
-        snippet = snippet.annotation(Level::Error.span(relative_pos_range(graph_span, *next_span)))
-    }
-    for chain_span in chain_record {
-        snippet = snippet.annotation(Level::Error.span(relative_pos_range(graph_span, *chain_span)))
+    for diagnostic in diagnostics.iter() {
+        snippet =
+            snippet.annotation(Level::Error.span(relative_pos_range(graph_span, diagnostic.span)))
     }
     let message = Level::Warning
         .title("Inefficient iterators detected")
         .snippet(snippet)
         .footer(Level::Help.title("Use chunk iterators."));
     let renderer = Renderer::styled();
-    println!("{}", renderer.render(message));
+    print_report(graph.def_id, renderer.render(message));
+    for diagnostic in diagnostics {
+        diagnostic.emit();
+    }
 }
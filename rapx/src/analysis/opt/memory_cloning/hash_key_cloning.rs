@@ -1,3 +1,5 @@
+use crate::utils::diagnostic::{print_report, Diagnostic, Rule};
+use crate::utils::source::get_fn_name_byid;
 use crate::{
     analysis::{
         core::dataflow::{graph::*, *},
@@ -126,6 +128,18 @@ fn find_hash_new_node(graph: &Graph, node: &GraphNode) -> Option<Local> {
 }
 
 fn report_hash_key_cloning(graph: &Graph, clone_span: Span, insert_span: Span) {
+    let diagnostic = Diagnostic::new(
+        Rule::MemoryCloning,
+        "Unnecessary memory cloning detected.",
+        clone_span,
+    )
+    .function(get_fn_name_byid(&graph.def_id))
+    .def_id(graph.def_id)
+    .related(insert_span, "Used here.")
+    .help("Use borrowings as keys.");
+    if diagnostic.is_baselined() {
+        return;
+    }
     let code_source = span_to_source_code(graph.span);
     let filename = span_to_filename(clone_span);
     let snippet = Snippet::source(&code_source)
@@ -147,7 +161,8 @@ fn report_hash_key_cloning(graph: &Graph, clone_span: Span, insert_span: Span) {
         .snippet(snippet)
         .footer(Level::Help.title("Use borrowings as keys."));
     let renderer = Renderer::styled();
-    println!("{}", renderer.render(message));
+    print_report(graph.def_id, renderer.render(message));
+    diagnostic.emit();
 }
 
 pub struct HashKeyCloningCheck {
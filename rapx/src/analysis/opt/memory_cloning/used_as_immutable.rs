@@ -1,3 +1,5 @@
+use crate::utils::diagnostic::{print_report, Diagnostic, Rule};
+use crate::utils::source::get_fn_name_byid;
 use crate::{
     analysis::{
         core::dataflow::{graph::*, *},
@@ -155,6 +157,18 @@ impl OptCheck for UsedAsImmutableCheck {
 }
 
 fn report_used_as_immutable(graph: &Graph, clone_span: Span, use_span: Span) {
+    let diagnostic = Diagnostic::new(
+        Rule::MemoryCloning,
+        "Unnecessary memory cloning detected.",
+        clone_span,
+    )
+    .function(get_fn_name_byid(&graph.def_id))
+    .def_id(graph.def_id)
+    .related(use_span, "Used here.")
+    .help("Use borrowings instead.");
+    if diagnostic.is_baselined() {
+        return;
+    }
     let code_source = span_to_source_code(graph.span);
     let filename = span_to_filename(clone_span);
     let snippet = Snippet::source(&code_source)
@@ -176,5 +190,6 @@ fn report_used_as_immutable(graph: &Graph, clone_span: Span, use_span: Span) {
         .snippet(snippet)
         .footer(Level::Help.title("Use borrowings instead."));
     let renderer = Renderer::styled();
-    println!("{}", renderer.render(message));
+    print_report(graph.def_id, renderer.render(message));
+    diagnostic.emit();
 }
@@ -6,9 +6,12 @@ pub mod memory_cloning;
 use rustc_middle::ty::TyCtxt;
 
 use crate::rap_warn;
+use crate::utils::diagnostic::{suppression::allowed_rules, Rule};
 use crate::utils::log::span_to_source_code;
+use crate::utils::scope;
 
-use super::core::dataflow::{default::DataFlowAnalyzer, graph::Graph};
+use super::context::AnalysisContext;
+use super::core::dataflow::graph::Graph;
 use checking::bounds_checking::BoundsCheck;
 use checking::encoding_checking::EncodingCheck;
 use data_collection::initialization::InitializationCheck;
@@ -51,9 +54,8 @@ impl<'tcx> Opt<'tcx> {
         false
     }
 
-    pub fn start(&mut self) {
-        let mut dataflow = DataFlowAnalyzer::new(self.tcx, false);
-        dataflow.build_graphs();
+    pub fn start(&mut self, ctx: &AnalysisContext<'tcx>) {
+        let dataflow = ctx.dataflow();
         {
             let mut no_std = NO_STD.lock().unwrap();
             *no_std = !self.has_crate("std");
@@ -67,53 +69,79 @@ impl<'tcx> Opt<'tcx> {
 
         let mut statistics = vec![0 as usize; 6];
 
-        dataflow.graphs.iter().for_each(|(_, graph)| {
-            let mut bounds_check = BoundsCheck::new();
-            bounds_check.check(graph, &self.tcx);
-            statistics[0] += bounds_check.cnt();
-
-            if self.level > 0 {
-                bounds_check.report(graph);
+        // the callees of the functions in the scope are only built for the dataflow
+        let graphs: Vec<_> = dataflow
+            .graphs
+            .values()
+            .filter(|graph| scope::contains(self.tcx, graph.def_id))
+            .collect();
+        graphs.iter().for_each(|graph| {
+            // the checkers of the rules allowed by `rapx::allow` attributes are skipped
+            let allowed = allowed_rules(self.tcx, graph.def_id);
+
+            if !allowed.contains(&Rule::BoundsChecking) {
+                let mut bounds_check = BoundsCheck::new();
+                bounds_check.check(graph, &self.tcx);
+                statistics[0] += bounds_check.cnt();
+                if self.level > 0 {
+                    bounds_check.report(graph);
+                }
             }
 
             let no_std = NO_STD.lock().unwrap();
             if !*no_std {
-                let mut encoding_check = EncodingCheck::new();
-                encoding_check.check(graph, &self.tcx);
-                statistics[1] += encoding_check.cnt();
-
-                let mut suboptimal_check = SuboptimalCheck::new();
-                suboptimal_check.check(graph, &self.tcx);
-                statistics[2] += suboptimal_check.cnt();
+                if !allowed.contains(&Rule::EncodingChecking) {
+                    let mut encoding_check = EncodingCheck::new();
+                    encoding_check.check(graph, &self.tcx);
+                    statistics[1] += encoding_check.cnt();
+                    if self.level > 0 {
+                        encoding_check.report(graph);
+                    }
+                }
 
-                let mut initialization_check = InitializationCheck::new();
-                initialization_check.check(graph, &self.tcx);
-                statistics[3] += initialization_check.cnt();
+                if !allowed.contains(&Rule::SuboptimalCollection) {
+                    let mut suboptimal_check = SuboptimalCheck::new();
+                    suboptimal_check.check(graph, &self.tcx);
+                    statistics[2] += suboptimal_check.cnt();
+                    if self.level > 0 {
+                        suboptimal_check.report(graph);
+                    }
+                }
 
-                let mut reservation_check = ReservationCheck::new();
-                reservation_check.check(graph, &self.tcx);
-                statistics[4] += reservation_check.cnt();
+                if !allowed.contains(&Rule::CollectionInitialization) {
+                    let mut initialization_check = InitializationCheck::new();
+                    initialization_check.check(graph, &self.tcx);
+                    statistics[3] += initialization_check.cnt();
+                    if self.level > 0 {
+                        initialization_check.report(graph);
+                    }
+                }
 
-                let mut used_as_immutable_check = UsedAsImmutableCheck::new();
-                used_as_immutable_check.check(graph, &self.tcx);
-                statistics[5] += used_as_immutable_check.cnt();
+                if !allowed.contains(&Rule::Reallocation) {
+                    let mut reservation_check = ReservationCheck::new();
+                    reservation_check.check(graph, &self.tcx);
+                    statistics[4] += reservation_check.cnt();
+                    if self.level > 0 {
+                        reservation_check.report(graph);
+                    }
+                }
 
-                if self.level > 0 {
-                    encoding_check.report(graph);
-                    suboptimal_check.report(graph);
-                    initialization_check.report(graph);
-                    reservation_check.report(graph);
-                    used_as_immutable_check.report(graph);
+                if !allowed.contains(&Rule::MemoryCloning) {
+                    let mut used_as_immutable_check = UsedAsImmutableCheck::new();
+                    used_as_immutable_check.check(graph, &self.tcx);
+                    statistics[5] += used_as_immutable_check.cnt();
+                    if self.level > 0 {
+                        used_as_immutable_check.report(graph);
+                    }
                 }
             }
         });
 
         let bug_cnt: usize = statistics.iter().sum();
-        let func_cnt: usize = dataflow.graphs.iter().count();
-        let line_cnt: usize = dataflow
-            .graphs
+        let func_cnt: usize = graphs.len();
+        let line_cnt: usize = graphs
             .iter()
-            .map(|(_, graph)| span_to_source_code(graph.span).lines().count())
+            .map(|graph| span_to_source_code(graph.span).lines().count())
             .sum();
         if bug_cnt > 0 {
             rap_warn!("Potential optimizations detected.");
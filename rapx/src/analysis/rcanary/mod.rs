@@ -1,10 +1,10 @@
 pub mod ranalyzer;
 
-use crate::analysis::{
-    core::ownedheap_analysis::{default::OwnedHeapAnalyzer, OHAResultMap, OwnedHeapAnalysis},
-    Analysis,
+use crate::analysis::{context::AnalysisContext, core::ownedheap_analysis::OHAResultMap};
+use ranalyzer::{
+    inter_visitor::OwnershipSummary, FlowAnalysis, IcxSliceFroBlock, IntraFlowContext, MirGraph,
 };
-use ranalyzer::{FlowAnalysis, IcxSliceFroBlock, IntraFlowContext, MirGraph};
+use rustc_hir::def_id::DefId;
 use rustc_middle::ty::TyCtxt;
 use std::collections::HashMap;
 
@@ -14,6 +14,9 @@ pub struct rCanary<'tcx> {
     tcx: TyCtxt<'tcx>,
     adt_owner: OHAResultMap,
     mir_graph: MirGraph,
+    summaries: HashMap<DefId, OwnershipSummary<'tcx>>,
+    /// The functions in the bottom-up order of the call graph.
+    bottom_up_order: Vec<DefId>,
 }
 
 impl<'tcx> rCanary<'tcx> {
@@ -22,14 +25,14 @@ impl<'tcx> rCanary<'tcx> {
             tcx,
             adt_owner: adt_owner,
             mir_graph: HashMap::default(),
+            summaries: HashMap::default(),
+            bottom_up_order: Vec::new(),
         }
     }
 
-    pub fn start(&mut self) {
-        let mut heap = OwnedHeapAnalyzer::new(self.tcx);
-        heap.run();
-        let adt_owner = heap.get_all_items();
-        let rcx_boxed = Box::new(rCanary::new(self.tcx, adt_owner));
+    pub fn start(&mut self, ctx: &AnalysisContext<'tcx>) {
+        self.bottom_up_order = ctx.callgraph().bottom_up_order();
+        let rcx_boxed = Box::new(self.clone());
         let rcx = Box::leak(rcx_boxed);
         FlowAnalysis::new(rcx).start();
     }
@@ -46,6 +49,10 @@ impl<'tcx> rCanary<'tcx> {
         &mut self.adt_owner
     }
 
+    pub fn bottom_up_order(&self) -> &[DefId] {
+        &self.bottom_up_order
+    }
+
     pub fn mir_graph(&self) -> &MirGraph {
         &self.mir_graph
     }
@@ -53,6 +60,14 @@ impl<'tcx> rCanary<'tcx> {
     pub fn mir_graph_mut(&mut self) -> &mut MirGraph {
         &mut self.mir_graph
     }
+
+    pub fn summaries(&self) -> &HashMap<DefId, OwnershipSummary<'tcx>> {
+        &self.summaries
+    }
+
+    pub fn summaries_mut(&mut self) -> &mut HashMap<DefId, OwnershipSummary<'tcx>> {
+        &mut self.summaries
+    }
 }
 
 pub trait Tcx<'tcx, 'o, 'a> {
@@ -28,6 +28,8 @@ pub struct Graph {
     e: Edges,
     pre: Edges,
     topo: ToPo,
+    // the MIR basic block of each node, which differ if the loops are unrolled
+    blocks: Vec<usize>,
 }
 
 impl Default for Graph {
@@ -36,6 +38,7 @@ impl Default for Graph {
             e: Vec::default(),
             pre: Vec::default(),
             topo: Vec::default(),
+            blocks: Vec::default(),
         }
     }
 }
@@ -46,9 +49,14 @@ impl Graph {
             e: vec![Vec::new(); len],
             pre: vec![Vec::new(); len],
             topo: Vec::new(),
+            blocks: (0..len).collect(),
         }
     }
 
+    pub fn get_blocks(&self) -> &Vec<usize> {
+        &self.blocks
+    }
+
     pub fn get_edges(&self) -> &Edges {
         &self.e
     }
@@ -108,8 +116,8 @@ impl<'tcx, 'a> FlowAnalysis<'tcx, 'a> {
         // Note: we will not visit the clean-up blocks (unwinding)
         self.order();
         // this phase will generate the Intra procedural visitor for us to visit the block
-        // note that the inter procedural part is inside in this function but cod in module inter_visitor
-        self.intra_run();
+        // the functions are visited bottom-up to apply the ownership summaries of the callees
+        self.inter_run();
     }
 }
 
@@ -170,6 +178,8 @@ struct IntraFlowAnalysis<'tcx, 'ctx, 'a> {
     pub graph: &'a Graph,
     taint_flag: bool,
     taint_source: Vec<Terminator<'tcx>>,
+    // the taint of the return value, i.e., the heap escaping to the caller
+    ret_taint: Taint<'tcx>,
 }
 
 impl<'tcx, 'ctx, 'a> IntraFlowAnalysis<'tcx, 'ctx, 'a> {
@@ -179,9 +189,9 @@ impl<'tcx, 'ctx, 'a> IntraFlowAnalysis<'tcx, 'ctx, 'a> {
         //unique: &'a mut HashSet<DefId>,
     ) -> Self {
         let body = rcx.tcx.instance_mir(Item(def_id));
-        let v_len = body.local_decls.len();
-        let b_len = body.basic_blocks.len();
         let graph = rcx.mir_graph().get(&def_id).unwrap();
+        let v_len = body.local_decls.len();
+        let b_len = graph.get_blocks().len();
 
         Self {
             rcx,
@@ -192,6 +202,7 @@ impl<'tcx, 'ctx, 'a> IntraFlowAnalysis<'tcx, 'ctx, 'a> {
             graph,
             taint_flag: false,
             taint_source: Vec::default(),
+            ret_taint: Taint::default(),
         }
     }
 
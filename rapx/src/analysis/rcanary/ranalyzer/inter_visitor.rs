@@ -1,29 +1,195 @@
-use super::super::RcxMut;
-use super::FlowAnalysis;
-use rustc_data_structures::graph;
-use rustc_middle::ty::InstanceKind::Item;
+use rustc_hir::def_id::DefId;
+use rustc_middle::mir::{Local, Operand, Rvalue, StatementKind, TerminatorKind, RETURN_PLACE};
+use rustc_middle::ty;
+
+use std::collections::HashSet;
+
+use super::super::{Rcx, RcxMut};
+use super::ownership::Taint;
+use super::{FlowAnalysis, IntraFlowAnalysis};
+use crate::{
+    rap_debug,
+    utils::{fn_summary, scope},
+};
+
+/// The ownership effects of a function on its caller, which are applied at the call sites.
+#[derive(Clone, Debug, Default)]
+pub struct OwnershipSummary<'tcx> {
+    /// The pointer arguments (starting from 1) whose heap is released or taken over by the callee.
+    pub consumed: Vec<usize>,
+    /// The pointer arguments (starting from 1) handed back to the caller in the return value.
+    pub returned: Vec<usize>,
+    /// The owned instances whose heap escapes through the return value, i.e., the return value
+    /// holds the pointers owning them. It is untainted if the return value owns no such heap.
+    pub escaped: Taint<'tcx>,
+}
 
 impl<'tcx, 'a> FlowAnalysis<'tcx, 'a> {
+    /// Visit the functions bottom-up in the call graph, so that the ownership summaries of the
+    /// callees are known at their call sites; the callees in the same recursion group are visited
+    /// in an arbitrary order.
     pub fn inter_run(&mut self) {
+        for def_id in self.bottom_up_order() {
+            let summary = self.intra_run(def_id);
+            rap_debug!("Ownership summary of {:?}: {:?}", def_id, summary);
+            self.rcx_mut().summaries_mut().insert(def_id, summary);
+        }
+    }
+
+    fn bottom_up_order(&self) -> Vec<DefId> {
         let tcx = self.tcx();
-        let mir_keys = tcx.mir_keys(());
+        // the functions not ordered yet
+        let mut pending: HashSet<DefId> = tcx
+            .mir_keys(())
+            .iter()
+            .map(|local_def_id| local_def_id.to_def_id())
+            .filter(|def_id| scope::needs(tcx, *def_id))
+            .collect();
+        let mut order: Vec<DefId> = self
+            .rcx()
+            .bottom_up_order()
+            .iter()
+            .copied()
+            .filter(|def_id| pending.remove(def_id))
+            .collect();
+        // e.g., the bodies of constants are not in the call graph
+        for local_def_id in tcx.mir_keys(()) {
+            let def_id = local_def_id.to_def_id();
+            if pending.remove(&def_id) {
+                order.push(def_id);
+            }
+        }
+        order
+    }
+}
 
-        for each_mir in mir_keys {
-            //let sw = Stopwatch::start_new();
-            let def_id = each_mir.to_def_id();
-            let body = tcx.instance_mir(Item(def_id));
-            if graph::is_cyclic(&body.basic_blocks) {
+impl<'tcx, 'ctx, 'a> IntraFlowAnalysis<'tcx, 'ctx, 'a> {
+    /// Summarize the ownership effects of the function after its body is visited.
+    pub(crate) fn summarize(&mut self) -> OwnershipSummary<'tcx> {
+        let mut summary = OwnershipSummary {
+            escaped: self.ret_taint.clone(),
+            ..Default::default()
+        };
+        for arg in 1..=self.body.arg_count {
+            if !self.body.local_decls[Local::from_usize(arg)]
+                .ty
+                .is_raw_ptr()
+            {
                 continue;
             }
+            let flows = self.arg_flows(arg);
+            if flows.contains(&RETURN_PLACE) {
+                summary.returned.push(arg);
+            }
+            if self.is_arg_consumed(&flows) {
+                summary.consumed.push(arg);
+            }
+        }
+        summary
+    }
 
-            let mut cfg = z3::Config::new();
-            cfg.set_model_generation(true);
-            cfg.set_timeout_msec(1000);
+    /// Return the summary of a local callee that has been visited.
+    pub(crate) fn callee_summary(
+        &self,
+        func: &Operand<'tcx>,
+    ) -> Option<&'a OwnershipSummary<'tcx>> {
+        let rcx = self.rcx;
+        match func.constant()?.ty().kind() {
+            ty::FnDef(def_id, ..) => rcx.summaries().get(def_id),
+            _ => None,
+        }
+    }
 
-            //let ctx = z3::Context::new(&cfg);
-            //let goal = z3::Goal::new(&ctx, true, false, false);
-            //let solver = z3::Solver::new(&ctx);
-            //let inter_visitor = InterFlowAnalysis::new(self.rcx);
+    /// Collect the locals holding the value of the argument, through assignments, casts and the
+    /// callees handing it back.
+    fn arg_flows(&self, arg: usize) -> HashSet<Local> {
+        let mut flows = HashSet::from([Local::from_usize(arg)]);
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for data in self.body.basic_blocks.iter() {
+                for stmt in &data.statements {
+                    let StatementKind::Assign(box (place, rvalue)) = &stmt.kind else {
+                        continue;
+                    };
+                    let from = match rvalue {
+                        Rvalue::Use(op) | Rvalue::Cast(_, op, _) => op.place(),
+                        Rvalue::CopyForDeref(from) => Some(*from),
+                        _ => None,
+                    };
+                    if from.is_some_and(|from| !from.is_indirect() && flows.contains(&from.local)) {
+                        changed |= flows.insert(place.local);
+                    }
+                }
+                if let TerminatorKind::Call {
+                    func,
+                    args,
+                    destination,
+                    ..
+                } = &data.terminator().kind
+                {
+                    let Some(summary) = self.callee_summary(func) else {
+                        continue;
+                    };
+                    let handed_back = summary.returned.iter().any(|idx| {
+                        args.get(idx - 1)
+                            .and_then(|arg| arg.node.place())
+                            .is_some_and(|place| flows.contains(&place.local))
+                    });
+                    if handed_back {
+                        changed |= flows.insert(destination.local);
+                    }
+                }
+            }
+        }
+        flows
+    }
+
+    /// Test if the value of the argument is passed to a call releasing it, e.g., `Box::from_raw`,
+    /// a callee consuming it in its ownership summary, or an opaque callee freeing it in the
+    /// summary file.
+    fn is_arg_consumed(&mut self, flows: &HashSet<Local>) -> bool {
+        for data in self.body.basic_blocks.iter() {
+            let TerminatorKind::Call {
+                func,
+                args,
+                destination,
+                ..
+            } = &data.terminator().kind
+            else {
+                continue;
+            };
+            for (idx, arg) in args.iter().enumerate() {
+                let Some(place) = arg.node.place() else {
+                    continue;
+                };
+                if !flows.contains(&place.local) {
+                    continue;
+                }
+                let callee = match func.constant().map(|constant| constant.ty().kind()) {
+                    Some(ty::FnDef(def_id, ..)) => Some(*def_id),
+                    _ => None,
+                };
+                if callee
+                    .and_then(fn_summary::get)
+                    .is_some_and(|summary| summary.releases(idx + 1))
+                    || self
+                        .callee_summary(func)
+                        .is_some_and(|summary| summary.consumed.contains(&(idx + 1)))
+                {
+                    return true;
+                }
+                // a raw pointer turned into an owning instance
+                let dest_ty = destination.ty(&self.body.local_decls, self.tcx());
+                if place.ty(&self.body.local_decls, self.tcx()).ty.is_raw_ptr()
+                    && self
+                        .extract_default_ty_layout(dest_ty.ty, dest_ty.variant_index)
+                        .is_owned()
+                {
+                    return true;
+                }
+            }
         }
+        false
     }
 }
@@ -1,5 +1,4 @@
 use rustc_abi::VariantIdx;
-use rustc_data_structures::graph;
 use rustc_hir::def_id::DefId;
 use rustc_middle::{
     mir::{
@@ -11,10 +10,11 @@ use rustc_middle::{
 use rustc_span::{source_map::Spanned, Symbol};
 
 use annotate_snippets::{Level, Renderer, Snippet};
-use std::ops::Add;
+use std::{ops::Add, time::Instant};
 use z3::ast::{self, Ast};
 
 use super::super::{IcxMut, IcxSliceMut, Rcx, RcxMut};
+use super::inter_visitor::OwnershipSummary;
 use super::is_z3_goal_verbose;
 use super::ownership::IntraVar;
 use super::{FlowAnalysis, IcxSliceFroBlock, IntraFlowAnalysis};
@@ -22,10 +22,14 @@ use crate::{
     analysis::core::ownedheap_analysis::{default::*, *},
     rap_debug, rap_error, rap_trace, rap_warn,
     utils::{
+        budget::{self, Exhaustion},
+        diagnostic::{print_report, suppression::is_allowed, Diagnostic, Rule},
+        fn_summary,
         log::{
             are_spans_in_same_file, relative_pos_range, span_to_filename, span_to_line_number,
             span_to_source_code,
         },
+        profile, scope,
         source::get_name,
     },
 };
@@ -43,30 +47,25 @@ pub enum AsgnKind {
 }
 
 impl<'tcx, 'a> FlowAnalysis<'tcx, 'a> {
-    pub fn intra_run(&mut self) {
+    pub fn intra_run(&mut self, def_id: DefId) -> OwnershipSummary<'tcx> {
         let tcx = self.tcx();
-        let mir_keys = tcx.mir_keys(());
-
-        for each_mir in mir_keys {
-            let def_id = each_mir.to_def_id();
-            let body = tcx.instance_mir(Item(def_id));
-            if graph::is_cyclic(&body.basic_blocks) {
-                continue;
-            }
-            if format!("{:?}", def_id).contains("syscall_dispatch") {
-                continue;
-            }
-
-            let mut cfg = z3::Config::new();
-            cfg.set_model_generation(true);
-            cfg.set_timeout_msec(1000);
-            let ctx = z3::Context::new(&cfg);
-            let goal = z3::Goal::new(&ctx, true, false, false);
-            let solver = z3::Solver::new(&ctx);
-
-            let mut intra_visitor = IntraFlowAnalysis::new(self.rcx, def_id);
-            intra_visitor.visit_body(&ctx, &goal, &solver, body);
-        }
+        let body = tcx.instance_mir(Item(def_id));
+        if format!("{:?}", def_id).contains("syscall_dispatch") {
+            return OwnershipSummary::default();
+        }
+
+        let mut cfg = z3::Config::new();
+        cfg.set_model_generation(true);
+        cfg.set_timeout_msec(budget::get().z3_timeout as u64);
+        let ctx = z3::Context::new(&cfg);
+        let goal = z3::Goal::new(&ctx, true, false, false);
+        let solver = z3::Solver::new(&ctx);
+
+        let start = Instant::now();
+        let mut intra_visitor = IntraFlowAnalysis::new(self.rcx, def_id);
+        intra_visitor.visit_body(&ctx, &goal, &solver, body);
+        profile::record_time("rcanary", def_id, start.elapsed());
+        intra_visitor.summarize()
     }
 }
 
@@ -78,9 +77,11 @@ impl<'tcx, 'ctx, 'a> IntraFlowAnalysis<'tcx, 'ctx, 'a> {
         solver: &'ctx z3::Solver<'ctx>,
         body: &'tcx Body<'tcx>,
     ) {
+        // the nodes of the graph are the MIR basic blocks unless there are loops to unroll
         let topo: Vec<usize> = self.graph.get_topo().iter().map(|id| *id).collect();
         for bidx in topo {
-            let data = &body.basic_blocks[BasicBlock::from(bidx)];
+            let block = self.graph.get_blocks()[bidx];
+            let data = &body.basic_blocks[BasicBlock::from(block)];
             self.visit_block_data(ctx, goal, solver, data, bidx);
         }
     }
@@ -977,7 +978,7 @@ impl<'tcx, 'ctx, 'a> IntraFlowAnalysis<'tcx, 'ctx, 'a> {
         let rust_bv_for_op_and = if self.icx_slice().taint()[ru].is_tainted() {
             rustbv_merge(
                 &heap_layout_to_rustbv(default_heap.layout()),
-                &self.generate_ptr_layout(rpj_ty.ty, rpj_ty.variant_index),
+                &self.generate_ptr_layout(rpj_ty.ty, rpj_ty.variant_index, false),
             )
         } else {
             heap_layout_to_rustbv(default_heap.layout())
@@ -1150,7 +1151,7 @@ impl<'tcx, 'ctx, 'a> IntraFlowAnalysis<'tcx, 'ctx, 'a> {
         let rust_bv_for_op_and = if self.icx_slice().taint()[ru].is_tainted() {
             rustbv_merge(
                 &heap_layout_to_rustbv(default_heap.layout()),
-                &self.generate_ptr_layout(rpj_ty.ty, rpj_ty.variant_index),
+                &self.generate_ptr_layout(rpj_ty.ty, rpj_ty.variant_index, false),
             )
         } else {
             heap_layout_to_rustbv(default_heap.layout())
@@ -1977,6 +1978,15 @@ impl<'tcx, 'ctx, 'a> IntraFlowAnalysis<'tcx, 'ctx, 'a> {
         }
     }
 
+    pub(crate) fn check_fn_alloc(&mut self, dest: &Place<'tcx>) -> bool {
+        let l_place_ty = dest.ty(&self.body.local_decls, self.tcx());
+        if !is_place_containing_ptr(&l_place_ty.ty) {
+            return false;
+        }
+        self.taint_flag = true;
+        true
+    }
+
     pub(crate) fn check_fn_recovery(
         &mut self,
         //args: &Vec<Operand<'tcx>>,
@@ -2070,13 +2080,63 @@ impl<'tcx, 'ctx, 'a> IntraFlowAnalysis<'tcx, 'ctx, 'a> {
         let llocal = dest.local;
         let lu: usize = llocal.as_usize();
 
+        let summary = match func {
+            Operand::Constant(constant) => match constant.ty().kind() {
+                ty::FnDef(id, ..) => fn_summary::get(*id),
+                _ => None,
+            },
+            _ => None,
+        };
+
+        let own_summary = self.callee_summary(func);
+        // the args handed back by the callee, which the return value takes over
+        let returned: Vec<usize> = own_summary
+            .into_iter()
+            .flat_map(|s| s.returned.iter())
+            .filter_map(|idx| args.get(idx - 1)?.node.place())
+            .map(|aplace| aplace.local.as_usize())
+            .filter(|au| self.icx_slice().taint()[*au].is_tainted())
+            .collect();
+        // a callee returning the heap it owns is a source as well
+        let escape_flag =
+            own_summary.is_some_and(|s| s.escaped.is_tainted()) || !returned.is_empty();
+        if escape_flag {
+            self.taint_flag = true;
+        }
+
         // the source flag is for fn(self) -> */&
         // we will tag the lvalue as tainted and change the default ctor to modified one
-        let source_flag = self.check_fn_source(args, dest);
+        // an opaque callee allocating its return value in the summary file is also a source
+        let source_flag = self.check_fn_source(args, dest)
+            || summary.is_some_and(|s| s.allocates) && self.check_fn_alloc(dest)
+            || escape_flag;
         // the recovery flag is for fn(*) -> Self
         // the return value should have the same layout as tainted one
         // we will take the heap of the args if the arg is a pointer
-        let recovery_flag = self.check_fn_recovery(args, dest);
+        let mut recovery_flag = self.check_fn_recovery(args, dest);
+        // the pointers freed or taken over by an opaque callee in the summary file are recovered
+        if let Some(summary) = summary {
+            for (idx, arg) in args.iter().enumerate() {
+                if let Some(aplace) = arg.node.place() {
+                    if summary.releases(idx + 1) {
+                        recovery_flag.0 = true;
+                        recovery_flag.1.push(aplace.local.as_usize());
+                    }
+                }
+            }
+        }
+        // so are the pointers consumed or handed back by a local callee
+        if let Some(own_summary) = own_summary {
+            for (idx, arg) in args.iter().enumerate() {
+                if let Some(aplace) = arg.node.place() {
+                    let au = aplace.local.as_usize();
+                    if own_summary.consumed.contains(&(idx + 1)) || returned.contains(&au) {
+                        recovery_flag.0 = true;
+                        recovery_flag.1.push(au);
+                    }
+                }
+            }
+        }
         if source_flag {
             self.add_taint(term);
         }
@@ -2253,6 +2313,15 @@ impl<'tcx, 'ctx, 'a> IntraFlowAnalysis<'tcx, 'ctx, 'a> {
             }
         }
 
+        if let Some(own_summary) = own_summary {
+            for ty in own_summary.escaped.set() {
+                self.icx_slice_mut().taint_mut()[lu].insert(ty.clone());
+            }
+            for au in returned {
+                self.handle_taint(lu, au);
+            }
+        }
+
         // establish constraints for return value
         if self.icx_slice().var()[lu].is_unsupported() {
             self.handle_intra_var_unsupported(lu);
@@ -2276,8 +2345,11 @@ impl<'tcx, 'ctx, 'a> IntraFlowAnalysis<'tcx, 'ctx, 'a> {
                 }
 
                 let int_for_gen = if source_flag {
-                    let modified_layout_bv =
-                        self.generate_ptr_layout(l_place_ty.ty, l_place_ty.variant_index);
+                    let modified_layout_bv = self.generate_ptr_layout(
+                        l_place_ty.ty,
+                        l_place_ty.variant_index,
+                        escape_flag,
+                    );
                     let merge_layout_bv = rustbv_merge(
                         &heap_layout_to_rustbv(return_value_layout.layout()),
                         &modified_layout_bv,
@@ -2422,6 +2494,11 @@ impl<'tcx, 'ctx, 'a> IntraFlowAnalysis<'tcx, 'ctx, 'a> {
         solver: &'ctx z3::Solver<'ctx>,
         bidx: usize,
     ) {
+        // the heap escaping through the return value goes to the ownership summary
+        for ty in self.icx_slice().taint()[0].set().clone() {
+            self.ret_taint.insert(ty);
+        }
+
         let place_0 = Place::from(Local::from_usize(0));
         self.handle_drop(ctx, goal, solver, &place_0, bidx, false);
 
@@ -2456,6 +2533,7 @@ impl<'tcx, 'ctx, 'a> IntraFlowAnalysis<'tcx, 'ctx, 'a> {
 
         let result = solver.check();
         let model = solver.get_model();
+        profile::record_count("rcanary", self.def_id, "solver_calls", 1);
 
         if is_z3_goal_verbose() {
             let g = format!("{}", goal);
@@ -2470,7 +2548,35 @@ impl<'tcx, 'ctx, 'a> IntraFlowAnalysis<'tcx, 'ctx, 'a> {
         // let g = format!("{}", goal);
         // rap_debug!("{}\n", g.color(Color::LightGray).bold());
 
-        if result == z3::SatResult::Unsat && self.taint_flag {
+        if result == z3::SatResult::Unknown {
+            budget::truncate(self.tcx(), "rcanary", self.def_id, Exhaustion::Solver);
+            profile::record_count("rcanary", self.def_id, "solver_timeouts", 1);
+        }
+
+        if result == z3::SatResult::Unsat
+            && self.taint_flag
+            && scope::contains(self.tcx(), self.def_id)
+            && !is_allowed(self.tcx(), self.def_id, Rule::MemoryLeak)
+        {
+            let fn_path = self.tcx().def_path_str(self.def_id);
+            let primary = self
+                .taint_source
+                .first()
+                .map_or(self.body.span, |source| source.source_info.span);
+            let mut diagnostic = Diagnostic::new(
+                Rule::MemoryLeak,
+                format!("Memory Leak detected in function `{}`.", fn_path),
+                primary,
+            )
+            .function(&fn_path)
+            .def_id(self.def_id);
+            for source in self.taint_source.iter() {
+                diagnostic = diagnostic.related(source.source_info.span, "Memory Leak Candidates.");
+            }
+            if diagnostic.is_baselined() {
+                return;
+            }
+
             let fn_name = get_name(self.tcx(), self.def_id)
                 .unwrap_or_else(|| Symbol::intern("no symbol available"));
 
@@ -2503,7 +2609,9 @@ impl<'tcx, 'ctx, 'a> IntraFlowAnalysis<'tcx, 'ctx, 'a> {
                 .title("Memory Leak detected.")
                 .snippet(snippet);
             let renderer = Renderer::styled();
-            println!("{}", renderer.render(message));
+            print_report(self.def_id, renderer.render(message));
+
+            diagnostic.emit();
         }
     }
 
@@ -2732,10 +2840,13 @@ impl<'tcx, 'ctx, 'a> IntraFlowAnalysis<'tcx, 'ctx, 'a> {
         }
     }
 
+    /// The pointer fields of an ADT are only marked with `adt_ptr` set, for the values returned by
+    /// a local callee with an ownership summary, whose pointer fields may hold the escaping heap.
     pub(crate) fn generate_ptr_layout(
         &mut self,
         ty: Ty<'tcx>,
         variant: Option<VariantIdx>,
+        adt_ptr: bool,
     ) -> Vec<bool> {
         let mut res = Vec::new();
         match ty.kind() {
@@ -2754,7 +2865,7 @@ impl<'tcx, 'ctx, 'a> IntraFlowAnalysis<'tcx, 'ctx, 'a> {
 
                 res
             }
-            TyKind::Adt(adtdef, _substs) => {
+            TyKind::Adt(adtdef, substs) => {
                 // check the ty is or is not an enum and the variant of this enum is or is not given
                 if adtdef.is_enum() && variant.is_none() {
                     return res;
@@ -2762,16 +2873,16 @@ impl<'tcx, 'ctx, 'a> IntraFlowAnalysis<'tcx, 'ctx, 'a> {
 
                 // check the ty if it is a struct or union
                 if adtdef.is_struct() || adtdef.is_union() {
-                    for _field in adtdef.all_fields() {
-                        res.push(false);
+                    for field in adtdef.all_fields() {
+                        res.push(adt_ptr && field.ty(self.tcx(), substs).is_any_ptr());
                     }
                 }
                 // check the ty which is an enum with a exact variant idx
                 else if adtdef.is_enum() {
                     let vidx = variant.unwrap();
 
-                    for _field in &adtdef.variants()[vidx].fields {
-                        res.push(false);
+                    for field in &adtdef.variants()[vidx].fields {
+                        res.push(adt_ptr && field.ty(self.tcx(), substs).is_any_ptr());
                     }
                 }
                 res
@@ -1,10 +1,16 @@
+use rustc_data_structures::graph::{scc::Sccs, vec_graph::VecGraph};
 use rustc_middle::{mir::TerminatorKind, ty::InstanceKind::Item};
 
-use std::collections::BinaryHeap;
+use std::collections::{BinaryHeap, HashMap};
 //use stopwatch::Stopwatch;
 
-use super::super::ranalyzer::{FlowAnalysis, NodeOrder};
+use super::super::ranalyzer::{Edges, FlowAnalysis, Graph, NodeOrder};
 use super::super::RcxMut;
+use crate::utils::scope;
+
+/// The number of back edges a path may take in the unrolled graph of a loop nest, i.e., the body of
+/// a single loop is visited at most twice, and the nested loops can still reach the exits.
+pub const LOOP_UNROLL_BOUND: usize = 2;
 
 impl<'tcx, 'a> FlowAnalysis<'tcx, 'a> {
     pub fn order(&mut self) {
@@ -19,12 +25,16 @@ impl<'tcx, 'a> FlowAnalysis<'tcx, 'a> {
         for each_mir in mir_keys {
             // Get the defid of current crate and get mir Body through this id
             let def_id = each_mir.to_def_id();
+            if !scope::needs(tcx, def_id) {
+                continue;
+            }
             let body = tcx.instance_mir(Item(def_id));
 
             let mut path = NodeOrder::new(body);
             let mut lev: Vec<usize> = vec![0; body.basic_blocks.len()];
 
             path.collect_edges(&mut lev);
+            path.unroll_loops(&mut lev, LOOP_UNROLL_BOUND);
             path.topo_order(&mut lev);
             self.rcx_mut()
                 .mir_graph_mut()
@@ -78,6 +88,67 @@ impl<'tcx> NodeOrder<'tcx> {
         }
     }
 
+    /// Unroll the loops so that the graph becomes acyclic for the encoding, which only covers
+    /// acyclic paths. The blocks in a loop nest (a strongly connected component) are copied once
+    /// per number of back edges taken since entering the nest, up to `bound`; the back edges go to
+    /// the next copy, and those beyond the bound are cut. The other blocks are not copied, so each
+    /// node of the unrolled graph records its MIR basic block in `blocks`.
+    pub(crate) fn unroll_loops(&mut self, lev: &mut Vec<usize>, bound: usize) {
+        let e = self.graph().e.clone();
+        let len = e.len();
+        let edge_pairs: Vec<(usize, usize)> = e
+            .iter()
+            .enumerate()
+            .flat_map(|(from, succ)| succ.iter().map(move |to| (from, *to)))
+            .collect();
+        let sccs: Sccs<usize, usize> = Sccs::new(&VecGraph::<usize>::new(len, edge_pairs));
+        let mut scc_size = vec![0; sccs.num_sccs()];
+        for block in 0..len {
+            scc_size[sccs.scc(block)] += 1;
+        }
+        let in_loop: Vec<bool> = (0..len)
+            .map(|block| scc_size[sccs.scc(block)] > 1 || e[block].contains(&block))
+            .collect();
+        if !in_loop.contains(&true) {
+            return;
+        }
+        let back_edges = find_back_edges(&e);
+
+        let mut nodes: HashMap<(usize, usize), usize> = HashMap::new();
+        let mut graph = Graph::default();
+        let mut worklist = vec![(0, 0)];
+        nodes.insert((0, 0), 0);
+        graph.blocks.push(0);
+        graph.e.push(Vec::new());
+        graph.pre.push(Vec::new());
+        while let Some((block, iter)) = worklist.pop() {
+            let from = nodes[&(block, iter)];
+            for &succ in &e[block] {
+                let succ_iter = if sccs.scc(block) != sccs.scc(succ) || !in_loop[succ] {
+                    0
+                } else if back_edges.contains(&(block, succ)) {
+                    iter + 1
+                } else {
+                    iter
+                };
+                if succ_iter > bound {
+                    continue;
+                }
+                let to = *nodes.entry((succ, succ_iter)).or_insert_with(|| {
+                    worklist.push((succ, succ_iter));
+                    graph.blocks.push(succ);
+                    graph.e.push(Vec::new());
+                    graph.pre.push(Vec::new());
+                    graph.blocks.len() - 1
+                });
+                graph.e[from].push(to);
+                graph.pre[to].push(from);
+            }
+        }
+        *lev = graph.pre.iter().map(Vec::len).collect();
+        *self.graph_mut() = graph;
+    }
+
     pub(crate) fn topo_order(&mut self, lev: &mut Vec<usize>) {
         let mut q: BinaryHeap<usize> = BinaryHeap::new();
         q.push(0);
@@ -94,3 +165,32 @@ impl<'tcx> NodeOrder<'tcx> {
         }
     }
 }
+
+/// Find the back edges of a depth-first search from the entry block, i.e., the edges to the blocks
+/// on the stack.
+fn find_back_edges(e: &Edges) -> Vec<(usize, usize)> {
+    let mut back_edges = Vec::new();
+    let mut visited = vec![false; e.len()];
+    let mut on_stack = vec![false; e.len()];
+    // the block and the index of its next successor to visit
+    let mut stack = vec![(0, 0)];
+    visited[0] = true;
+    on_stack[0] = true;
+    while let Some((block, next)) = stack.last_mut() {
+        let block = *block;
+        if let Some(&succ) = e[block].get(*next) {
+            *next += 1;
+            if on_stack[succ] {
+                back_edges.push((block, succ));
+            } else if !visited[succ] {
+                visited[succ] = true;
+                on_stack[succ] = true;
+                stack.push((succ, 0));
+            }
+        } else {
+            on_stack[block] = false;
+            stack.pop();
+        }
+    }
+    back_edges
+}
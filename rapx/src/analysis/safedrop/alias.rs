@@ -1,12 +1,23 @@
 use super::graph::*;
 use crate::{
-    analysis::core::alias_analysis::default::{types::*, MopAAFact, MopAAResultMap},
+    analysis::core::{
+        alias_analysis::default::{
+            alias::spread_args,
+            context::CallContext,
+            provenance::{ptr_source, PtrSource},
+            types::*,
+            MopAAFact, MopAAResultMap,
+        },
+        callgraph::indirect::{indirect_callees, is_fn_trait_method},
+    },
     rap_error,
+    utils::precision,
 };
 use rustc_middle::{
-    mir::{Operand, Place, ProjectionElem, TerminatorKind},
-    ty::{self, TyCtxt, TypingEnv},
+    mir::{BasicBlock, Operand, Place, ProjectionElem, TerminatorKind},
+    ty::{self, GenericArgs, TyCtxt, TypingEnv},
 };
+use rustc_span::source_map::Spanned;
 
 impl<'tcx> SafeDropGraph<'tcx> {
     /* alias analysis for a single block */
@@ -26,14 +37,16 @@ impl<'tcx> SafeDropGraph<'tcx> {
                 AssignType::InitBox => {
                     lv_aliaset_idx = *self.values[lv_aliaset_idx].fields.get(&0).unwrap();
                 }
-                _ => {} // Copy or Move
+                _ => {} // Copy, Move or Exposed
+            }
+            if assign.atype != AssignType::Exposed {
+                self.uaf_check(
+                    rv_aliaset_idx,
+                    assign.span,
+                    assign.rv.local.as_usize(),
+                    false,
+                );
             }
-            self.uaf_check(
-                rv_aliaset_idx,
-                assign.span,
-                assign.rv.local.as_usize(),
-                false,
-            );
             self.fill_birth(lv_aliaset_idx, self.scc_indices[bb_index] as isize);
             if self.values[lv_aliaset_idx].local != self.values[rv_aliaset_idx].local {
                 self.merge_alias(lv_aliaset_idx, rv_aliaset_idx, 0);
@@ -55,68 +68,110 @@ impl<'tcx> SafeDropGraph<'tcx> {
                 fn_span: _,
             } = call.kind
             {
-                if let Operand::Constant(ref constant) = func {
-                    let lv = self.projection(tcx, false, destination.clone());
-                    self.values[lv].birth = self.scc_indices[bb_index] as isize;
-                    let mut merge_vec = Vec::new();
-                    merge_vec.push(lv);
-                    let mut may_drop_flag = 0;
-                    if self.values[lv].may_drop {
-                        may_drop_flag += 1;
+                // calls through fn pointers, trait objects and closures
+                let callees = indirect_callees(tcx, self.def_id, BasicBlock::from(bb_index));
+                if callees.is_empty() && !matches!(func, Operand::Constant(_)) {
+                    continue;
+                }
+                let lv = self.projection(tcx, false, destination.clone());
+                self.values[lv].birth = self.scc_indices[bb_index] as isize;
+                let mut merge_vec = Vec::new();
+                merge_vec.push(lv);
+                let mut may_drop_flag = 0;
+                if self.values[lv].may_drop {
+                    may_drop_flag += 1;
+                }
+                for arg in args {
+                    match arg.node {
+                        Operand::Copy(ref p) => {
+                            let rv = self.projection(tcx, true, p.clone());
+                            self.uaf_check(rv, call.source_info.span, p.local.as_usize(), true);
+                            merge_vec.push(rv);
+                            if self.values[rv].may_drop {
+                                may_drop_flag += 1;
+                            }
+                        }
+                        Operand::Move(ref p) => {
+                            let rv = self.projection(tcx, true, p.clone());
+                            self.uaf_check(rv, call.source_info.span, p.local.as_usize(), true);
+                            merge_vec.push(rv);
+                            if self.values[rv].may_drop {
+                                may_drop_flag += 1;
+                            }
+                        }
+                        Operand::Constant(_) => {
+                            merge_vec.push(0);
+                        }
                     }
-                    for arg in args {
-                        match arg.node {
-                            Operand::Copy(ref p) => {
-                                let rv = self.projection(tcx, true, p.clone());
-                                self.uaf_check(rv, call.source_info.span, p.local.as_usize(), true);
-                                merge_vec.push(rv);
-                                if self.values[rv].may_drop {
-                                    may_drop_flag += 1;
-                                }
+                }
+                // a pointer from an integer aliases while no argument may drop
+                if self.alias_provenance(tcx, func, args, lv) || may_drop_flag <= 1 {
+                    continue;
+                }
+                if let Some((context, merge_vec)) = self.call_context(tcx, func, args, &merge_vec) {
+                    let contexts = self.contexts.clone();
+                    if let Some(summary) = contexts.summary_of(tcx, &context, fn_map) {
+                        for assign in summary.aliases().iter() {
+                            if assign.valuable() {
+                                self.merge(assign, &merge_vec);
                             }
-                            Operand::Move(ref p) => {
-                                let rv = self.projection(tcx, true, p.clone());
-                                self.uaf_check(rv, call.source_info.span, p.local.as_usize(), true);
-                                merge_vec.push(rv);
-                                if self.values[rv].may_drop {
-                                    may_drop_flag += 1;
-                                }
+                        }
+                        continue;
+                    }
+                }
+                if !callees.is_empty() {
+                    let merge_vec = match func {
+                        Operand::Constant(constant) => match constant.const_.ty().kind() {
+                            &ty::FnDef(method, _) if is_fn_trait_method(tcx, method) => {
+                                spread_args(tcx, self.def_id, args, &merge_vec, |place| {
+                                    self.projection(tcx, true, place)
+                                })
                             }
-                            Operand::Constant(_) => {
-                                merge_vec.push(0);
+                            _ => merge_vec,
+                        },
+                        _ => merge_vec,
+                    };
+                    for callee in callees {
+                        if let Some(assignments) = fn_map.get(callee) {
+                            for assign in assignments.aliases().iter() {
+                                if !assign.valuable() {
+                                    continue;
+                                }
+                                self.merge(assign, &merge_vec);
                             }
                         }
                     }
+                    continue;
+                }
+                if let Operand::Constant(ref constant) = func {
                     if let ty::FnDef(ref target_id, _) = constant.const_.ty().kind() {
-                        if may_drop_flag > 1 {
-                            if tcx.is_mir_available(*target_id) {
-                                if fn_map.contains_key(&target_id) {
-                                    let assignments = fn_map.get(&target_id).unwrap();
-                                    for assign in assignments.aliases().iter() {
-                                        if !assign.valuable() {
-                                            continue;
-                                        }
-                                        self.merge(assign, &merge_vec);
-                                    }
-                                }
-                            } else {
-                                if self.values[lv].may_drop {
-                                    if self.corner_handle(lv, &merge_vec, *target_id) {
+                        if fn_map.contains_key(target_id) || tcx.is_mir_available(*target_id) {
+                            if fn_map.contains_key(&target_id) {
+                                let assignments = fn_map.get(&target_id).unwrap();
+                                for assign in assignments.aliases().iter() {
+                                    if !assign.valuable() {
                                         continue;
                                     }
-                                    let mut right_set = Vec::new();
-                                    for rv in &merge_vec {
-                                        if self.values[*rv].may_drop
-                                            && lv != *rv
-                                            && self.values[lv].is_ptr()
-                                        {
-                                            right_set.push(*rv);
-                                        }
-                                    }
-                                    if right_set.len() == 1 {
-                                        self.merge_alias(lv, right_set[0], 0);
+                                    self.merge(assign, &merge_vec);
+                                }
+                            }
+                        } else {
+                            if self.values[lv].may_drop {
+                                if self.corner_handle(lv, &merge_vec, *target_id) {
+                                    continue;
+                                }
+                                let mut right_set = Vec::new();
+                                for rv in &merge_vec {
+                                    if self.values[*rv].may_drop
+                                        && lv != *rv
+                                        && self.values[lv].is_ptr()
+                                    {
+                                        right_set.push(*rv);
                                     }
                                 }
+                                if right_set.len() == 1 {
+                                    self.merge_alias(lv, right_set[0], 0);
+                                }
                             }
                         }
                     }
@@ -125,6 +180,65 @@ impl<'tcx> SafeDropGraph<'tcx> {
         }
     }
 
+    /// Return the context of a call whose destination and arguments are `merge_vec`, and the
+    /// values to merge the summary of the callee with, as the alias analysis does.
+    fn call_context(
+        &mut self,
+        tcx: TyCtxt<'tcx>,
+        func: &Operand<'tcx>,
+        args: &[Spanned<Operand<'tcx>>],
+        merge_vec: &[usize],
+    ) -> Option<(CallContext<'tcx>, Vec<usize>)> {
+        let depth = precision::get().context_depth;
+        if depth == 0 {
+            return None;
+        }
+        let caller_args = GenericArgs::identity_for_item(tcx, self.def_id);
+        let mut context = CallContext::resolve(tcx, self.def_id, caller_args, func)?;
+        context.depth = depth - 1;
+        let merge_vec = match tcx.is_closure_like(context.callee) {
+            true => spread_args(tcx, self.def_id, args, merge_vec, |place| {
+                self.projection(tcx, true, place)
+            }),
+            false => merge_vec.to_vec(),
+        };
+        let constant = self.constant.clone();
+        let context = context.with_args(args, &merge_vec, &constant, |lhs, rhs| {
+            self.union_is_same(lhs, rhs)
+        })?;
+        Some((context, merge_vec))
+    }
+
+    /* Merge the pointer returned by a call to a std function deriving it from another pointer or
+     * from an integer as the alias analysis does, which returns false for the other calls. */
+    fn alias_provenance(
+        &mut self,
+        tcx: TyCtxt<'tcx>,
+        func: &Operand<'tcx>,
+        args: &[Spanned<Operand<'tcx>>],
+        lv: usize,
+    ) -> bool {
+        match ptr_source(func) {
+            Some(PtrSource::Base) => {
+                if let Some(Operand::Copy(place) | Operand::Move(place)) =
+                    args.first().map(|arg| &arg.node)
+                {
+                    let rv = self.projection(tcx, true, *place);
+                    self.merge_alias(lv, rv, 0);
+                }
+                true
+            }
+            Some(PtrSource::Exposed) => {
+                for place in self.exposed.clone() {
+                    let rv = self.projection(tcx, true, place);
+                    self.merge_alias(lv, rv, 0);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
     // assign to the variable _x, we will set the birth of _x and its child self.values a new birth.
     pub fn fill_birth(&mut self, node: usize, birth: isize) {
         self.values[node].birth = birth;
@@ -198,13 +312,7 @@ impl<'tcx> SafeDropGraph<'tcx> {
         }
         self.union_merge(lv, rv);
 
-        let max_field_depth = match std::env::var_os("SAFEDROP") {
-            Some(val) if val == "0" => 10,
-            Some(val) if val == "1" => 20,
-            Some(val) if val == "2" => 30,
-            Some(val) if val == "3" => 50,
-            _ => 15,
-        };
+        let max_field_depth = precision::get().safedrop_field_depth();
 
         if depth > max_field_depth {
             return;
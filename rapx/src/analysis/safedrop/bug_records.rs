@@ -1,7 +1,12 @@
 use rustc_data_structures::fx::{FxHashMap, FxHashSet};
+use rustc_hir::def_id::DefId;
 use rustc_span::Span;
 
 use crate::rap_warn;
+use crate::utils::diagnostic::{
+    baseline::{self, Fingerprint},
+    print_report, Diagnostic, Rule,
+};
 use crate::utils::log::are_spans_in_same_file;
 use rustc_span::symbol::Symbol;
 
@@ -39,7 +44,7 @@ impl BugRecords {
             && self.dp_bugs_unwind.is_empty()
     }
 
-    pub fn df_bugs_output(&self, fn_name: Symbol, span: Span) {
+    pub fn df_bugs_output(&self, def_id: DefId, fn_name: Symbol, span: Span) {
         if !self.df_bugs.is_empty() {
             rap_warn!("Double free detected in function {:}", fn_name);
             let code_source = span_to_source_code(span);
@@ -62,11 +67,11 @@ impl BugRecords {
                 .title("Double free detected.")
                 .snippet(snippet);
             let renderer = Renderer::styled();
-            println!("{}", renderer.render(message));
+            print_report(def_id, renderer.render(message));
         }
     }
 
-    pub fn uaf_bugs_output(&self, fn_name: Symbol, span: Span) {
+    pub fn uaf_bugs_output(&self, def_id: DefId, fn_name: Symbol, span: Span) {
         if !self.uaf_bugs.is_empty() {
             rap_warn!("Use after free detected in function {:?}", fn_name);
             let code_source = span_to_source_code(span);
@@ -89,11 +94,11 @@ impl BugRecords {
                 .title("Use after free detected.")
                 .snippet(snippet);
             let renderer = Renderer::styled();
-            println!("{}", renderer.render(message));
+            print_report(def_id, renderer.render(message));
         }
     }
 
-    pub fn dp_bug_output(&self, fn_name: Symbol, span: Span) {
+    pub fn dp_bug_output(&self, def_id: DefId, fn_name: Symbol, span: Span) {
         let code_source = span_to_source_code(span);
         let filename = span_to_filename(span);
         if !self.dp_bugs.is_empty() {
@@ -116,7 +121,7 @@ impl BugRecords {
                 .title("Dangling pointer detected.")
                 .snippet(snippet);
             let renderer = Renderer::styled();
-            println!("{}", renderer.render(message));
+            print_report(def_id, renderer.render(message));
         }
         if !self.dp_bugs_unwind.is_empty() {
             rap_warn!(
@@ -141,7 +146,56 @@ impl BugRecords {
                 .title("Dangling pointer detected during unwinding.")
                 .snippet(snippet);
             let renderer = Renderer::styled();
-            println!("{}", renderer.render(message));
+            print_report(def_id, renderer.render(message));
+        }
+    }
+
+    /// Drop the bugs of the rules allowed by `rapx::allow` attributes and the ones recorded in the
+    /// baseline, so that they are neither printed nor emitted.
+    pub fn suppress(&mut self, allowed: &[Rule], fn_path: &str) {
+        let is_reported = |rule: Rule, bug: &Span| {
+            !allowed.contains(&rule)
+                && !baseline::is_known(&Fingerprint::new(rule, Some(fn_path), *bug))
+        };
+        self.df_bugs
+            .retain(|_, bug| is_reported(Rule::DoubleFree, bug));
+        self.uaf_bugs
+            .retain(|bug| is_reported(Rule::UseAfterFree, bug));
+        self.dp_bugs
+            .retain(|bug| is_reported(Rule::DanglingPointer, bug));
+        self.dp_bugs_unwind
+            .retain(|bug| is_reported(Rule::DanglingPointer, bug));
+    }
+
+    /// Record the bugs as structured diagnostics; `span` is the span of the function body.
+    pub fn emit_diagnostics(&self, def_id: DefId, fn_path: &str, span: Span) {
+        let bugs = self
+            .df_bugs
+            .values()
+            .map(|bug| (Rule::DoubleFree, "Double free detected.", bug))
+            .chain(
+                self.uaf_bugs
+                    .iter()
+                    .map(|bug| (Rule::UseAfterFree, "Use after free detected.", bug)),
+            )
+            .chain(
+                self.dp_bugs
+                    .iter()
+                    .map(|bug| (Rule::DanglingPointer, "Dangling pointer detected.", bug)),
+            )
+            .chain(self.dp_bugs_unwind.iter().map(|bug| {
+                (
+                    Rule::DanglingPointer,
+                    "Dangling pointer detected during unwinding.",
+                    bug,
+                )
+            }));
+        for (rule, message, bug) in bugs {
+            Diagnostic::new(rule, message, *bug)
+                .function(fn_path)
+                .def_id(def_id)
+                .related(span, format!("In function `{}`.", fn_path))
+                .emit();
         }
     }
 }
@@ -1,4 +1,5 @@
 use super::graph::*;
+use crate::utils::diagnostic::suppression::allowed_rules;
 use crate::utils::source::*;
 use rustc_data_structures::fx::FxHashSet;
 use rustc_middle::mir::SourceInfo;
@@ -6,7 +7,7 @@ use rustc_span::symbol::Symbol;
 use rustc_span::Span;
 
 impl<'tcx> SafeDropGraph<'tcx> {
-    pub fn report_bugs(&self) {
+    pub fn report_bugs(&mut self) {
         let filename = get_filename(self.tcx, self.def_id);
         match filename {
             Some(filename) => {
@@ -16,6 +17,9 @@ impl<'tcx> SafeDropGraph<'tcx> {
             }
             None => {}
         }
+        let fn_path = self.tcx.def_path_str(self.def_id);
+        let allowed = allowed_rules(self.tcx, self.def_id);
+        self.bug_records.suppress(&allowed, &fn_path);
         if self.bug_records.is_bug_free() {
             return;
         }
@@ -23,9 +27,14 @@ impl<'tcx> SafeDropGraph<'tcx> {
             Some(name) => name,
             None => Symbol::intern("no symbol available"),
         };
-        self.bug_records.df_bugs_output(fn_name, self.span);
-        self.bug_records.uaf_bugs_output(fn_name, self.span);
-        self.bug_records.dp_bug_output(fn_name, self.span);
+        self.bug_records
+            .df_bugs_output(self.def_id, fn_name, self.span);
+        self.bug_records
+            .uaf_bugs_output(self.def_id, fn_name, self.span);
+        self.bug_records
+            .dp_bug_output(self.def_id, fn_name, self.span);
+        self.bug_records
+            .emit_diagnostics(self.def_id, &fn_path, self.span);
     }
 
     pub fn uaf_check(&mut self, aliaset_idx: usize, span: Span, local: usize, is_func_call: bool) {
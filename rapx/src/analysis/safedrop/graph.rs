@@ -1,12 +1,18 @@
 use super::bug_records::*;
 use crate::{
-    analysis::{core::alias_analysis::default::types::*, core::ownedheap_analysis::OHAResultMap},
+    analysis::core::{
+        alias_analysis::default::{
+            context::ContextSummaries, provenance::exposed_places, types::*,
+        },
+        ownedheap_analysis::OHAResultMap,
+    },
     def_id::*,
+    utils::{budget::FnBudget, fn_summary},
 };
 use rustc_data_structures::fx::{FxHashMap, FxHashSet};
 use rustc_middle::mir::{
-    BasicBlock, Body, Const, Operand, Place, Rvalue, StatementKind, Terminator, TerminatorKind,
-    UnwindAction,
+    BasicBlock, BinOp, Body, CastKind, Const, Operand, Place, Rvalue, StatementKind, Terminator,
+    TerminatorKind, UnwindAction,
 };
 use rustc_middle::ty::{self, TyCtxt, TypingEnv};
 use rustc_span::{def_id::DefId, Span};
@@ -18,6 +24,8 @@ pub enum AssignType {
     Move,
     InitBox,
     Variant,
+    // a pointer cast from an integer, which aliases an exposed pointer without using it.
+    Exposed,
 }
 
 //self-defined assignments structure.
@@ -165,6 +173,7 @@ pub struct SafeDropGraph<'tcx> {
     pub bug_records: BugRecords,
     // a threhold to avoid path explosion.
     pub visit_times: usize,
+    pub budget: FnBudget,
     pub alias_set: Vec<usize>,
     pub dead_record: Vec<bool>,
     // analysis of heap item
@@ -179,6 +188,10 @@ pub struct SafeDropGraph<'tcx> {
     >,
     pub disc_map: FxHashMap<usize, usize>,
     pub terms: Vec<TerminatorKind<'tcx>>,
+    // the summaries of the callees in their calling contexts, shared with the alias analysis.
+    pub contexts: ContextSummaries<'tcx>,
+    // the pointers whose addresses are exposed, see `provenance`.
+    pub exposed: Vec<Place<'tcx>>,
 }
 
 impl<'tcx> SafeDropGraph<'tcx> {
@@ -215,6 +228,7 @@ impl<'tcx> SafeDropGraph<'tcx> {
         let mut scc_indices = Vec::<usize>::new();
         let mut disc_map = FxHashMap::default();
         let mut terms = Vec::new();
+        let exposed = exposed_places(body);
 
         // handle each basicblock
         for i in 0..basicblocks.len() {
@@ -307,6 +321,28 @@ impl<'tcx> SafeDropGraph<'tcx> {
                                 Operand::Constant(_) => {}
                             }
                         }
+                        // the pointer keeps the allocation of its base at an unknown offset
+                        Rvalue::BinaryOp(BinOp::Offset, operands) => match operands.0 {
+                            Operand::Copy(rv) | Operand::Move(rv) => {
+                                let rv_local = rv.local.as_usize();
+                                if values[lv_local].may_drop && values[rv_local].may_drop {
+                                    let assign = Assignment::new(lv, rv, AssignType::Copy, span);
+                                    cur_bb.assignments.push(assign);
+                                }
+                            }
+                            Operand::Constant(_) => {}
+                        },
+                        // the pointer may point to any allocation exposed in the function
+                        Rvalue::Cast(CastKind::PointerWithExposedProvenance, _, _) => {
+                            for rv in exposed.iter() {
+                                let rv_local = rv.local.as_usize();
+                                if values[lv_local].may_drop && values[rv_local].may_drop {
+                                    let assign =
+                                        Assignment::new(lv, *rv, AssignType::Exposed, span);
+                                    cur_bb.assignments.push(assign);
+                                }
+                            }
+                        }
                         Rvalue::Cast(_, x, _) => match x {
                             Operand::Copy(rv) => {
                                 let rv_local = rv.local.as_usize();
@@ -400,6 +436,7 @@ impl<'tcx> SafeDropGraph<'tcx> {
                                 || id == drop_in_place()
                                 || id == manually_drop()
                                 || dealloc_opt().map(|f| f == id).unwrap_or(false)
+                                || fn_summary::get(id).is_some_and(|s| !s.drops.is_empty())
                             {
                                 cur_bb.drops.push(terminator.clone());
                             }
@@ -485,12 +522,15 @@ impl<'tcx> SafeDropGraph<'tcx> {
             return_set: FxHashSet::default(),
             bug_records: BugRecords::new(),
             visit_times: 0,
+            budget: FnBudget::start(),
             alias_set: alias,
             dead_record: dead,
             adt_owner,
             child_scc: FxHashMap::default(),
             disc_map,
             terms,
+            contexts: ContextSummaries::default(),
+            exposed,
         }
     }
 
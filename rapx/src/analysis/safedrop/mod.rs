@@ -8,15 +8,20 @@ pub mod safedrop;
 
 use rustc_hir::def_id::DefId;
 use rustc_middle::ty::TyCtxt;
+use std::time::Instant;
 
-use crate::analysis::core::{
-    alias_analysis::default::{AliasAnalyzer, MopAAResultMap},
-    ownedheap_analysis::{default::OwnedHeapAnalyzer, OHAResultMap, OwnedHeapAnalysis},
+use crate::analysis::{
+    context::AnalysisContext,
+    core::{
+        alias_analysis::default::{context::ContextSummaries, MopAAResultMap},
+        ownedheap_analysis::OHAResultMap,
+    },
+};
+use crate::{
+    rap_debug,
+    utils::{budget, profile, scope},
 };
 use graph::SafeDropGraph;
-use safedrop::*;
-
-use super::Analysis;
 
 pub struct SafeDrop<'tcx> {
     pub tcx: TyCtxt<'tcx>,
@@ -26,30 +31,25 @@ impl<'tcx> SafeDrop<'tcx> {
     pub fn new(tcx: TyCtxt<'tcx>) -> Self {
         Self { tcx }
     }
-    pub fn start(&self) {
-        let mut mop = AliasAnalyzer::new(self.tcx);
-        mop.run();
-        let fn_map = mop.get_all_fn_alias_raw();
-
-        let mut heap = OwnedHeapAnalyzer::new(self.tcx);
-        heap.run();
-        let adt_owner = heap.get_all_items();
+    pub fn start(&self, ctx: &AnalysisContext<'tcx>) {
+        let fn_map = &ctx.alias().fn_map;
+        let contexts = &ctx.alias().contexts;
+        let adt_owner = ctx.ownedheap();
 
         let mir_keys = self.tcx.mir_keys(());
         for local_def_id in mir_keys {
-            query_safedrop(
-                self.tcx,
-                &fn_map,
-                local_def_id.to_def_id(),
-                adt_owner.clone(),
-            );
+            let def_id = local_def_id.to_def_id();
+            if scope::contains(self.tcx, def_id) {
+                query_safedrop(self.tcx, fn_map, contexts, def_id, adt_owner.clone());
+            }
         }
     }
 }
 
-pub fn query_safedrop(
-    tcx: TyCtxt,
+pub fn query_safedrop<'tcx>(
+    tcx: TyCtxt<'tcx>,
     fn_map: &MopAAResultMap,
+    contexts: &ContextSummaries<'tcx>,
     def_id: DefId,
     adt_owner: OHAResultMap,
 ) {
@@ -58,14 +58,18 @@ pub fn query_safedrop(
         return;
     }
     if tcx.is_mir_available(def_id) {
+        let start = Instant::now();
         let body = tcx.optimized_mir(def_id);
         let mut safedrop_graph = SafeDropGraph::new(body, tcx, def_id, adt_owner);
+        safedrop_graph.contexts = contexts.clone();
         safedrop_graph.solve_scc();
-        safedrop_graph.check(0, tcx, fn_map);
-        if safedrop_graph.visit_times <= VISIT_LIMIT {
-            safedrop_graph.report_bugs();
-        } else {
-            println!("Over visited: {:?}", def_id);
+        safedrop_graph.check_within_budget(tcx, fn_map);
+        profile::record_time("safedrop", def_id, start.elapsed());
+        profile::record_count("safedrop", def_id, "visits", safedrop_graph.visit_times);
+        if let Some(reason) = safedrop_graph.budget.exhausted() {
+            rap_debug!("Over budget ({}): {:?}", reason, def_id);
+            budget::truncate(tcx, "safedrop", def_id, reason);
         }
+        safedrop_graph.report_bugs();
     }
 }
@@ -1,6 +1,15 @@
-use crate::analysis::{core::alias_analysis::default::MopAAResultMap, safedrop::SafeDropGraph};
-use crate::rap_error;
-use rustc_data_structures::fx::FxHashSet;
+use crate::analysis::{
+    core::alias_analysis::default::{
+        mop::{scc_successors, topo_sccs},
+        MopAAResultMap,
+    },
+    safedrop::{bug_records::BugRecords, graph::ValueNode, SafeDropGraph},
+};
+use crate::{
+    rap_error,
+    utils::{fn_summary, precision},
+};
+use rustc_data_structures::fx::{FxHashMap, FxHashSet};
 use rustc_middle::{
     mir::{
         Operand::{self, Constant, Copy, Move},
@@ -10,8 +19,6 @@ use rustc_middle::{
 };
 use std::collections::{HashMap, HashSet};
 
-pub const VISIT_LIMIT: usize = 1000;
-
 impl<'tcx> SafeDropGraph<'tcx> {
     // analyze the drop statement and update the liveness for nodes.
     pub fn drop_check(&mut self, bb_index: usize, tcx: TyCtxt<'tcx>) {
@@ -35,16 +42,32 @@ impl<'tcx> SafeDropGraph<'tcx> {
                     self.dead_node(drop_local, birth, &info, false);
                 }
                 TerminatorKind::Call {
-                    func: _, ref args, ..
+                    ref func, ref args, ..
                 } => {
-                    if args.len() > 0 {
+                    // drop(), drop_in_place() and dealloc() free their first argument, while the
+                    // callees in the summary file free the arguments declared there.
+                    let summary = match func {
+                        Constant(c) => match c.ty().kind() {
+                            TyKind::FnDef(id, ..) => fn_summary::get(*id),
+                            _ => None,
+                        },
+                        _ => None,
+                    };
+                    let dropped = match summary {
+                        Some(summary) => summary.drops.iter().map(|arg| arg - 1).collect(),
+                        None => vec![0],
+                    };
+                    for idx in dropped {
+                        if idx >= args.len() {
+                            continue;
+                        }
                         let birth = self.scc_indices[bb_index];
-                        let place = match args[0].node {
+                        let place = match args[idx].node {
                             Operand::Copy(place) => place,
                             Operand::Move(place) => place,
                             _ => {
-                                rap_error!("Constant operand exists: {:?}", args[0]);
-                                return;
+                                rap_error!("Constant operand exists: {:?}", args[idx]);
+                                continue;
                             }
                         };
                         let drop_local = self.projection(tcx, false, place.clone());
@@ -115,10 +138,151 @@ impl<'tcx> SafeDropGraph<'tcx> {
         self.dead_record = backup_dead;
     }
 
+    /// Visit the paths from the entry within the budget of the function. Once it runs out, the
+    /// bugs found on the visited paths are kept and the whole function is checked again
+    /// path-insensitively, so the bugs on the paths not visited are still reported.
+    pub fn check_within_budget(&mut self, tcx: TyCtxt<'tcx>, fn_map: &MopAAResultMap) {
+        self.check(0, tcx, fn_map);
+        if self.budget.exhausted().is_none() {
+            return;
+        }
+        // restart from the initial state of the function
+        let body = tcx.optimized_mir(self.def_id);
+        let mut graph = SafeDropGraph::new(body, tcx, self.def_id, self.adt_owner.clone());
+        graph.contexts = std::mem::take(&mut self.contexts);
+        graph.solve_scc();
+        graph.bug_records = std::mem::replace(&mut self.bug_records, BugRecords::new());
+        graph.check_merged(tcx, fn_map);
+        self.bug_records = graph.bug_records;
+    }
+
+    /// The path-insensitive fallback: each SCC is visited once in topological order, starting from
+    /// the join of the states at the ends of the SCCs before it, so the aliases and the drops of all
+    /// paths are merged instead of enumerated.
+    fn check_merged(&mut self, tcx: TyCtxt<'tcx>, fn_map: &MopAAResultMap) {
+        let order = topo_sccs(&self.scc_indices, |block| &self.blocks[block].next);
+        // the states at the ends of the SCCs and the number of their successors yet to be visited
+        let mut out_states: FxHashMap<usize, (PathState, usize)> = FxHashMap::default();
+        let mut preds: FxHashMap<usize, Vec<usize>> = FxHashMap::default();
+        for scc in order {
+            for (nth, pred) in preds
+                .remove(&scc)
+                .unwrap_or_default()
+                .into_iter()
+                .enumerate()
+            {
+                let pending = &mut out_states.get_mut(&pred).unwrap().1;
+                *pending -= 1;
+                let state = if *pending == 0 {
+                    out_states.remove(&pred).unwrap().0
+                } else {
+                    out_states[&pred].0.clone()
+                };
+                if nth == 0 {
+                    self.set_path_state(state);
+                } else {
+                    self.join_path_state(&state);
+                }
+            }
+            let block = self.blocks[scc].clone();
+            for idx in std::iter::once(scc).chain(block.scc_sub_blocks.iter().copied()) {
+                self.alias_bb(idx, tcx);
+                self.alias_bbcall(idx, tcx, fn_map);
+                self.drop_check(idx, tcx);
+            }
+            if block.next.is_empty() && Self::should_check(self.def_id) {
+                self.dp_check(&block);
+            }
+            let successors = scc_successors(&self.scc_indices, &block.next, scc);
+            if successors.is_empty() {
+                continue;
+            }
+            for succ in successors.iter() {
+                preds.entry(*succ).or_default().push(scc);
+            }
+            out_states.insert(scc, (self.path_state(), successors.len()));
+        }
+    }
+
+    fn path_state(&self) -> PathState {
+        PathState {
+            values: self.values.clone(),
+            alias_set: self.alias_set.clone(),
+            dead_record: self.dead_record.clone(),
+            constant: self.constant.clone(),
+        }
+    }
+
+    fn set_path_state(&mut self, state: PathState) {
+        self.values = state.values;
+        self.alias_set = state.alias_set;
+        self.dead_record = state.dead_record;
+        self.constant = state.constant;
+    }
+
+    /// Join the state of another path into the current one: two values may alias if they do on
+    /// either path, and a value is dead if it is on either path. The values are matched by their
+    /// places, as the paths add the nodes of the fields in the order they visit them.
+    fn join_path_state(&mut self, other: &PathState) {
+        // the nodes of the other path -> the nodes of the current one
+        let mut nodes = vec![usize::MAX; other.values.len()];
+        let mut stack = Vec::new();
+        for (idx, value) in other.values.iter().enumerate() {
+            if value.field_id == usize::MAX {
+                nodes[idx] = idx;
+                stack.push(idx);
+            }
+        }
+        while let Some(idx) = stack.pop() {
+            for (field, child) in other.values[idx].fields.iter() {
+                if nodes[*child] != usize::MAX {
+                    continue;
+                }
+                let node = match self.values[nodes[idx]].fields.get(field) {
+                    Some(node) => *node,
+                    None => {
+                        let node = self.values.len();
+                        let mut value = other.values[*child].clone();
+                        value.index = node;
+                        value.fields = FxHashMap::default();
+                        self.values[nodes[idx]].fields.insert(*field, node);
+                        self.alias_set.push(node);
+                        self.dead_record.push(false);
+                        self.values.push(value);
+                        node
+                    }
+                };
+                nodes[*child] = node;
+                stack.push(*child);
+            }
+        }
+
+        for (idx, node) in nodes.iter().enumerate() {
+            if *node == usize::MAX {
+                continue;
+            }
+            let birth = other.values[idx].birth;
+            let value = &mut self.values[*node];
+            if birth == -1 || value.is_alive() && birth < value.birth {
+                value.birth = birth;
+            }
+            self.dead_record[*node] |= other.dead_record[idx];
+            let mut root = idx;
+            while other.alias_set[root] != root {
+                root = other.alias_set[root];
+            }
+            if nodes[root] != usize::MAX && !self.union_is_same(*node, nodes[root]) {
+                self.union_merge(*node, nodes[root]);
+            }
+        }
+        self.constant
+            .retain(|id, value| other.constant.get(id) == Some(value));
+    }
+
     // the core function of the safedrop.
     pub fn check(&mut self, bb_index: usize, tcx: TyCtxt<'tcx>, fn_map: &MopAAResultMap) {
         self.visit_times += 1;
-        if self.visit_times > VISIT_LIMIT {
+        if self.budget.is_exhausted(self.visit_times) {
             return;
         }
         let cur_block = self.blocks[self.scc_indices[bb_index]].clone();
@@ -267,11 +431,11 @@ impl<'tcx> SafeDropGraph<'tcx> {
 
         /* Handle cases if the current block is a merged scc block with sub block */
         if !cur_block.scc_sub_blocks.is_empty() {
-            match std::env::var_os("SAFEDROP") {
-                Some(val) if val == "0" => {
+            match precision::get().safedrop_merges_scc() {
+                true => {
                     order.push(cur_block.scc_sub_blocks.clone());
                 }
-                _ => {
+                false => {
                     self.calculate_scc_order(
                         &mut cur_block.scc_sub_blocks.clone(),
                         &mut vec![],
@@ -389,7 +553,7 @@ impl<'tcx> SafeDropGraph<'tcx> {
                 // Other cases in switchInt terminators
                 if let Some(targets) = sw_targets {
                     for iter in targets.iter() {
-                        if self.visit_times > VISIT_LIMIT {
+                        if self.budget.is_exhausted(self.visit_times) {
                             continue;
                         }
                         let next_index = iter.1.as_usize();
@@ -414,7 +578,7 @@ impl<'tcx> SafeDropGraph<'tcx> {
                     );
                 } else {
                     for i in cur_block.next {
-                        if self.visit_times > VISIT_LIMIT {
+                        if self.budget.is_exhausted(self.visit_times) {
                             continue;
                         }
                         let next_index = i;
@@ -549,3 +713,12 @@ impl<'tcx> SafeDropGraph<'tcx> {
         visit.remove(&idx);
     }
 }
+
+/// The state of a path, which the path-insensitive fallback joins where the paths meet.
+#[derive(Clone)]
+struct PathState {
+    values: Vec<ValueNode>,
+    alias_set: Vec<usize>,
+    dead_record: Vec<bool>,
+    constant: FxHashMap<usize, usize>,
+}
@@ -17,20 +17,27 @@ use rustc_middle::{
     mir::{BasicBlock, Operand, TerminatorKind},
     ty::{self, TyCtxt},
 };
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{HashMap, HashSet},
+    time::Instant,
+};
 use visitor::{BodyVisitor, CheckResult};
 
 use crate::{
     analysis::{
-        core::alias_analysis::{default::AliasAnalyzer, AAResult, AliasAnalysis},
+        context::AnalysisContext,
+        core::alias_analysis::{AAResult, AliasAnalysis},
         unsafety_isolation::{
             hir_visitor::{ContainsUnsafe, RelatedFnCollector},
             UnsafetyIsolationCheck,
         },
         utils::fn_info::*,
-        Analysis,
     },
     rap_info, rap_warn,
+    utils::{
+        diagnostic::{suppression::is_allowed, Diagnostic, Rule},
+        profile, scope,
+    },
 };
 
 macro_rules! cond_print {
@@ -58,18 +65,18 @@ impl<'tcx> SenryxCheck<'tcx> {
         }
     }
 
-    pub fn start(&mut self, check_level: CheckLevel, is_verify: bool) {
+    pub fn start(&mut self, ctx: &AnalysisContext<'tcx>, check_level: CheckLevel, is_verify: bool) {
         let tcx = self.tcx;
-        let mut analyzer = AliasAnalyzer::new(self.tcx);
-        analyzer.run();
-        let fn_map = &analyzer.get_all_fn_alias();
+        let fn_map = &ctx.alias().get_all_fn_alias();
         let related_items = RelatedFnCollector::collect(tcx);
         for vec in related_items.clone().values() {
             for (body_id, _span) in vec {
                 let (function_unsafe, block_unsafe) =
                     ContainsUnsafe::contains_unsafe(tcx, *body_id);
                 let def_id = tcx.hir_body_owner_def_id(*body_id).to_def_id();
-                if !Self::filter_by_check_level(tcx, &check_level, def_id) {
+                if !Self::filter_by_check_level(tcx, &check_level, def_id)
+                    || !scope::contains(tcx, def_id)
+                {
                     continue;
                 }
                 if block_unsafe
@@ -124,7 +131,9 @@ impl<'tcx> SenryxCheck<'tcx> {
     }
 
     pub fn check_soundness(&mut self, def_id: DefId, fn_map: &FxHashMap<DefId, AAResult>) {
+        let start = Instant::now();
         let check_results = self.body_visit_and_check(def_id, fn_map);
+        profile::record_time("senryx", def_id, start.elapsed());
         let tcx = self.tcx;
         if !check_results.is_empty() {
             Self::show_check_results(tcx, def_id, check_results);
@@ -220,23 +229,50 @@ impl<'tcx> SenryxCheck<'tcx> {
     }
 
     pub fn show_check_results(tcx: TyCtxt<'tcx>, def_id: DefId, check_results: Vec<CheckResult>) {
+        if is_allowed(tcx, def_id, Rule::UnsafePrecondition) {
+            return;
+        }
         rap_info!(
             "--------In safe function {:?}---------",
             get_cleaned_def_path_name(tcx, def_id)
         );
         for check_result in &check_results {
+            let failed_contracts: Vec<_> = check_result
+                .failed_contracts
+                .iter()
+                .map(|failed_contract| {
+                    let mut sps: Vec<_> = failed_contract.1.iter().cloned().collect();
+                    sps.sort();
+                    let diagnostic = Diagnostic::new(
+                        Rule::UnsafePrecondition,
+                        format!(
+                            "Argument {} of unsafe API `{}` may violate safety properties: {}.",
+                            failed_contract.0,
+                            check_result.func_name,
+                            sps.join(", ")
+                        ),
+                        check_result.func_span,
+                    )
+                    .function(tcx.def_path_str(def_id))
+                    .def_id(def_id)
+                    .related(tcx.def_span(def_id), "In this safe function.");
+                    (failed_contract, diagnostic)
+                })
+                .filter(|(_, diagnostic)| !diagnostic.is_baselined())
+                .collect();
             cond_print!(
-                !check_result.failed_contracts.is_empty(),
+                !failed_contracts.is_empty(),
                 "  Use unsafe api {:?}.",
                 check_result.func_name
             );
-            for failed_contract in &check_result.failed_contracts {
+            for (failed_contract, diagnostic) in failed_contracts {
                 cond_print!(
                     true,
                     "      Argument {}'s failed Sps: {:?}",
                     failed_contract.0,
                     failed_contract.1
                 );
+                diagnostic.emit();
             }
             for passed_contract in &check_result.passed_contracts {
                 cond_print!(
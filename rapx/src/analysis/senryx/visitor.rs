@@ -17,6 +17,7 @@ use crate::{
         Analysis,
     },
     rap_debug, rap_warn,
+    utils::{fn_summary, profile},
 };
 use rustc_middle::ty::GenericParamDefKind;
 use serde::de;
@@ -161,6 +162,7 @@ impl<'tcx> BodyVisitor<'tcx> {
 
         // get path and body
         let paths = self.get_all_paths();
+        profile::record_count("senryx", self.def_id, "paths", paths.len());
         // self.paths = paths.clone();
         let body = self.tcx.optimized_mir(self.def_id);
 
@@ -422,12 +424,25 @@ impl<'tcx> BodyVisitor<'tcx> {
         fn_span: Span,
         generic_mapping: FxHashMap<String, Ty<'tcx>>,
     ) {
+        // The callees in the summary file free the declared arguments, i.e., the objects they
+        // point to if they are pointers.
+        let summary = fn_summary::get(*def_id);
+        for arg in summary.iter().flat_map(|s| &s.drops) {
+            if let (false, arg_local) = get_arg_place(&args[arg - 1].node) {
+                let drop_local = self.chains.get_point_to_id(arg_local);
+                self.chains.set_drop(drop_local);
+            }
+        }
+
         if !self.tcx.is_mir_available(def_id) {
             self.insert_path_abstate(
                 path_index,
                 dst_place.local.as_usize(),
                 AbstractStateItem::new_default(),
             );
+            if summary.is_some() {
+                self.handle_ret_alias(dst_place, def_id, fn_map, args);
+            }
             return;
         }
 
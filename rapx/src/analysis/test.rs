@@ -1,11 +1,10 @@
 use crate::{
     analysis::{
+        context::AnalysisContext,
         core::{
-            alias_analysis::{default::AliasAnalyzer, AAResultMapWrapper, AliasAnalysis},
-            dataflow::{default::DataFlowAnalyzer, Arg2RetMapWrapper, DataFlowAnalysis},
-            ownedheap_analysis::{
-                default::OwnedHeapAnalyzer, OHAResultMapWrapper, OwnedHeapAnalysis,
-            },
+            alias_analysis::{AAResultMapWrapper, AliasAnalysis},
+            dataflow::{Arg2RetMapWrapper, DataFlowAnalysis},
+            ownedheap_analysis::OHAResultMapWrapper,
             range_analysis::{default::RangeAnalyzer, PathConstraintMapWrapper, RangeAnalysis},
         },
         Analysis,
@@ -24,20 +23,14 @@ impl<'tcx> Test<'tcx> {
         Self { tcx }
     }
 
-    pub fn start(&self) {
-        let mut alias_analysis = AliasAnalyzer::new(self.tcx);
-        alias_analysis.run();
-        let result = alias_analysis.get_local_fn_alias();
+    pub fn start(&self, ctx: &AnalysisContext<'tcx>) {
+        let result = ctx.alias().get_local_fn_alias();
         rap_info!("{}", AAResultMapWrapper(result));
 
-        let mut dataflow_analysis = DataFlowAnalyzer::new(self.tcx, false);
-        dataflow_analysis.run();
-        let dataflow = dataflow_analysis.get_all_arg2ret();
+        let dataflow = ctx.dataflow().get_all_arg2ret();
         rap_info!("{}", Arg2RetMapWrapper(dataflow));
 
-        let mut heap_analysis = OwnedHeapAnalyzer::new(self.tcx);
-        heap_analysis.run();
-        let result = heap_analysis.get_all_items();
+        let result = ctx.ownedheap().clone();
         rap_info!("{}", OHAResultMapWrapper(result));
 
         let mut range_analysis = RangeAnalyzer::<i128>::new(self.tcx, false);
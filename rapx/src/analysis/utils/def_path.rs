@@ -164,7 +164,8 @@ fn local_item_children_by_name(tcx: &TyCtxt<'_>, local_id: LocalDefId, name: Sym
     let hir_node = tcx.hir_node_by_def_id(local_id);
     let item_kind = match hir_node {
         Node::Crate(module) => {
-            root_mod = ItemKind::Mod(hir_node.ident().unwrap(), module);
+            // the crate root has no ident of its own
+            root_mod = ItemKind::Mod(Ident::with_dummy_span(tcx.crate_name(LOCAL_CRATE)), module);
             &root_mod
         }
         Node::Item(item) => &item.kind,
@@ -184,7 +185,17 @@ fn local_item_children_by_name(tcx: &TyCtxt<'_>, local_id: LocalDefId, name: Sym
         ItemKind::Mod(_ident, module) => module
             .item_ids
             .iter()
-            .filter_map(|&item_id| res(tcx.hir_ident(item_id.hir_id()), item_id.owner_id))
+            .flat_map(|&item_id| match tcx.hir_item(item_id).kind {
+                // the items of `extern` blocks belong to the enclosing module
+                ItemKind::ForeignMod { items, .. } => items
+                    .iter()
+                    .map(|foreign_item| (foreign_item.hir_id(), foreign_item.owner_id))
+                    .collect(),
+                _ => vec![(item_id.hir_id(), item_id.owner_id)],
+            })
+            // e.g., `impl` blocks and glob imports are unnamed
+            .filter(|(hir_id, _)| tcx.hir_opt_name(*hir_id).is_some())
+            .filter_map(|(hir_id, owner_id)| res(tcx.hir_ident(hir_id), owner_id))
             .collect(),
         ItemKind::Impl(r#impl) => r#impl
             .items
@@ -1,7 +1,10 @@
+use crate::config;
+use rapx::utils::{diagnostic::results::FailOn, log::rap_error_and_exit};
 use std::{
     env,
     path::{Path, PathBuf},
     sync::LazyLock,
+    time::Duration,
 };
 
 struct Arguments {
@@ -12,7 +15,14 @@ struct Arguments {
     /// options as second half after -- in args
     args_group2: Vec<String>,
     current_exe_path: PathBuf,
-    rap_clean: bool,
+    /// the switches set by the environment variables, which override the configuration file
+    rap_clean: Option<bool>,
+    rap_cache: Option<bool>,
+    rap_dep_summary: Option<bool>,
+    /// the options handled by `cargo rapx` itself, which are not forwarded to rapx
+    fail_on: Option<String>,
+    jobs: Option<String>,
+    timeout: Option<String>,
 }
 
 impl Arguments {
@@ -40,28 +50,38 @@ impl Arguments {
     }
 
     fn new() -> Self {
-        fn rap_clean() -> bool {
-            match env::var("RAP_CLEAN")
+        fn env_flag(name: &str) -> Option<bool> {
+            match env::var(name)
                 .ok()
                 .map(|s| s.trim().to_ascii_lowercase())
                 .as_deref()
             {
-                Some("false") => false,
-                _ => true, // clean is the preferred behavior
+                Some("false") => Some(false),
+                Some(_) => Some(true),
+                None => None,
             }
         }
 
         let args: Vec<_> = env::args().collect();
         let path = env::current_exe().expect("Current executable path invalid.");
         rap_trace!("Current exe: {path:?}\tReceived args: {args:?}");
-        let [args_group1, args_group2] = split_args_by_double_dash(&args);
+        let [mut args_group1, args_group2] = split_args_by_double_dash(&args);
+        absolutize_output_paths(&mut args_group1);
+        let fail_on = take_option(&mut args_group1, "-fail-on=");
+        let jobs = take_option(&mut args_group1, "-jobs=");
+        let timeout = take_option(&mut args_group1, "-timeout=");
 
         Arguments {
             args,
             args_group1,
             args_group2,
             current_exe_path: path,
-            rap_clean: rap_clean(),
+            rap_clean: env_flag("RAP_CLEAN"),
+            rap_cache: env_flag("RAP_CACHE"),
+            rap_dep_summary: env_flag("RAP_DEP_SUMMARY"),
+            fail_on,
+            jobs,
+            timeout,
         }
     }
 
@@ -81,8 +101,29 @@ impl Arguments {
     }
 }
 
+// cargo only checks the crates changed since the last run in the target folder of rapx, so there
+// is no need to clean
 pub fn rap_clean() -> bool {
     ARGS.rap_clean
+        .or(config::run_config().clean)
+        .unwrap_or(false)
+}
+
+pub fn rap_cache() -> bool {
+    ARGS.rap_cache
+        .or(config::run_config().cache)
+        .unwrap_or(true)
+}
+
+/// It is read in the rustc-wrapper phase, where `cargo rapx` has forwarded the configuration file
+/// in `RAP_DEP_SUMMARY`.
+pub fn rap_dep_summary() -> bool {
+    ARGS.rap_dep_summary.unwrap_or(true)
+}
+
+/// Forward the switch of the configuration file to the rustc-wrapper phase.
+pub fn rap_dep_summary_env() -> Option<bool> {
+    ARGS.rap_dep_summary.or(config::run_config().dep_summary)
 }
 
 fn split_args_by_double_dash(args: &[String]) -> [Vec<String>; 2] {
@@ -92,6 +133,48 @@ fn split_args_by_double_dash(args: &[String]) -> [Vec<String>; 2] {
     [rap_args, cargo_args]
 }
 
+/// Remove an option of `cargo rapx` itself from the options forwarded to rapx, and return the value
+/// of its last occurrence.
+fn take_option(rap_args: &mut Vec<String>, option: &str) -> Option<String> {
+    let value = rap_args
+        .iter()
+        .rev()
+        .find_map(|arg| arg.strip_prefix(option))
+        .map(str::to_owned);
+    rap_args.retain(|arg| !arg.starts_with(option));
+    value
+}
+
+/// Rustc is launched in the folder of each package, so the paths in `-sarif=<file>`,
+/// `-output=<dir>`, `-summaries=<file>`, the baseline options and the globs of `-include=<glob>`
+/// and `-exclude=<glob>` are made absolute to let all crates share them.
+///
+/// Whether `-baseline=<file>` compares with the baseline or records it is decided here once for
+/// the whole run, since the crates analyzed first would create the file otherwise.
+fn absolutize_output_paths(rap_args: &mut [String]) {
+    for arg in rap_args.iter_mut() {
+        for option in [
+            "-sarif=",
+            "-output=",
+            "-summaries=",
+            "-baseline=",
+            "-baseline-write=",
+            "-include=",
+            "-exclude=",
+        ] {
+            if let Some(path) = arg.strip_prefix(option) {
+                let path = std::path::absolute(path).expect("Invalid output path.");
+                let option = match option {
+                    "-baseline=" if !path.exists() => "-baseline-write=",
+                    _ => option,
+                };
+                *arg = format!("{}{}", option, path.display());
+                break;
+            }
+        }
+    }
+}
+
 static ARGS: LazyLock<Arguments> = LazyLock::new(Arguments::new);
 
 pub fn get_arg_flag_value(name: &str) -> Option<&'static str> {
@@ -126,6 +209,88 @@ pub fn filter_crate_type() -> bool {
     true
 }
 
+/// Returns true if a library crate is being compiled in rustc phase, i.e., the crates for which
+/// rapx computes the summaries used by the analysis of its dependents.
+pub fn is_compiling_lib() -> bool {
+    ARGS.args_group1.iter().any(|arg| arg.ends_with(".rs"))
+        && matches!(get_arg_flag_value("--crate-type"), Some("lib" | "rlib"))
+}
+
+/// The SARIF log specified by `-sarif=<path>` or by the configuration file.
+pub fn sarif_output() -> Option<&'static Path> {
+    ARGS.args_group1
+        .iter()
+        .find_map(|arg| arg.strip_prefix("-sarif="))
+        .map(Path::new)
+        .or(config::run_config().sarif.as_deref())
+}
+
+/// The findings failing the run, given by `-fail-on=<severity|rules>` or by the configuration file.
+pub fn fail_on() -> Option<FailOn> {
+    let spec = ARGS
+        .fail_on
+        .as_ref()
+        .or(config::run_config().fail_on.as_ref())?;
+    match FailOn::parse(spec) {
+        Ok(fail_on) => Some(fail_on),
+        Err(err) => rap_error_and_exit(err),
+    }
+}
+
+/// The number of package folders checked at the same time in the recursive modes, given by
+/// `-jobs=<n>` or by the configuration file; default to 1.
+pub fn jobs() -> usize {
+    match &ARGS.jobs {
+        Some(jobs) => match jobs.parse() {
+            Ok(jobs) if jobs > 0 => jobs,
+            _ => rap_error_and_exit(format!("Invalid number of jobs: {jobs}")),
+        },
+        None => config::run_config().jobs.unwrap_or(1),
+    }
+}
+
+/// The time to check each package folder, given by `-timeout=<secs>` or by the configuration file;
+/// default to an hour.
+pub fn timeout() -> Duration {
+    let secs = match &ARGS.timeout {
+        Some(secs) => match secs.parse() {
+            Ok(secs) => secs,
+            Err(_) => rap_error_and_exit(format!("Invalid number of seconds: {secs}")),
+        },
+        None => config::run_config().timeout.unwrap_or(60 * 60),
+    };
+    Duration::from_secs(secs)
+}
+
+/// The baseline recorded in this run, specified by `-baseline-write=<path>` or by `-baseline=<path>`
+/// if the file does not exist yet.
+pub fn baseline_output() -> Option<&'static Path> {
+    ARGS.args_group1
+        .iter()
+        .find_map(|arg| arg.strip_prefix("-baseline-write="))
+        .map(Path::new)
+}
+
+/// The `-C extra-filename` which cargo assigns to the unit being compiled in the rustc-wrapper
+/// phase.
+pub fn extra_filename() -> &'static str {
+    ARGS.args_group1
+        .iter()
+        .find_map(|arg| {
+            arg.strip_prefix("extra-filename=")
+                .or_else(|| arg.strip_prefix("-Cextra-filename="))
+        })
+        .unwrap_or_default()
+}
+
+/// The root source file of the crate being compiled in the rustc-wrapper phase.
+pub fn crate_root_file() -> Option<&'static Path> {
+    ARGS.args_group1
+        .iter()
+        .find(|arg| arg.ends_with(".rs"))
+        .map(Path::new)
+}
+
 pub fn get_arg(pos: usize) -> Option<&'static str> {
     ARGS.args.get(pos).map(|x| x.as_str())
 }
@@ -137,3 +302,12 @@ pub fn skip2() -> &'static [String] {
 pub fn current_exe_path() -> &'static Path {
     &ARGS.current_exe_path
 }
+
+/// The driver run on the local crates: the one in `RAP_DRIVER`, e.g., a driver with plugins, or the
+/// `rapx` next to `cargo-rapx`.
+pub fn rap_driver() -> PathBuf {
+    match env::var_os("RAP_DRIVER") {
+        Some(path) => std::path::absolute(path).expect("Invalid RAP_DRIVER path."),
+        None => current_exe_path().with_file_name("rapx"),
+    }
+}
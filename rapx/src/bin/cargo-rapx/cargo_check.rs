@@ -1,14 +1,63 @@
-use crate::args;
-use cargo_metadata::camino::Utf8Path;
-use rapx::utils::log::rap_error_and_exit;
-use std::{env, process::Command, time::Duration};
+use crate::{args, config};
+use cargo_metadata::{
+    camino::{Utf8Path, Utf8PathBuf},
+    Metadata, MetadataCommand,
+};
+use rapx::{
+    utils::{
+        cache::RAP_CACHE_DIR,
+        diagnostic::results::{self, RAP_RESULTS_DIR},
+        log::rap_error_and_exit,
+    },
+    RAP_FINGERPRINT,
+};
+use std::{
+    collections::BTreeSet,
+    env,
+    fs::{self, File},
+    hash::{DefaultHasher, Hash, Hasher},
+    io::{self, Write},
+    process::{self, Command},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
+};
 use wait_timeout::ChildExt;
 
+mod summary;
 mod workspace;
 
+/// The exit code of a run in which cargo succeeds but some findings match `-fail-on`.
+const GATED_EXIT_CODE: i32 = 2;
+
+/// The file in the build folder of rapx recording what the artifacts in it depend on, apart from
+/// the fingerprint tracked by cargo.
+const BUILD_STAMP: &str = "rapx-stamp";
+
+/// The folders where the rapx driver writes the findings of each crate in this run, see
+/// [`results`].
+static RESULTS_DIRS: Mutex<BTreeSet<Utf8PathBuf>> = Mutex::new(BTreeSet::new());
+
 pub fn run() {
-    match env::var("RAP_RECURSIVE")
+    // findings of each crate are merged into the SARIF log and the baseline, so remove the stale
+    // ones first
+    for output in [args::sarif_output(), args::baseline_output()]
+        .into_iter()
+        .flatten()
+    {
+        if output.exists() {
+            rap_trace!("remove stale output {}", output.display());
+            if let Err(err) = std::fs::remove_file(output) {
+                rap_error_and_exit(format!("Failed to remove {}: {err}", output.display()));
+            }
+        }
+    }
+    let fail_on = args::fail_on();
+    let succeeded = match env::var("RAP_RECURSIVE")
         .ok()
+        .or(config::run_config().recursive.clone())
         .map(|s| s.trim().to_ascii_lowercase())
         .as_deref()
     {
@@ -18,22 +67,95 @@ pub fn run() {
         _ => rap_error_and_exit(
             "`recursive` should only accept one the values: none, shallow or deep.",
         ),
+    };
+    let mut crates = Vec::new();
+    for dir in RESULTS_DIRS.lock().unwrap().iter() {
+        match results::read_results(dir.as_std_path()) {
+            Ok(results) => crates.extend(results),
+            Err(err) => rap_error_and_exit(format!("Failed to collect the findings: {err}")),
+        }
+    }
+    let gated = summary::report(&crates, fail_on.as_ref());
+    if !succeeded {
+        rap_error_and_exit("Finished with non-zero exit code.");
+    }
+    if gated > 0 {
+        process::exit(GATED_EXIT_CODE);
     }
 }
 
-fn cargo_check(dir: &Utf8Path) {
-    // always clean before check due to outdated except `RAP_CLEAN` is false
-    rap_trace!("cargo clean in package folder {dir}");
-    cargo_clean(dir, args::rap_clean());
+/// Check the package folders, `-jobs` of them at the same time, and return whether all of them
+/// succeed. All folders are checked even if some of them fail, so that the summary covers them.
+fn check_all(dirs: &[&Utf8Path]) -> bool {
+    let jobs = args::jobs().min(dirs.len());
+    if jobs <= 1 {
+        return dirs
+            .iter()
+            .fold(true, |succeeded, dir| cargo_check(dir, false) && succeeded);
+    }
+    rap_info!("Check {} package folders with {jobs} jobs.", dirs.len());
+    let next = AtomicUsize::new(0);
+    let succeeded = AtomicBool::new(true);
+    thread::scope(|scope| {
+        for _ in 0..jobs {
+            scope.spawn(|| {
+                while let Some(dir) = dirs.get(next.fetch_add(1, Ordering::Relaxed)) {
+                    if !cargo_check(dir, true) {
+                        succeeded.store(false, Ordering::Relaxed);
+                    }
+                }
+            });
+        }
+    });
+    succeeded.into_inner()
+}
 
-    rap_trace!("cargo check in package folder {dir}");
+/// Run cargo check in a package folder, and return whether it succeeds.
+///
+/// When it runs in `parallel` with other folders, the package has a build folder of its own, since
+/// cargo locks the build folder for the whole check, and its output is kept in a log file and
+/// printed at once when it finishes.
+fn cargo_check(dir: &Utf8Path, parallel: bool) -> bool {
+    let metadata = metadata(dir);
+    let forwarded = config::load(&metadata);
     let [rap_args, cargo_args] = args::rap_and_cargo_args();
     rap_trace!("rap_args={rap_args:?}\tcargo_args={cargo_args:?}");
 
+    // rapx builds in its own target folder, so that the build of the user is never touched and
+    // cargo only checks the crates whose sources or rapx options have changed
+    let rapx_dir = metadata.target_directory.join("rapx");
+    let package = metadata
+        .workspace_packages()
+        .into_iter()
+        .find(|package| package.manifest_path.parent() == Some(dir))
+        .map_or_else(|| hash_hex(dir), |package| package.name.to_string());
+    let build_dir = target_dir_arg(cargo_args).unwrap_or_else(|| {
+        if parallel {
+            rapx_dir.join("members").join(&package)
+        } else {
+            rapx_dir.join("build")
+        }
+    });
+    check_build_stamp(&build_dir, &forwarded);
+    // the SARIF log and the baseline are written from scratch, which needs all crates analyzed
+    let full_run = args::sarif_output().is_some() || args::baseline_output().is_some();
+    if args::rap_clean() && !args::rap_cache() {
+        rap_trace!("cargo clean {build_dir}");
+        cargo_clean(dir, &build_dir);
+    } else if args::rap_clean() || full_run {
+        rap_trace!("cargo clean workspace packages in {build_dir}");
+        cargo_clean_packages(dir, &build_dir, &metadata);
+    }
+
+    rap_trace!("cargo check in package folder {dir}");
+
     /*Here we prepare the cargo command as cargo check, which is similar to build, but much faster*/
     let mut cmd = Command::new("cargo");
     cmd.current_dir(dir);
     cmd.arg("check");
+    if target_dir_arg(cargo_args).is_none() {
+        cmd.arg("--target-dir").arg(&build_dir);
+    }
 
     /* set the target as a filter for phase_rustc_rap */
     cmd.args(cargo_args);
@@ -47,39 +169,168 @@ fn cargo_check(dir: &Utf8Path) {
         serde_json::to_string(rap_args).expect("Failed to serialize args."),
     );
 
+    // The configuration of each package is applied in the rustc-wrapper phase, and so are the
+    // switches of the whole run unless they are set by the environment.
+    cmd.env(config::RAP_CONFIG, config::to_env(&forwarded));
+    if let Some(dep_summary) = args::rap_dep_summary_env() {
+        cmd.env("RAP_DEP_SUMMARY", dep_summary.to_string());
+    }
+    if let (Err(_), Some(log)) = (env::var("RAP_LOG"), &config::run_config().log) {
+        cmd.env("RAP_LOG", log);
+    }
+
+    cmd.env(RAP_FINGERPRINT, fingerprint(rap_args, &forwarded));
+
+    // The findings of the crates which are not checked again are kept from the previous runs with
+    // the same cargo options.
+    let results_dir = rapx_dir.join("results").join(hash_hex(cargo_args));
+    if let Err(err) = fs::create_dir_all(&results_dir) {
+        rap_error_and_exit(format!("Failed to create {results_dir}: {err}"));
+    }
+    cmd.env(RAP_RESULTS_DIR, &results_dir);
+    RESULTS_DIRS.lock().unwrap().insert(results_dir);
+
+    // Summaries of unchanged functions are reused from the persistent cache.
+    if args::rap_cache() {
+        cmd.env(RAP_CACHE_DIR, rapx_dir.join("cache"));
+    }
+
     // Invoke actual cargo for the job, but with different flags.
     let cargo_rap_path = args::current_exe_path();
     cmd.env("RUSTC_WRAPPER", cargo_rap_path);
+    // the packages are compiled in their own folders
+    cmd.env("RAP_DRIVER", args::rap_driver());
+
+    let log = parallel.then(|| {
+        let log = rapx_dir.join("logs").join(format!("{package}.log"));
+        let created = fs::create_dir_all(rapx_dir.join("logs"))
+            .and_then(|()| File::create(&log))
+            .and_then(|file| {
+                cmd.stdout(file.try_clone()?);
+                cmd.stderr(file);
+                Ok(())
+            });
+        if let Err(err) = created {
+            rap_error_and_exit(format!("Failed to create the log {log}: {err}"));
+        }
+        log
+    });
 
     rap_trace!("Command is: {:?}.", cmd);
 
     let mut child = cmd.spawn().expect("Could not run cargo check.");
-    match child
-        .wait_timeout(Duration::from_secs(60 * 60)) // 1 hour timeout
+    let succeeded = match child
+        .wait_timeout(args::timeout())
         .expect("Failed to wait for subprocess.")
     {
-        Some(status) => {
-            if !status.success() {
-                rap_error_and_exit("Finished with non-zero exit code.");
-            }
-        }
+        Some(status) => status.success(),
         None => {
             child.kill().expect("Failed to kill subprocess.");
             child.wait().expect("Failed to wait for subprocess.");
-            rap_error_and_exit("Process killed due to timeout.");
+            rap_error!("Process killed due to timeout in {dir}.");
+            false
         }
     };
+    if let Some(log) = log {
+        print_log(dir, &log);
+    }
+    succeeded
 }
 
-fn cargo_clean(dir: &Utf8Path, really: bool) {
-    if really {
-        if let Err(err) = Command::new("cargo").arg("clean").current_dir(dir).output() {
-            rap_error_and_exit(format!("`cargo clean` exits unexpectedly:\n{err}"));
+/// Print the output of cargo check in a package folder at once, so that it is not interleaved with
+/// the output of the other folders.
+fn print_log(dir: &Utf8Path, log: &Utf8Path) {
+    let mut err = io::stderr().lock();
+    let _ = writeln!(err, "==> {dir}");
+    let _ = err.write_all(&fs::read(log).unwrap_or_default());
+}
+
+fn cargo_clean(dir: &Utf8Path, target_dir: &Utf8Path) {
+    let mut cmd = Command::new("cargo");
+    cmd.arg("clean").arg("--target-dir").arg(target_dir);
+    if let Err(err) = cmd.current_dir(dir).output() {
+        rap_error_and_exit(format!("`cargo clean` exits unexpectedly:\n{err}"));
+    }
+}
+
+/// Only clean the workspace packages, which are the crates analyzed by rapx. With the persistent
+/// cache, rapx runs on them again but only analyzes the changed functions, and the dependencies
+/// are not rebuilt.
+fn cargo_clean_packages(dir: &Utf8Path, target_dir: &Utf8Path, metadata: &Metadata) {
+    let mut cmd = Command::new("cargo");
+    cmd.arg("clean").arg("--target-dir").arg(target_dir);
+    cmd.current_dir(dir);
+    for pkg in metadata.workspace_packages() {
+        cmd.args(["-p", &pkg.name]);
+    }
+    if let Err(err) = cmd.output() {
+        rap_error_and_exit(format!("`cargo clean` exits unexpectedly:\n{err}"));
+    }
+}
+
+/// The target folder given by `--target-dir` among the options of cargo check, if any.
+fn target_dir_arg(cargo_args: &[String]) -> Option<Utf8PathBuf> {
+    let mut args = cargo_args.iter().take_while(|arg| *arg != "--");
+    while let Some(arg) = args.next() {
+        if arg == "--target-dir" {
+            return args.next().map(Utf8PathBuf::from);
+        }
+        if let Some(dir) = arg.strip_prefix("--target-dir=") {
+            return Some(dir.into());
+        }
+    }
+    None
+}
+
+fn hash_hex(value: impl Hash) -> String {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+/// The fingerprint of the version and the options of rapx. Cargo checks a local crate again once
+/// it changes, see [`RAP_FINGERPRINT`].
+fn fingerprint(rap_args: &[String], forwarded: &config::Forwarded) -> String {
+    hash_hex((
+        env!("CARGO_PKG_VERSION"),
+        args::rap_driver(),
+        rap_args,
+        config::to_env(forwarded),
+    ))
+}
+
+/// Clear the build folder of rapx if it was built by another version or driver of rapx, with or
+/// without the summaries of the dependencies, or with other crates to analyze. Cargo does not track them: the
+/// dependencies are never checked again, and neither are the local crates compiled by plain rustc.
+fn check_build_stamp(build_dir: &Utf8Path, forwarded: &config::Forwarded) {
+    let stamp = hash_hex((
+        env!("CARGO_PKG_VERSION"),
+        args::rap_driver(),
+        args::rap_dep_summary_env(),
+        forwarded.scopes(),
+    ));
+    let path = build_dir.join(BUILD_STAMP);
+    if fs::read_to_string(&path).is_ok_and(|old| old != stamp) {
+        rap_trace!("clear {build_dir} built with another stamp");
+        if let Err(err) = fs::remove_dir_all(build_dir) {
+            rap_error_and_exit(format!("Failed to clear {build_dir}: {err}"));
         }
     }
+    if let Err(err) = fs::create_dir_all(build_dir).and_then(|()| fs::write(&path, stamp)) {
+        rap_error_and_exit(format!("Failed to write {path}: {err}"));
+    }
+}
+
+fn metadata(dir: &Utf8Path) -> Metadata {
+    match MetadataCommand::new().current_dir(dir).no_deps().exec() {
+        Ok(metadata) => metadata,
+        Err(err) => rap_error_and_exit(format!(
+            "Failed to get the result of cargo metadata in {dir}:\n{err}"
+        )),
+    }
 }
 
 /// Just like running a cargo check in a folder.
-fn default_run() {
-    cargo_check(".".into());
+fn default_run() -> bool {
+    cargo_check(".".into(), false)
 }
@@ -0,0 +1,57 @@
+use rapx::utils::diagnostic::{
+    results::{CrateResults, FailOn},
+    Severity,
+};
+use std::collections::BTreeMap;
+
+/// Print the summary of the findings of all crates, and return the number of findings which match
+/// `-fail-on`.
+pub fn report(crates: &[CrateResults], fail_on: Option<&FailOn>) -> usize {
+    if crates.is_empty() {
+        return 0;
+    }
+    let findings: Vec<_> = crates.iter().flat_map(|krate| &krate.findings).collect();
+    let mut by_rule = BTreeMap::new();
+    for finding in findings.iter() {
+        *by_rule.entry(finding.rule.as_str()).or_insert(0) += 1;
+    }
+    let severities: Vec<_> = [Severity::Error, Severity::Warning, Severity::Note]
+        .into_iter()
+        .map(|severity| {
+            let count = findings
+                .iter()
+                .filter(|finding| finding.severity == severity.as_str())
+                .count();
+            format!("{count} {severity}")
+        })
+        .collect();
+    rap_info!(
+        "Summary: {} findings in {} analyzed crates ({})",
+        findings.len(),
+        crates.len(),
+        severities.join(", ")
+    );
+    for (rule, count) in by_rule {
+        rap_info!("    {rule}: {count}");
+    }
+
+    let Some(fail_on) = fail_on else {
+        return 0;
+    };
+    let gated: Vec<_> = findings
+        .iter()
+        .filter(|finding| fail_on.gates(finding))
+        .collect();
+    for finding in gated.iter() {
+        rap_error!(
+            "{}: [{}] {}",
+            finding.location,
+            finding.rule,
+            finding.message
+        );
+    }
+    if !gated.is_empty() {
+        rap_error!("{} findings fail the run with -fail-on", gated.len());
+    }
+    gated.len()
+}
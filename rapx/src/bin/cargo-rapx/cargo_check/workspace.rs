@@ -6,33 +6,21 @@ use rapx::utils::log::rap_error_and_exit;
 use std::collections::BTreeMap;
 
 /// Run cargo check in each member folder under current workspace.
-pub fn shallow_run() {
+pub fn shallow_run() -> bool {
     let cargo_toml = Utf8Path::new("Cargo.toml");
     if !cargo_toml.exists() {
         rap_error_and_exit("rapx should be run in a folder directly containing Cargo.toml");
     }
     let ws_metadata = workspace(cargo_toml);
-    check_members(&ws_metadata);
+    super::check_all(&get_member_folders(&ws_metadata))
 }
 
 /// Recursively run cargo check in each package folder from current folder.
-pub fn deep_run() {
+pub fn deep_run() -> bool {
     let cargo_tomls = get_cargo_tomls_deep_recursively(".");
-    for ws_metadata in workspaces(&cargo_tomls).values() {
-        check_members(ws_metadata);
-    }
-}
-
-fn check_members(ws_metadata: &Metadata) {
-    // Force clean even if `RAP_CLEAN` is false, because rapx is in control of
-    // caches for all packages and there should be no cache.
-    let ws_root = &ws_metadata.workspace_root;
-    rap_trace!("cargo clean in workspace root {ws_root}");
-    super::cargo_clean(ws_root, true);
-
-    for pkg_folder in get_member_folders(ws_metadata) {
-        super::cargo_check(pkg_folder);
-    }
+    let workspaces = workspaces(&cargo_tomls);
+    let member_folders: Vec<_> = workspaces.values().flat_map(get_member_folders).collect();
+    super::check_all(&member_folders)
 }
 
 fn get_member_folders(meta: &Metadata) -> Vec<&Utf8Path> {
@@ -0,0 +1,102 @@
+//! Load `rapx.toml` and the `[package.metadata.rapx]` sections in the cargo phase, and forward the
+//! configuration of each package to the rustc-wrapper phase through the `RAP_CONFIG` variable.
+
+use crate::args;
+use cargo_metadata::{camino::Utf8Path, Metadata, MetadataCommand};
+use rapx::utils::{
+    config::{RapxConfig, ResolvedConfig, CONFIG_FILE},
+    log::rap_error_and_exit,
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, env, path::Path, sync::LazyLock};
+
+pub const RAP_CONFIG: &str = "RAP_CONFIG";
+
+/// The configurations of the packages in a workspace, keyed by the package names.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Forwarded {
+    workspace: ResolvedConfig,
+    packages: BTreeMap<String, ResolvedConfig>,
+}
+
+impl Forwarded {
+    /// The paths to include and to exclude of the workspace and its packages, which decide the
+    /// crates compiled by rapx.
+    pub fn scopes(&self) -> Vec<(&[String], &[String])> {
+        std::iter::once(&self.workspace)
+            .chain(self.packages.values())
+            .map(|config| (config.include.as_slice(), config.exclude.as_slice()))
+            .collect()
+    }
+}
+
+/// The configuration of the workspace in the current folder, which holds the keys of the whole run.
+static RUN_CONFIG: LazyLock<RapxConfig> = LazyLock::new(|| {
+    let root = match MetadataCommand::new().no_deps().exec() {
+        Ok(metadata) => metadata.workspace_root.into_std_path_buf(),
+        // e.g., a folder of several workspaces checked with `RAP_RECURSIVE=deep`
+        Err(_) => env::current_dir().expect("Invalid current folder."),
+    };
+    load_file(&root)
+});
+
+pub fn run_config() -> &'static RapxConfig {
+    &RUN_CONFIG
+}
+
+fn load_file(root: &Path) -> RapxConfig {
+    match RapxConfig::from_file(&root.join(CONFIG_FILE)) {
+        Ok(config) => config.map(|c| c.rebase(root)).unwrap_or_default(),
+        Err(err) => rap_error_and_exit(err),
+    }
+}
+
+/// Resolve the configurations of the workspace and of its packages.
+pub fn load(metadata: &Metadata) -> Forwarded {
+    let root = metadata.workspace_root.as_std_path();
+    let workspace = load_file(root);
+    let mut packages = BTreeMap::new();
+    for pkg in metadata.workspace_packages() {
+        let dir = pkg.manifest_path.parent().unwrap_or(Utf8Path::new("."));
+        match RapxConfig::from_package_metadata(&pkg.name, &pkg.metadata) {
+            Ok(Some(config)) => {
+                let config = workspace.merge(&config.rebase(dir.as_std_path()));
+                packages.insert(pkg.name.clone(), config.resolve());
+            }
+            Ok(None) => {}
+            Err(err) => rap_error_and_exit(err),
+        }
+    }
+    Forwarded {
+        workspace: workspace.resolve(),
+        packages,
+    }
+}
+
+/// Serialize the configurations for the rustc-wrapper phase.
+pub fn to_env(forwarded: &Forwarded) -> String {
+    serde_json::to_string(forwarded).expect("Failed to serialize the configuration.")
+}
+
+/// The configuration of the crate being compiled in the rustc-wrapper phase.
+pub fn current_crate_config() -> ResolvedConfig {
+    let Ok(magic) = env::var(RAP_CONFIG) else {
+        return ResolvedConfig::default();
+    };
+    let mut forwarded: Forwarded =
+        serde_json::from_str(&magic).expect("Failed to deserialize RAP_CONFIG.");
+    env::var("CARGO_PKG_NAME")
+        .ok()
+        .and_then(|name| forwarded.packages.remove(&name))
+        .unwrap_or(forwarded.workspace)
+}
+
+/// Test if the crate being compiled in the rustc-wrapper phase is included by the configuration.
+pub fn is_current_crate_included(config: &ResolvedConfig) -> bool {
+    let Some(root_file) = args::crate_root_file() else {
+        return true;
+    };
+    let root_file = std::path::absolute(root_file).expect("Invalid crate root.");
+    let dir = root_file.parent().unwrap_or(Path::new("/"));
+    config.includes_crate(&root_file, dir)
+}
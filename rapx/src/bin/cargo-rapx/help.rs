@@ -13,9 +13,12 @@ Application:
 
 Analysis:
     -alias          perform alias analysis (meet-over-paths by default)
+    -alias=andersen perform alias analysis with a whole-program points-to analysis, which is
+                    faster on large crates but merges the aliases of all paths and calls
     -adg            generate API dependency graphs
     -audit          (under development) generate unsafe code audit units
     -callgraph      generate callgraphs
+    -callgraph=dot  generate callgraphs and dump them to callgraph_<crate>_<type>.dot
     -dataflow       generate dataflow graphs
     -ownedheap      analyze if the type holds a piece of memory on heap
     -pathcond       extract path constraints
@@ -26,6 +29,75 @@ General command:
     -help                        show help information
     -version                     show the version of RAPx
     -test-crate=<package_name>   specify the tested package in the workspace
+    -sarif=<file>                write the bugs detected by -F, -M, -O and -V to a SARIF 2.1.0 log
+    -baseline=<file>             only report the findings of -F, -M, -O and -V missing from the
+                                 baseline file; record the baseline if the file does not exist
+    -baseline-write=<file>       record the findings into the baseline file, replacing it
+    -fail-on=<severity|rules>    exit with code 2 if some findings are of the severity (error,
+                                 warning or note) or a more severe one, or of the rules, e.g.,
+                                 `-fail-on=double_free,use_after_free`; a summary of the findings
+                                 of all crates is printed at the end of each run
+    -jobs=<n>                    check <n> package folders at the same time with RAP_RECURSIVE,
+                                 each in a build folder of its own; default to 1
+    -timeout=<secs>              the time to check each package folder; default to 3600
+    -format=json                 write the results of analyses above to rapx_<crate>_<type>.json
+    -output=<dir>                the folder of the json files; default to the current folder
+    -profile                     print the time and peak memory of each analysis and the most
+                                 expensive functions; write them to rapx_profile_<crate>_<type>.json
+                                 in the -output folder
+    -summaries=<file>            read the effects of opaque callees (e.g., FFI functions) from a
+                                 TOML or JSON file: aliases, drops, transfers and allocates
+    -budget-visits=<n>           the path visits of each function in -alias and -F; default to 1000
+    -budget-time=<secs>          the time to visit the paths of each function; unlimited by default
+    -budget-z3=<ms>              the timeout of the Z3 solver in -M; default to 1000
+                                 the functions over budget are listed at the end of the analysis
+    -field-depth=<n>             the field depth of -alias and -F, overriding the one of -alias0..2
+                                 and -F0..2 (10, 20 and 30); default to 15 for -alias and 20 for -F
+    -context-depth=<k>           analyze the callees of the crate again for each calling context in
+                                 -alias and -F, up to <k> calls deep; the contexts are told apart by
+                                 the generic args, the constant arguments and the aliased arguments
+    -include=<glob>              only report the functions defined in the files matching the glob,
+                                 e.g., `-include=src/parser/**`; it may be given several times
+    -exclude=<glob>              skip the functions defined in the files matching the glob
+    -entry=<fn path>             only report the functions reachable from the function in the call
+                                 graph, e.g., `-entry=parser::parse`
+                                 the callees of the functions reported are still analyzed
+
+Configuration:
+    rapx.toml at the workspace root and [package.metadata.rapx] in Cargo.toml set the options
+    of each package, which are overridden by the ones on the command line, e.g.,
+
+        analyses = ["-F", "-M"]          # the options run by `cargo rapx` without arguments
+        field-depth = 20
+        context-depth = 1                # -context-depth
+        include = ["src/**"]             # the paths to analyze, relative to the file
+        exclude = ["src/generated/**"]   # the paths to skip
+        entry = ["parser::parse"]        # -entry
+        summaries = "summaries.toml"
+        format = "json"
+        output = "rapx-out"
+        sarif = "rapx.sarif"
+        [budget]
+        visits = 2000                    # -budget-visits
+        time = 10                        # -budget-time
+        z3 = 3000                        # -budget-z3
+
+    rapx.toml also takes fail-on, jobs and timeout, which are overridden by the options of the
+    same names, and log, clean, cache, dep-summary and recursive, which are overridden by the
+    environment variables below.
+
+Suppression:
+    #[rapx::allow(<rule>, ..)] on a function, an impl, a module or the crate suppresses the
+    findings of the rules in it. The rules are use_after_free, double_free, dangling_pointer,
+    memory_leak, unsafe_precondition, bounds_checking, encoding_checking, suboptimal_collection,
+    collection_initialization, reallocation, inefficient_iterator and memory_cloning. RAPx
+    registers the `rapx` tool and sets `--cfg rapx`, so wrap the attribute in
+    `#[cfg_attr(rapx, rapx::allow(..))]` to keep the crate compiling without RAPx.
+
+Lints:
+    The findings are emitted as the tool lints `rapx::<rule>`, warn by default. The lint levels
+    apply to them, e.g., `#[cfg_attr(rapx, deny(rapx::memory_leak))]`, `-- -D rapx::double_free`
+    or `--cap-lints`, and they are part of the JSON messages of `--message-format=json`.
 
 NOTE: multiple detections can be processed in single run by 
 appending the options to the arguments. Like `cargo rapx -F -M`
@@ -46,8 +118,27 @@ Environment Variables (Values are case insensitive):
                      warn: show bugs detected only.
 
     RAP_CLEAN        run cargo clean before check: true, false
+                     * false is the default value except that true is set
+                     * rapx builds in target/rapx/build, apart from the build of
+                       the user, where cargo only checks the crates whose sources,
+                       rapx version or rapx options have changed
+                     * with RAP_CACHE, only the workspace packages are cleaned
+                     * the workspace packages are always cleaned with -sarif or
+                       when a baseline is recorded, which need all crates analyzed
+
+    RAP_CACHE        reuse the analysis summaries of unchanged functions,
+                     stored in target/rapx/cache: true, false
+                     * true is the default value except that false is set
+                     * the range analysis is not cached
+
+    RAP_DEP_SUMMARY  compute alias summaries of dependency crates when they
+                     are compiled: true, false
                      * true is the default value except that false is set
 
+    RAP_DRIVER       the driver run in place of rapx, e.g., one built on the rapx
+                     library with plugins: the analyses of other crates, enabled by
+                     their flags like the ones above; `<driver> -plugins` lists them
+
     RAP_RECURSIVE    scope of packages to check: none, shallow, deep
                      * none or the variable not set: check for current folder
                      * shallow: check for current workpace members
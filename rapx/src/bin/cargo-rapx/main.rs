@@ -10,6 +10,7 @@ extern crate rapx;
 use rapx::utils::log::{init_log, rap_error_and_exit};
 
 mod args;
+mod config;
 mod help;
 
 mod utils;
@@ -21,16 +22,18 @@ fn phase_cargo_rap() {
     rap_trace!("Start cargo-rapx.");
 
     // here we skip two args: cargo rapx
-    let Some(arg) = args::get_arg(2) else {
+    let arg = args::get_arg(2);
+    // the analyses may be given by rapx.toml instead
+    if arg.is_none() && config::run_config().analyses.is_none() {
         rap_error!("Expect command: e.g., `cargo rapx -help`.");
         return;
-    };
+    }
     match arg {
-        "-version" | "-v" | "--version" => {
+        Some("-version" | "-v" | "--version") => {
             println!("{}", help::RAPX_VERSION);
             return;
         }
-        "-help" | "-h" | "--help" => {
+        Some("-help" | "-h" | "--help") => {
             println!("{}", help::RAPX_HELP);
             return;
         }
@@ -44,9 +47,23 @@ fn phase_rustc_wrapper() {
     rap_trace!("Launch cargo-rapx again triggered by cargo check.");
 
     let is_direct = args::is_current_compile_crate();
-    // rapx only checks local crates
+    if is_direct {
+        clear_unit_results();
+    }
+    // rapx only checks local crates, unless they are excluded by the configuration
     if is_direct && args::filter_crate_type() {
-        run_rap();
+        let config = config::current_crate_config();
+        if config::is_current_crate_included(&config) {
+            run_rap(config.args);
+            return;
+        }
+        rap_trace!("skip analyzing the crate excluded by the configuration");
+    }
+
+    // the summaries of dependency libraries let the analysis of local crates follow the calls
+    // into them
+    if !is_direct && args::is_compiling_lib() && args::rap_dep_summary() {
+        run_rap_dep_summary();
         return;
     }
 
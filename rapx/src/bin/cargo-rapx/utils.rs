@@ -1,16 +1,11 @@
 use crate::args;
+use rapx::utils::diagnostic::results::{self, RAP_RESULTS_DIR};
 use std::{
-    env,
-    path::PathBuf,
+    env, fs,
+    path::Path,
     process::{self, Command},
 };
 
-fn find_rap() -> PathBuf {
-    let mut path = args::current_exe_path().to_owned();
-    path.set_file_name("rapx");
-    path
-}
-
 pub fn run_cmd(mut cmd: Command) {
     rap_trace!("Command is: {:?}.", cmd);
     match cmd.status() {
@@ -29,12 +24,39 @@ pub fn run_rustc() {
     run_cmd(cmd);
 }
 
-pub fn run_rap() {
-    let mut cmd = Command::new(find_rap());
+/// Run rapx on a local crate with the options in the configuration file and then the ones on the
+/// command line, so that the latter take precedence.
+pub fn run_rap(config_args: Vec<String>) {
+    let mut cmd = Command::new(args::rap_driver());
     cmd.args(args::skip2());
     let magic = env::var("RAP_ARGS").expect("Missing RAP_ARGS.");
     let rap_args: Vec<String> =
         serde_json::from_str(&magic).expect("Failed to deserialize RAP_ARGS.");
+    cmd.args(config_args);
     cmd.args(rap_args);
     run_cmd(cmd);
 }
+
+/// Compile a dependency crate with rapx to compute its summaries.
+pub fn run_rap_dep_summary() {
+    let mut cmd = Command::new(args::rap_driver());
+    cmd.args(args::skip2());
+    cmd.arg("-dep-summary");
+    run_cmd(cmd);
+}
+
+/// Remove the findings of the unit being compiled left by the previous runs; they are written again
+/// if the unit is analyzed.
+pub fn clear_unit_results() {
+    let (Some(dir), Some(krate)) = (
+        env::var_os(RAP_RESULTS_DIR),
+        args::get_arg_flag_value("--crate-name"),
+    ) else {
+        return;
+    };
+    let path = results::unit_file(Path::new(&dir), krate, args::extra_filename());
+    if path.exists() {
+        rap_trace!("remove the findings of the previous run {}", path.display());
+        let _ = fs::remove_file(path);
+    }
+}
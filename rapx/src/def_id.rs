@@ -102,6 +102,10 @@ macro_rules! intrinsics {
 // for #![no_std] crates, intrinsics fn paths start from core instead of core.
 // cc https://github.com/Artisan-Lab/RAPx/issues/190
 intrinsics! {
+    arith_offset: &[
+        "std::intrinsics::arith_offset",
+        "core::intrinsics::arith_offset"
+    ],
     assume_init_drop: &[
         "std::mem::MaybeUninit::<T>::assume_init_drop",
         "core::mem::MaybeUninit::<T>::assume_init_drop"
@@ -134,6 +138,10 @@ intrinsics! {
         "std::alloc::dealloc",
         "alloc::alloc::dealloc"
     ],
+    const_expose_provenance: &[
+        "std::ptr::const_ptr::<impl *const T>::expose_provenance",
+        "core::ptr::const_ptr::<impl *const T>::expose_provenance"
+    ],
     drop: &[
         "std::mem::drop",
         "core::mem::drop",
@@ -146,6 +154,18 @@ intrinsics! {
         "std::mem::ManuallyDrop::<T>::drop",
         "core::mem::ManuallyDrop::<T>::drop"
     ],
+    mut_expose_provenance: &[
+        "std::ptr::mut_ptr::<impl *mut T>::expose_provenance",
+        "core::ptr::mut_ptr::<impl *mut T>::expose_provenance"
+    ],
+    with_exposed_provenance: &[
+        "std::ptr::with_exposed_provenance",
+        "core::ptr::with_exposed_provenance"
+    ],
+    with_exposed_provenance_mut: &[
+        "std::ptr::with_exposed_provenance_mut",
+        "core::ptr::with_exposed_provenance_mut"
+    ],
 }
 
 /// rustc_public DefId to internal DefId
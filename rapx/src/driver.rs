@@ -0,0 +1,167 @@
+//! The entry of the rapx driver, which cargo-rapx runs in place of rustc.
+
+use crate::{
+    rap_info, rap_trace,
+    utils::{
+        diagnostic::baseline::BaselineMode,
+        log::{init_log, rap_error_and_exit},
+    },
+    OutputFormat, RapCallback, RAP_DEFAULT_ARGS, RAP_DEP_SUMMARY_ARGS,
+};
+use regex::Regex;
+use rustc_session::{config::ErrorOutputType, EarlyDiagCtxt};
+use std::{env, time::Duration};
+
+fn run_complier(args: &mut Vec<String>, callback: &mut RapCallback) {
+    // Finally, add the default flags all the way in the beginning, but after the binary name.
+    let default_args = if callback.is_dep_summary_enabled() {
+        RAP_DEP_SUMMARY_ARGS
+    } else {
+        RAP_DEFAULT_ARGS
+    };
+    args.splice(1..1, default_args.iter().map(ToString::to_string));
+
+    let handler = EarlyDiagCtxt::new(ErrorOutputType::default());
+    rustc_driver::init_rustc_env_logger(&handler);
+    rustc_driver::install_ice_hook("bug_report_url", |_| ());
+
+    rustc_driver::run_compiler(args, callback);
+    rap_trace!("The arg for compilation is {:?}", args);
+}
+
+/// Run rapx as rustc with the arguments of the process. A driver built on this crate registers its
+/// plugins into `compiler` before, and their flags are recognized as the ones of the built-in
+/// analyses; `-plugins` lists them.
+pub fn run(mut compiler: RapCallback) {
+    // Parse the arguments from env.
+    let mut args = vec![];
+    let re_test_crate = Regex::new(r"-test-crate=(\S*)").unwrap();
+
+    for arg in env::args() {
+        if let Some((_full, [test_crate_name])) =
+            re_test_crate.captures(&arg).map(|caps| caps.extract())
+        {
+            compiler.set_test_crate(test_crate_name.to_owned());
+            continue;
+        }
+        if let Some(path) = arg.strip_prefix("-sarif=") {
+            compiler.set_sarif_output(path);
+            continue;
+        }
+        if let Some(path) = arg.strip_prefix("-baseline=") {
+            compiler.set_baseline(BaselineMode::detect(path));
+            continue;
+        }
+        if let Some(path) = arg.strip_prefix("-baseline-write=") {
+            compiler.set_baseline(BaselineMode::Write(path.into()));
+            continue;
+        }
+        if let Some(path) = arg.strip_prefix("-summaries=") {
+            compiler.set_summary_file(path);
+            continue;
+        }
+        if let Some(visits) = arg.strip_prefix("-budget-visits=") {
+            match visits.parse() {
+                Ok(visits) => compiler.set_visit_budget(visits),
+                Err(_) => rap_error_and_exit(format!("Invalid number of visits: {visits}")),
+            }
+            continue;
+        }
+        if let Some(secs) = arg.strip_prefix("-budget-time=") {
+            match secs.parse() {
+                Ok(secs) => compiler.set_time_budget(Duration::from_secs(secs)),
+                Err(_) => rap_error_and_exit(format!("Invalid number of seconds: {secs}")),
+            }
+            continue;
+        }
+        if let Some(ms) = arg.strip_prefix("-budget-z3=") {
+            match ms.parse() {
+                Ok(ms) => compiler.set_z3_timeout(ms),
+                Err(_) => rap_error_and_exit(format!("Invalid number of milliseconds: {ms}")),
+            }
+            continue;
+        }
+        if let Some(depth) = arg.strip_prefix("-field-depth=") {
+            match depth.parse() {
+                Ok(depth) => compiler.set_field_depth(depth),
+                Err(_) => rap_error_and_exit(format!("Invalid field depth: {depth}")),
+            }
+            continue;
+        }
+        if let Some(depth) = arg.strip_prefix("-context-depth=") {
+            match depth.parse() {
+                Ok(depth) => compiler.set_context_depth(depth),
+                Err(_) => rap_error_and_exit(format!("Invalid context depth: {depth}")),
+            }
+            continue;
+        }
+        if let Some(glob) = arg.strip_prefix("-include=") {
+            compiler.include_files(glob);
+            continue;
+        }
+        if let Some(glob) = arg.strip_prefix("-exclude=") {
+            compiler.exclude_files(glob);
+            continue;
+        }
+        if let Some(path) = arg.strip_prefix("-entry=") {
+            compiler.add_entry(path);
+            continue;
+        }
+        if let Some(dir) = arg.strip_prefix("-output=") {
+            compiler.set_output_dir(dir);
+            continue;
+        }
+        if arg == "-plugins" {
+            for plugin in compiler.plugins() {
+                println!("{plugin}");
+            }
+            return;
+        }
+        if compiler.enable_plugin(&arg) {
+            continue;
+        }
+        match arg.as_str() {
+            "-alias" | "-alias0" | "-alias1" | "-alias2" | "-alias=mop" | "-alias=andersen" => {
+                compiler.enable_alias(arg)
+            }
+            "-adg" => compiler.enable_api_dependency(), // api dependency graph
+            "-callgraph" => compiler.enable_callgraph(1),
+            "-callgraph=dot" => compiler.enable_callgraph(2),
+            "-dataflow" => compiler.enable_dataflow(1),
+            "-dataflow=debug" => compiler.enable_dataflow(2),
+            "-ownedheap" => compiler.enable_ownedheap(),
+            "-range" => compiler.enable_range_analysis(1),
+            "-range=print_mir" => compiler.enable_range_analysis(2),
+            "-pathcond" => compiler.enable_range_analysis(3),
+            "-test" => compiler.enable_test(),
+            "-F" | "-F0" | "-F1" | "-F2" | "-uaf" => compiler.enable_safedrop(arg),
+            "-I" | "-infer" => compiler.enable_infer(),
+            "-M" | "-mleak" => compiler.enable_rcanary(),
+            "-V" | "-verify" => compiler.enable_verify(),
+            "-O" | "-opt" => compiler.enable_opt(1),
+            "-opt=all" => compiler.enable_opt(2),
+            "-opt=report" => compiler.enable_opt(0),
+            "-scan" => compiler.enable_scan(),
+            "-ssa" => compiler.enable_ssa_transform(),
+            "-audit" => compiler.enable_unsafety_isolation(1),
+            "-doc" => compiler.enable_unsafety_isolation(2),
+            "-upg" => compiler.enable_unsafety_isolation(3),
+            "-ucons" => compiler.enable_unsafety_isolation(4),
+            "-verify-std" => compiler.enable_verify_std(),
+            "-mir" => compiler.enable_show_mir(),
+            "-format=json" => compiler.set_output_format(OutputFormat::Json),
+            "-format=text" => compiler.set_output_format(OutputFormat::Text),
+            "-dep-summary" => compiler.enable_dep_summary(),
+            "-profile" => compiler.enable_profile(),
+            _ => args.push(arg),
+        }
+    }
+    _ = init_log().inspect_err(|err| eprintln!("Failed to init log: {err}"));
+    if !compiler.is_dep_summary_enabled() {
+        rap_info!("Start analysis with RAPx.");
+    }
+    rap_trace!("rap received arguments: {:#?}", env::args());
+    rap_trace!("arguments to rustc: {:?}", &args);
+
+    run_complier(&mut args, &mut compiler);
+}
@@ -6,6 +6,8 @@
 pub mod utils;
 pub mod analysis;
 pub mod def_id;
+pub mod driver;
+pub mod plugin;
 pub mod preprocess;
 extern crate intervals;
 extern crate rustc_abi;
@@ -18,6 +20,7 @@ extern crate rustc_hir_pretty;
 extern crate rustc_index;
 extern crate rustc_infer;
 extern crate rustc_interface;
+extern crate rustc_lint;
 extern crate rustc_metadata;
 extern crate rustc_middle;
 extern crate rustc_public;
@@ -30,16 +33,27 @@ extern crate rustc_type_ir;
 extern crate thin_vec;
 use crate::analysis::scan::ScanAnalysis;
 use analysis::{
+    context::AnalysisContext,
     core::{
-        alias_analysis::{default::AliasAnalyzer, AAResultMapWrapper, AliasAnalysis},
+        alias_analysis::{
+            default::summary, serialize::AAResultMapJson, AAResultMapWrapper, AliasAnalysis,
+            AliasBackend,
+        },
         api_dependency::ApiDependencyAnalyzer,
-        callgraph::{default::CallGraphAnalyzer, CallGraphAnalysis, CallGraphDisplay},
+        callgraph::{
+            serialize::{CallGraphDot, CallGraphJson},
+            CallGraphDisplay,
+        },
         dataflow::{
-            default::DataFlowAnalyzer, Arg2RetMapWrapper, DataFlowAnalysis, DataFlowGraphMapWrapper,
+            default::DataFlowAnalyzer,
+            serialize::{Arg2RetMapJson, DataFlowGraphMapJson},
+            Arg2RetMapWrapper, DataFlowAnalysis, DataFlowGraphMapWrapper,
         },
-        ownedheap_analysis::{default::OwnedHeapAnalyzer, OHAResultMapWrapper, OwnedHeapAnalysis},
+        ownedheap_analysis::{serialize::OHAResultMapJson, OHAResultMapWrapper},
         range_analysis::{
-            default::RangeAnalyzer, PathConstraintMapWrapper, RAResultMapWrapper, RangeAnalysis,
+            default::RangeAnalyzer,
+            serialize::{PathConstraintMapJson, RAResultMapJson},
+            PathConstraintMapWrapper, RAResultMapWrapper, RangeAnalysis,
         },
         ssa_transform::SSATrans,
     },
@@ -52,6 +66,7 @@ use analysis::{
     utils::show_mir::ShowMir,
     Analysis,
 };
+use plugin::Plugin;
 use rustc_ast::ast;
 use rustc_driver::{Callbacks, Compilation};
 use rustc_interface::{
@@ -60,20 +75,50 @@ use rustc_interface::{
 };
 use rustc_middle::{ty::TyCtxt, util::Providers};
 use rustc_session::search_paths::PathKind;
+use rustc_span::Symbol;
+use std::panic::{self, AssertUnwindSafe};
 use std::path::PathBuf;
-use std::{env, sync::Arc};
+use std::time::Duration;
+use std::{fs, sync::Arc};
+use utils::{
+    budget::{self, Budget},
+    diagnostic::{
+        baseline::{self, BaselineMode},
+        lint, results,
+        sarif::write_sarif,
+        suppression, take_diagnostics,
+    },
+    fn_summary,
+    json::JsonReport,
+    log::rap_error_and_exit,
+    precision::{self, Precision},
+    profile,
+    scope::{self, ScopeFilters},
+};
 
 // Insert rustc arguments at the beginning of the argument list that RAP wants to be
 // set per default, for maximal validation power.
-pub static RAP_DEFAULT_ARGS: &[&str] = &["-Zalways-encode-mir", "-Zmir-opt-level=0"];
+// `rapx` is set as a cfg so that the analyzed crates may carry `#[cfg_attr(rapx, rapx::allow(..))]`
+// attributes; the tool itself is registered after parsing the crate root.
+pub static RAP_DEFAULT_ARGS: &[&str] = &["-Zalways-encode-mir", "-Zmir-opt-level=0", "--cfg=rapx"];
+
+// The default arguments for computing the summaries of a dependency crate. Its metadata is left as
+// it is, since the summaries stand in for the MIR of its functions.
+pub static RAP_DEP_SUMMARY_ARGS: &[&str] = &["-Zmir-opt-level=0"];
+
+/// The environment variable holding the fingerprint of the version and the options of rapx, which
+/// `cargo rapx` computes for each run. It is recorded in the dep-info of the crates compiled by
+/// rapx, so that cargo checks them again once the fingerprint changes.
+pub const RAP_FINGERPRINT: &str = "RAP_FINGERPRINT";
 
 /// This is the data structure to handle rapx options as a rustc callback.
 
 #[derive(Debug, Clone, Hash)]
 pub struct RapCallback {
     alias: bool,
+    alias_backend: AliasBackend,
     api_dependency: bool,
-    callgraph: bool,
+    callgraph: usize,
     dataflow: usize,
     ownedheap: bool,
     range: usize,
@@ -89,6 +134,27 @@ pub struct RapCallback {
     verify_std: bool,
     scan: bool,
     test_crate: Option<String>,
+    sarif: Option<PathBuf>,
+    baseline: Option<BaselineMode>,
+    format: OutputFormat,
+    output: Option<PathBuf>,
+    dep_summary: bool,
+    profile: bool,
+    summaries: Option<PathBuf>,
+    budget: Budget,
+    precision: Precision,
+    scope: ScopeFilters,
+    plugins: Vec<Plugin>,
+}
+
+/// The format in which the results of the core analyses are emitted.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    /// Print the results to the log.
+    #[default]
+    Text,
+    /// Write the results to a JSON file keyed by def paths.
+    Json,
 }
 
 #[allow(clippy::derivable_impls)]
@@ -96,8 +162,9 @@ impl Default for RapCallback {
     fn default() -> Self {
         Self {
             alias: false,
+            alias_backend: AliasBackend::Mop,
             api_dependency: false,
-            callgraph: false,
+            callgraph: 0,
             dataflow: 0,
             ownedheap: false,
             range: 0,
@@ -113,12 +180,31 @@ impl Default for RapCallback {
             verify_std: false,
             scan: false,
             test_crate: None,
+            sarif: None,
+            baseline: None,
+            format: OutputFormat::Text,
+            output: None,
+            dep_summary: false,
+            profile: false,
+            summaries: None,
+            budget: Budget::default(),
+            precision: Precision::default(),
+            scope: ScopeFilters::default(),
+            plugins: Vec::new(),
         }
     }
 }
 
 impl Callbacks for RapCallback {
     fn config(&mut self, config: &mut Config) {
+        config.register_lints = Some(Box::new(lint::register_lints));
+        config.psess_created = Some(Box::new(|psess| {
+            let fingerprint = std::env::var(RAP_FINGERPRINT).ok();
+            psess.env_depinfo.get_mut().insert((
+                Symbol::intern(RAP_FINGERPRINT),
+                fingerprint.as_deref().map(Symbol::intern),
+            ));
+        }));
         config.override_queries = Some(|_, providers| {
             providers.extern_queries.used_crate_source = |tcx, cnum| {
                 let mut providers = Providers::default();
@@ -135,10 +221,14 @@ impl Callbacks for RapCallback {
 
     fn after_crate_root_parsing(
         &mut self,
-        _compiler: &interface::Compiler,
-        _krate: &mut ast::Crate,
+        compiler: &interface::Compiler,
+        krate: &mut ast::Crate,
     ) -> Compilation {
-        preprocess::ssa_preprocess::create_ssa_struct(_krate);
+        // dependency crates are compiled as they are
+        if !self.is_dep_summary_enabled() {
+            suppression::register_tool(krate, &compiler.sess.psess.attr_id_generator);
+            preprocess::ssa_preprocess::create_ssa_struct(krate);
+        }
         Compilation::Continue
     }
     fn after_analysis<'tcx>(&mut self, _compiler: &Compiler, tcx: TyCtxt<'tcx>) -> Compilation {
@@ -146,7 +236,13 @@ impl Callbacks for RapCallback {
 
         rustc_public::rustc_internal::run(tcx, || {
             def_id::init(tcx);
-            if self.is_building_test_crate() {
+            fn_summary::init(tcx, self.summary_file().map(PathBuf::as_path));
+            budget::init(self.budget.clone());
+            precision::init(self.precision.clone());
+            profile::init(self.profile);
+            if self.is_dep_summary_enabled() {
+                dump_dep_summary(tcx);
+            } else if self.is_building_test_crate() {
                 start_analyzer(tcx, self);
             } else {
                 let package_name = std::env::var("CARGO_PKG_NAME")
@@ -176,25 +272,20 @@ impl RapCallback {
 
     /// Enable alias analysis. The parameter is used to config the threshold of alias analysis.
     /// Currently, we mainly use it to control the depth of field-sensitive analysis.
+    /// -alias: keep the default field depth limit, 15.
     /// -alias0: set field depth limit to 10; do not distinguish different flows within a each
     /// strongly-connected component.
-    /// -alias1: set field depth limit to 20 (this is default setting).
+    /// -alias1: set field depth limit to 20.
     /// -alias2: set field depth limit to 30.
+    /// -alias=andersen: use the whole-program points-to analysis instead of MoP.
     pub fn enable_alias(&mut self, arg: String) {
         self.alias = true;
         match arg.as_str() {
-            "-alias" => {
-                env::set_var("ALIAS", "1");
-            }
-            "-alias0" => {
-                env::set_var("ALIAS", "0");
-            }
-            "-alias1" => {
-                env::set_var("ALIAS", "1");
-            }
-            "-alias2" => {
-                env::set_var("ALIAS", "2");
-            }
+            "-alias1" => self.precision.mop = Some(1),
+            "-alias0" => self.precision.mop = Some(0),
+            "-alias2" => self.precision.mop = Some(2),
+            "-alias=mop" => self.alias_backend = AliasBackend::Mop,
+            "-alias=andersen" => self.alias_backend = AliasBackend::Andersen,
             _ => {}
         }
     }
@@ -204,6 +295,11 @@ impl RapCallback {
         self.alias
     }
 
+    /// Return the alias analysis chosen by `-alias=<backend>`.
+    pub fn alias_backend(&self) -> AliasBackend {
+        self.alias_backend
+    }
+
     /// Enable API-dependency graph generation.
     pub fn enable_api_dependency(&mut self) {
         self.api_dependency = true;
@@ -214,13 +310,13 @@ impl RapCallback {
         self.api_dependency
     }
 
-    /// Enable call-graph analysis.
-    pub fn enable_callgraph(&mut self) {
-        self.callgraph = true;
+    /// Enable call-graph analysis; the graph is also dumped in the DOT format if `x` is 2.
+    pub fn enable_callgraph(&mut self, x: usize) {
+        self.callgraph = x;
     }
 
     /// Test if call-graph analysis is enabled.
-    pub fn is_callgraph_enabled(&self) -> bool {
+    pub fn is_callgraph_enabled(&self) -> usize {
         self.callgraph
     }
 
@@ -299,29 +395,13 @@ impl RapCallback {
     /// field-sensitive analysis.
     pub fn enable_safedrop(&mut self, arg: String) {
         self.safedrop = true;
-        match arg.as_str() {
-            "-F" => {
-                env::set_var("SAFEDROP", "1");
-                env::set_var("MOP", "1");
-            }
-            "-F0" => {
-                env::set_var("SAFEDROP", "0");
-                env::set_var("MOP", "0");
-            }
-            "-F1" => {
-                env::set_var("SAFEDROP", "1");
-                env::set_var("MOP", "1");
-            }
-            "-F2" => {
-                env::set_var("SAFEDROP", "2");
-                env::set_var("MOP", "2");
-            }
-            "-uaf" => {
-                env::set_var("SAFEDROP", "1");
-                env::set_var("MOP", "1");
-            }
-            _ => {}
-        }
+        let level = match arg.as_str() {
+            "-F0" => 0,
+            "-F2" => 2,
+            _ => 1,
+        };
+        self.precision.safedrop = Some(level);
+        self.precision.mop = Some(level);
     }
 
     /// Test if safedrop is enabled.
@@ -382,15 +462,210 @@ impl RapCallback {
     pub fn set_test_crate(&mut self, crate_name: impl ToString) {
         self.test_crate = Some(crate_name.to_string())
     }
+
+    /// Write the findings of the bug-finding analyses to the given SARIF log.
+    pub fn set_sarif_output(&mut self, path: impl Into<PathBuf>) {
+        self.sarif = Some(path.into())
+    }
+
+    /// Return the path of the SARIF log if SARIF output is enabled.
+    pub fn sarif_output(&self) -> Option<&PathBuf> {
+        self.sarif.as_ref()
+    }
+
+    /// Compare the findings with a baseline, or record them into it.
+    pub fn set_baseline(&mut self, mode: BaselineMode) {
+        self.baseline = Some(mode)
+    }
+
+    pub fn baseline(&self) -> Option<&BaselineMode> {
+        self.baseline.as_ref()
+    }
+
+    /// Set the format of the results of the core analyses.
+    pub fn set_output_format(&mut self, format: OutputFormat) {
+        self.format = format;
+    }
+
+    pub fn output_format(&self) -> OutputFormat {
+        self.format
+    }
+
+    /// Compute the alias summaries of a dependency crate instead of analyzing it; they are used
+    /// when analyzing the crates depending on it.
+    pub fn enable_dep_summary(&mut self) {
+        self.dep_summary = true;
+    }
+
+    pub fn is_dep_summary_enabled(&self) -> bool {
+        self.dep_summary
+    }
+
+    /// Read the summaries of opaque callees from the given TOML or JSON file.
+    pub fn set_summary_file(&mut self, path: impl Into<PathBuf>) {
+        self.summaries = Some(path.into())
+    }
+
+    pub fn summary_file(&self) -> Option<&PathBuf> {
+        self.summaries.as_ref()
+    }
+
+    /// Set the maximum number of path visits of each function in MoP and SafeDrop.
+    pub fn set_visit_budget(&mut self, visits: usize) {
+        self.budget.visits = visits;
+    }
+
+    /// Set the maximum wall-clock time of the path visits of each function in MoP and SafeDrop.
+    pub fn set_time_budget(&mut self, time: Duration) {
+        self.budget.time = Some(time);
+    }
+
+    /// Set the timeout of the Z3 solver used by rCanary, in milliseconds.
+    pub fn set_z3_timeout(&mut self, timeout: u32) {
+        self.budget.z3_timeout = timeout;
+    }
+
+    /// Set the field depth of the alias analysis and SafeDrop, overriding the one of their levels.
+    pub fn set_field_depth(&mut self, depth: usize) {
+        self.precision.field_depth = Some(depth);
+    }
+
+    /// Set the depth of the calling contexts in which the alias analysis and SafeDrop analyze the
+    /// callees again.
+    pub fn set_context_depth(&mut self, depth: usize) {
+        self.precision.context_depth = depth;
+    }
+
+    /// Only analyze the functions defined in the source files matching the glob.
+    pub fn include_files(&mut self, glob: impl Into<String>) {
+        self.scope.include.push(glob.into());
+    }
+
+    /// Skip the functions defined in the source files matching the glob.
+    pub fn exclude_files(&mut self, glob: impl Into<String>) {
+        self.scope.exclude.push(glob.into());
+    }
+
+    /// Only analyze the functions reachable from the given function in the call graph.
+    pub fn add_entry(&mut self, path: impl Into<String>) {
+        self.scope.entries.push(path.into());
+    }
+
+    /// Register an analysis defined out of this crate, which runs if its flag is given.
+    pub fn register_plugin(&mut self, plugin: Plugin) {
+        if let Some(other) = self
+            .plugins
+            .iter()
+            .find(|other| other.flag() == plugin.flag())
+        {
+            rap_error_and_exit(format!(
+                "The plugins {} and {} share the flag {}",
+                other.name(),
+                plugin.name(),
+                plugin.flag()
+            ));
+        }
+        self.plugins.push(plugin);
+    }
+
+    /// Enable the plugin of the flag, and return if there is one.
+    pub fn enable_plugin(&mut self, flag: &str) -> bool {
+        match self.plugins.iter_mut().find(|plugin| plugin.flag() == flag) {
+            Some(plugin) => {
+                plugin.enable();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Return the registered plugins.
+    pub fn plugins(&self) -> &[Plugin] {
+        &self.plugins
+    }
+
+    /// Return the budget of each function in the path-sensitive analyses.
+    pub fn budget(&self) -> &Budget {
+        &self.budget
+    }
+
+    /// Report the time and memory of the analyses and of the functions they analyze.
+    pub fn enable_profile(&mut self) {
+        self.profile = true;
+    }
+
+    /// Set the directory to write the result files to.
+    pub fn set_output_dir(&mut self, dir: impl Into<PathBuf>) {
+        self.output = Some(dir.into());
+    }
+
+    /// Return the directory to write the result files to; it is the current directory by default.
+    pub fn output_dir(&self) -> PathBuf {
+        self.output.clone().unwrap_or_else(|| PathBuf::from("."))
+    }
+}
+
+/// Write the alias summaries of a dependency crate. A failure of the analysis must not break the
+/// compilation of the crate, so panics are caught and the crate is left without summaries.
+fn dump_dep_summary(tcx: TyCtxt) {
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+    let result = panic::catch_unwind(AssertUnwindSafe(|| summary::dump_crate_summary(tcx)));
+    panic::set_hook(hook);
+    match result {
+        Ok(Ok(path)) => rap_debug!("Dump alias summaries to {}", path.display()),
+        Ok(Err(err)) => rap_warn!("Failed to dump alias summaries: {}", err),
+        Err(_) => rap_warn!(
+            "Failed to compute alias summaries of crate {}",
+            tcx.crate_name(rustc_hir::def_id::LOCAL_CRATE)
+        ),
+    }
+}
+
+/// Add the result of an analysis to the JSON report; a result which cannot be serialized is left
+/// out of the report.
+fn add_to_report(report: &mut JsonReport, analysis: &str, result: impl serde::Serialize) {
+    if let Err(err) = report.add(analysis, result) {
+        rap_warn!("Failed to serialize the {} result: {}", analysis, err);
+    }
 }
 
 /// Start the analysis with the features enabled.
 pub fn start_analyzer(tcx: TyCtxt, callback: &RapCallback) {
+    if let Some(BaselineMode::Compare(path)) = callback.baseline() {
+        if let Err(err) = baseline::load(path) {
+            rap_error_and_exit(format!(
+                "Failed to read baseline {}: {}",
+                path.display(),
+                err
+            ));
+        }
+    }
+
+    let ctx = AnalysisContext::new(tcx);
+    scope::init(&ctx, &callback.scope);
+
+    let mut json_report = match callback.output_format() {
+        OutputFormat::Json => Some(JsonReport::new(tcx)),
+        OutputFormat::Text => None,
+    };
+
     if callback.is_alias_enabled() {
-        let mut analyzer = AliasAnalyzer::new(tcx);
-        analyzer.run();
-        let alias = analyzer.get_local_fn_alias();
-        rap_info!("{}", AAResultMapWrapper(alias));
+        let alias = match callback.alias_backend() {
+            AliasBackend::Mop => ctx.alias().get_local_fn_alias(),
+            AliasBackend::Andersen => ctx.andersen().get_local_fn_alias(),
+        };
+        match json_report.as_mut() {
+            Some(report) => add_to_report(
+                report,
+                "alias",
+                AAResultMapJson {
+                    result: &alias,
+                    tcx,
+                },
+            ),
+            None => rap_info!("{}", AAResultMapWrapper(alias)),
+        }
     }
 
     if callback.is_api_dependency_enabled() {
@@ -402,65 +677,123 @@ pub fn start_analyzer(tcx: TyCtxt, callback: &RapCallback) {
                 ignore_const_generic: true,
             },
         );
-        analyzer.run();
-    }
-
-    if callback.is_callgraph_enabled() {
-        let mut analyzer = CallGraphAnalyzer::new(tcx);
-        analyzer.run();
-        let callgraph = analyzer.get_callgraph();
-        rap_info!(
-            "{}",
-            CallGraphDisplay {
-                graph: &callgraph,
-                tcx
+        profile::analysis("api_dependency", || analyzer.run());
+    }
+
+    if callback.is_callgraph_enabled() > 0 {
+        let callgraph = ctx.callgraph();
+        match json_report.as_mut() {
+            Some(report) => add_to_report(
+                report,
+                "callgraph",
+                CallGraphJson {
+                    graph: callgraph,
+                    tcx,
+                },
+            ),
+            None => rap_info!(
+                "{}",
+                CallGraphDisplay {
+                    graph: callgraph,
+                    tcx
+                }
+            ),
+        }
+        if callback.is_callgraph_enabled() == 2 {
+            let dir = callback.output_dir();
+            let path = dir.join(format!(
+                "callgraph_{}_{}.dot",
+                tcx.crate_name(rustc_hir::def_id::LOCAL_CRATE),
+                tcx.crate_types()[0]
+            ));
+            let dot = CallGraphDot {
+                graph: callgraph,
+                tcx,
+            };
+            match fs::create_dir_all(&dir).and_then(|_| fs::write(&path, dot.to_string())) {
+                Ok(()) => rap_info!("Dump the call graph to {}", path.display()),
+                Err(err) => rap_error!("Failed to dump the call graph: {}", err),
             }
-        );
-        //analyzer.display();
+        }
     }
 
     match callback.is_dataflow_enabled() {
         1 => {
             let mut analyzer = DataFlowAnalyzer::new(tcx, false);
-            analyzer.run();
+            profile::analysis("dataflow", || analyzer.run());
             let result = analyzer.get_all_arg2ret();
-            rap_info!("{}", Arg2RetMapWrapper(result));
+            match json_report.as_mut() {
+                Some(report) => add_to_report(
+                    report,
+                    "dataflow",
+                    Arg2RetMapJson {
+                        result: &result,
+                        tcx,
+                    },
+                ),
+                None => rap_info!("{}", Arg2RetMapWrapper(result)),
+            }
         }
         2 => {
             let mut analyzer = DataFlowAnalyzer::new(tcx, true);
-            analyzer.run();
+            profile::analysis("dataflow", || analyzer.run());
             let result = analyzer.get_all_dataflow();
-            rap_info!("{}", DataFlowGraphMapWrapper(result));
+            match json_report.as_mut() {
+                Some(report) => add_to_report(
+                    report,
+                    "dataflow",
+                    DataFlowGraphMapJson {
+                        result: &result,
+                        tcx,
+                    },
+                ),
+                None => rap_info!("{}", DataFlowGraphMapWrapper(result)),
+            }
         }
         _ => {}
     }
 
     if callback.is_ownedheap_enabled() {
-        let mut analyzer = OwnedHeapAnalyzer::new(tcx);
-        analyzer.run();
-        let result = analyzer.get_all_items();
-        rap_info!("{}", OHAResultMapWrapper(result));
+        let result = ctx.ownedheap();
+        match json_report.as_mut() {
+            Some(report) => add_to_report(report, "ownedheap", OHAResultMapJson { result, tcx }),
+            None => rap_info!("{}", OHAResultMapWrapper(result.clone())),
+        }
     }
 
     if callback.is_range_analysis_enabled() {
         match callback.range {
-            1 => {
-                let mut analyzer = RangeAnalyzer::<i128>::new(tcx, false);
-                analyzer.run();
+            1 | 2 => {
+                let mut analyzer = RangeAnalyzer::<i128>::new(tcx, callback.range == 2);
+                profile::analysis("range", || analyzer.run());
                 let result = analyzer.get_all_fn_ranges();
-                rap_info!("{}", RAResultMapWrapper(result));
-            }
-            2 => {
-                let mut analyzer = RangeAnalyzer::<i128>::new(tcx, true);
-                analyzer.run();
-                let result = analyzer.get_all_fn_ranges();
-                rap_info!("{}", RAResultMapWrapper(result));
+                match json_report.as_mut() {
+                    Some(report) => add_to_report(
+                        report,
+                        "range",
+                        RAResultMapJson {
+                            result: &result,
+                            tcx,
+                        },
+                    ),
+                    None => rap_info!("{}", RAResultMapWrapper(result)),
+                }
             }
             3 => {
                 let mut analyzer = RangeAnalyzer::<i128>::new(tcx, false);
-                analyzer.start_path_constraints_analysis();
+                profile::analysis("pathcond", || analyzer.start_path_constraints_analysis());
                 let result = analyzer.get_all_path_constraints();
-                rap_info!("{}", PathConstraintMapWrapper(result));
+                match json_report.as_mut() {
+                    Some(report) => add_to_report(
+                        report,
+                        "pathcond",
+                        PathConstraintMapJson {
+                            result: &result,
+                            tcx,
+                        },
+                    ),
+                    None => rap_info!("{}", PathConstraintMapWrapper(result)),
+                }
             }
             _ => {}
         }
@@ -468,29 +801,27 @@ pub fn start_analyzer(tcx: TyCtxt, callback: &RapCallback) {
 
     if callback.is_test_enabled() {
         let test = Test::new(tcx);
-        test.start();
+        profile::analysis("test", || test.start(&ctx));
     }
 
-    match callback.is_opt_enabled() {
-        0 => Opt::new(tcx, 0).start(),
-        1 => Opt::new(tcx, 1).start(),
-        2 => Opt::new(tcx, 2).start(),
-        _ => {}
+    let level = callback.is_opt_enabled();
+    if level <= 2 {
+        profile::analysis("opt", || Opt::new(tcx, level).start(&ctx));
     }
 
     let _rcanary: Option<rCanary> = if callback.is_rcanary_enabled() {
-        let mut heap = OwnedHeapAnalyzer::new(tcx);
-        heap.run();
-        let adt_owner = heap.get_all_items();
-        let mut rcx = rCanary::new(tcx, adt_owner);
-        rcx.start();
+        let rcx = profile::analysis("rcanary", || {
+            let mut rcx = rCanary::new(tcx, ctx.ownedheap().clone());
+            rcx.start(&ctx);
+            rcx
+        });
         Some(rcx)
     } else {
         None
     };
 
     if callback.is_safedrop_enabled() {
-        SafeDrop::new(tcx).start();
+        profile::analysis("safedrop", || SafeDrop::new(tcx).start(&ctx));
     }
 
     if callback.is_show_mir_enabled() {
@@ -512,7 +843,9 @@ pub fn start_analyzer(tcx: TyCtxt, callback: &RapCallback) {
 
     if callback.is_verify_enabled() {
         let check_level = CheckLevel::Medium;
-        SenryxCheck::new(tcx, 2).start(check_level, true);
+        profile::analysis("senryx", || {
+            SenryxCheck::new(tcx, 2).start(&ctx, check_level, true)
+        });
     }
 
     if callback.is_verify_std_enabled() {
@@ -521,10 +854,82 @@ pub fn start_analyzer(tcx: TyCtxt, callback: &RapCallback) {
 
     if callback.is_infer_enabled() {
         let check_level = CheckLevel::Medium;
-        SenryxCheck::new(tcx, 2).start(check_level, false);
+        profile::analysis("senryx", || {
+            SenryxCheck::new(tcx, 2).start(&ctx, check_level, false)
+        });
     }
 
     if callback.is_scan_enabled() {
         ScanAnalysis::new(tcx).run();
     }
+
+    for plugin in callback
+        .plugins()
+        .iter()
+        .filter(|plugin| plugin.is_enabled())
+    {
+        profile::analysis(plugin.name(), || plugin.run(&ctx));
+    }
+
+    let truncated = budget::take_truncated();
+    if !truncated.is_empty() {
+        let list: Vec<String> = truncated.iter().map(|t| format!("    {t}")).collect();
+        rap_info!(
+            "{} functions ran out of budget and are analyzed path-insensitively or partially:\n{}",
+            truncated.len(),
+            list.join("\n")
+        );
+    }
+
+    if profile::is_enabled() {
+        match profile::report(tcx, &callback.output_dir()) {
+            Ok(path) => rap_info!("Dump the profile to {}", path.display()),
+            Err(err) => rap_error!("Failed to dump the profile: {}", err),
+        }
+    }
+
+    if let Some(report) = json_report.filter(|report| !report.is_empty()) {
+        match report.dump_to_json(callback.output_dir()) {
+            Ok(path) => rap_info!("Dump analysis results to {}", path.display()),
+            Err(err) => rap_error!("Failed to dump analysis results: {}", err),
+        }
+    }
+
+    let diagnostics = take_diagnostics();
+    for diagnostic in diagnostics.iter() {
+        lint::emit_lint(tcx, diagnostic);
+    }
+    match callback.baseline() {
+        Some(BaselineMode::Compare(path)) if baseline::skipped() > 0 => rap_info!(
+            "Skip {} findings recorded in the baseline {}",
+            baseline::skipped(),
+            path.display()
+        ),
+        Some(BaselineMode::Write(path)) => match baseline::write_baseline(path, &diagnostics) {
+            Ok(()) => rap_info!(
+                "Record {} findings in the baseline {}",
+                diagnostics.len(),
+                path.display()
+            ),
+            Err(err) => rap_error!("Failed to write baseline {}: {}", path.display(), err),
+        },
+        _ => {}
+    }
+
+    let krate = tcx.crate_name(rustc_hir::def_id::LOCAL_CRATE);
+    let extra_filename = &tcx.sess.opts.cg.extra_filename;
+    if let Err(err) = results::write_results(krate.as_str(), extra_filename, &diagnostics) {
+        rap_error!("Failed to pass the findings to cargo rapx: {}", err);
+    }
+
+    if let Some(path) = callback.sarif_output() {
+        match write_sarif(path, &diagnostics) {
+            Ok(()) => rap_info!(
+                "Write {} findings to SARIF log {}",
+                diagnostics.len(),
+                path.display()
+            ),
+            Err(err) => rap_error!("Failed to write SARIF log {}: {}", path.display(), err),
+        }
+    }
 }
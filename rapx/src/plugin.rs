@@ -0,0 +1,134 @@
+//! The analyses defined out of this crate.
+//!
+//! A downstream crate implements [`Analysis`] for its checker and registers it as a [`Plugin`]
+//! with the flag enabling it, e.g., `-alloc-check`, and the core analyses it depends on. A driver
+//! binary then runs the built-in analyses and the plugins together:
+//!
+//! ```ignore
+//! #![feature(rustc_private)]
+//! extern crate rustc_driver;
+//!
+//! fn main() {
+//!     let mut callback = rapx::RapCallback::default();
+//!     callback.register_plugin(
+//!         Plugin::new("alloc-check", "-alloc-check", |ctx| Box::new(AllocCheck::new(ctx)))
+//!             .help("check the calls of our allocator")
+//!             .requires(CoreAnalysis::Alias),
+//!     );
+//!     rapx::driver::run(callback);
+//! }
+//! ```
+//!
+//! `cargo rapx` runs such a driver instead of `rapx` if `RAP_DRIVER` is set to its path, e.g.,
+//! `RAP_DRIVER=target/release/my-rapx cargo rapx -F -alloc-check`. The plugins report their
+//! findings as [`crate::utils::diagnostic::Diagnostic`]s of the built-in rules or in the log.
+
+use crate::analysis::{
+    context::{AnalysisContext, CoreAnalysis},
+    Analysis,
+};
+use std::{
+    fmt,
+    hash::{Hash, Hasher},
+    sync::Arc,
+};
+
+/// Create the analysis of a plugin for a crate. The analysis may keep the context to request the
+/// results of the core analyses.
+pub type PluginFactory =
+    dyn for<'a, 'tcx> Fn(&'a AnalysisContext<'tcx>) -> Box<dyn Analysis + 'a> + Send + Sync;
+
+/// An analysis registered into [`crate::RapCallback`], which runs if its flag is given.
+#[derive(Clone)]
+pub struct Plugin {
+    name: String,
+    flag: String,
+    help: String,
+    requires: Vec<CoreAnalysis>,
+    factory: Arc<PluginFactory>,
+    enabled: bool,
+}
+
+impl Plugin {
+    pub fn new<F>(name: impl Into<String>, flag: impl Into<String>, factory: F) -> Self
+    where
+        F: for<'a, 'tcx> Fn(&'a AnalysisContext<'tcx>) -> Box<dyn Analysis + 'a>
+            + Send
+            + Sync
+            + 'static,
+    {
+        Plugin {
+            name: name.into(),
+            flag: flag.into(),
+            help: String::new(),
+            requires: Vec::new(),
+            factory: Arc::new(factory),
+            enabled: false,
+        }
+    }
+
+    /// Describe the plugin in the list printed by `-plugins`.
+    pub fn help(mut self, help: impl Into<String>) -> Self {
+        self.help = help.into();
+        self
+    }
+
+    /// Run the core analysis before the plugin, e.g., to share its results with the built-in
+    /// analyses of the run.
+    pub fn requires(mut self, analysis: CoreAnalysis) -> Self {
+        if !self.requires.contains(&analysis) {
+            self.requires.push(analysis);
+        }
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn flag(&self) -> &str {
+        &self.flag
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub(crate) fn enable(&mut self) {
+        self.enabled = true;
+    }
+
+    /// Run the plugin on the crate of the context.
+    pub fn run(&self, ctx: &AnalysisContext<'_>) {
+        for analysis in self.requires.iter() {
+            ctx.prepare(*analysis);
+        }
+        let mut analysis = (self.factory)(ctx);
+        rap_debug!("Run the plugin {}: {}", self.name, analysis.name());
+        analysis.run();
+    }
+}
+
+impl fmt::Debug for Plugin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Plugin")
+            .field("name", &self.name)
+            .field("flag", &self.flag)
+            .field("requires", &self.requires)
+            .field("enabled", &self.enabled)
+            .finish()
+    }
+}
+
+impl Hash for Plugin {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.name.hash(state);
+        self.enabled.hash(state);
+    }
+}
+
+impl fmt::Display for Plugin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:<16}{}", self.flag, self.help)
+    }
+}
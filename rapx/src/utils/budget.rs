@@ -0,0 +1,163 @@
+//! The resources each function may consume in the path-sensitive analyses.
+//!
+//! MoP and SafeDrop enumerate the paths of a function and rCanary solves its ownership constraints
+//! with Z3, both of which may explode on large functions. The budget of a function bounds the
+//! number of path visits, the wall-clock time of the visits and the timeout of the Z3 solver; it is
+//! set with `-budget-visits=<n>`, `-budget-time=<secs>` and `-budget-z3=<ms>`.
+//!
+//! A function running out of its budget is not dropped: the alias analysis and SafeDrop keep the
+//! results of the paths they have visited and analyze the function again path-insensitively,
+//! merging the states of the paths where they meet. rCanary has no such fallback, as the
+//! constraints of a function are solved at once: no leak is reported for a function whose
+//! constraints Z3 cannot solve in time. Such functions are recorded and listed at the end of the
+//! run, since their results are less precise.
+
+use rustc_hir::def_id::DefId;
+use rustc_middle::ty::TyCtxt;
+use std::{
+    fmt,
+    sync::{Mutex, OnceLock},
+    time::{Duration, Instant},
+};
+
+static BUDGET: OnceLock<Budget> = OnceLock::new();
+static TRUNCATED: Mutex<Vec<Truncation>> = Mutex::new(Vec::new());
+
+/// The budget of each function.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct Budget {
+    /// The maximum number of path visits.
+    pub visits: usize,
+    /// The maximum wall-clock time of the path visits; unlimited if `None`.
+    pub time: Option<Duration>,
+    /// The timeout of the Z3 solver in milliseconds.
+    pub z3_timeout: u32,
+}
+
+impl Default for Budget {
+    fn default() -> Self {
+        Self {
+            visits: 1000,
+            time: None,
+            z3_timeout: 1000,
+        }
+    }
+}
+
+/// Set the budget. It should be called once before the analyses start; the default budget is used
+/// otherwise.
+pub fn init(budget: Budget) {
+    BUDGET.get_or_init(|| budget);
+}
+
+/// Return the budget of each function.
+pub fn get() -> &'static Budget {
+    BUDGET.get_or_init(Budget::default)
+}
+
+/// The fingerprint of the budget. The cached results of the analyses bounded by it depend on it.
+pub fn fingerprint() -> String {
+    let budget = get();
+    format!(
+        "{}:{}",
+        budget.visits,
+        budget.time.map_or(0, |time| time.as_millis())
+    )
+}
+
+/// The resource a function runs out of.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Exhaustion {
+    Visits,
+    Time,
+    Solver,
+}
+
+impl fmt::Display for Exhaustion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Exhaustion::Visits => write!(f, "path visits"),
+            Exhaustion::Time => write!(f, "time"),
+            Exhaustion::Solver => write!(f, "solver timeout"),
+        }
+    }
+}
+
+/// The budget consumed by the analysis of a function, started when the analysis starts.
+#[derive(Debug, Clone)]
+pub struct FnBudget {
+    start: Instant,
+    exhausted: Option<Exhaustion>,
+}
+
+impl FnBudget {
+    pub fn start() -> Self {
+        Self {
+            start: Instant::now(),
+            exhausted: None,
+        }
+    }
+
+    /// Test if the function has run out of its budget after `visits` path visits. Once it has, it
+    /// stays exhausted.
+    pub fn is_exhausted(&mut self, visits: usize) -> bool {
+        if self.exhausted.is_some() {
+            return true;
+        }
+        let budget = get();
+        if visits > budget.visits {
+            self.exhausted = Some(Exhaustion::Visits);
+        } else if budget.time.is_some_and(|time| self.start.elapsed() > time) {
+            self.exhausted = Some(Exhaustion::Time);
+        }
+        self.exhausted.is_some()
+    }
+
+    /// Return the resource the function has run out of.
+    pub fn exhausted(&self) -> Option<Exhaustion> {
+        self.exhausted
+    }
+}
+
+/// A function whose analysis was cut short.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Truncation {
+    pub analysis: &'static str,
+    pub function: String,
+    pub reason: Exhaustion,
+}
+
+impl fmt::Display for Truncation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}] {}: {}", self.analysis, self.function, self.reason)
+    }
+}
+
+/// Record that the analysis of a function ran out of its budget.
+pub fn truncate(tcx: TyCtxt<'_>, analysis: &'static str, def_id: DefId, reason: Exhaustion) {
+    let truncation = Truncation {
+        analysis,
+        function: tcx.def_path_str(def_id),
+        reason,
+    };
+    let mut truncated = TRUNCATED.lock().unwrap();
+    // the alias analysis may run several times for different clients
+    if !truncated.contains(&truncation) {
+        truncated.push(truncation);
+    }
+}
+
+/// Test if the analysis of a function has been recorded as truncated.
+pub fn is_truncated(tcx: TyCtxt<'_>, analysis: &'static str, def_id: DefId) -> bool {
+    let function = tcx.def_path_str(def_id);
+    TRUNCATED
+        .lock()
+        .unwrap()
+        .iter()
+        .any(|truncation| truncation.analysis == analysis && truncation.function == function)
+}
+
+/// Take the functions truncated so far, e.g., to list them at the end of the run.
+pub fn take_truncated() -> Vec<Truncation> {
+    std::mem::take(&mut *TRUNCATED.lock().unwrap())
+}
@@ -0,0 +1,365 @@
+//! A persistent on-disk cache of analysis summaries.
+//!
+//! `cargo rapx` sets `RAP_CACHE_DIR` to `<target dir>/rapx/cache`, and the summaries of each crate
+//! are stored in `<crate>-<crate type>-<stable crate id>/<analysis>.json` under it. The cache is
+//! disabled if the variable is not set, e.g., when rapx is invoked directly.
+//!
+//! Each file is bound to a crate key, which covers the version of rapx, the options of the
+//! analysis and the SVH of all the dependency crates; a file with another key is discarded as a
+//! whole. Within the file, every summary is stored with the fingerprint of the MIR it is computed
+//! from (see [`fn_fingerprints`]), and it is only reused if the fingerprint is unchanged.
+//!
+//! The MoP alias summaries, the arg2ret summaries and the owned-heap results are cached. The
+//! results of the range analysis are not: they are keyed by the places of the SSA form built for
+//! each function, which has no stable counterpart in the MIR of the next session.
+
+use crate::{analysis::core::callgraph::indirect::indirect_callees, rap_debug, rap_warn};
+use rustc_data_structures::{
+    fingerprint::Fingerprint,
+    fx::{FxHashMap, FxHashSet},
+    stable_hasher::{HashStable, StableHasher},
+};
+use rustc_hir::{
+    def::DefKind,
+    def_id::{DefId, DefPathHash, LOCAL_CRATE},
+};
+use rustc_middle::{
+    mir::{
+        visit::{TyContext, Visitor},
+        Body, ConstOperand, Location,
+    },
+    ty::{self, Ty, TyCtxt},
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    hash::Hash,
+    io,
+    path::{Path, PathBuf},
+};
+
+/// The environment variable holding the folder of the cache.
+pub const RAP_CACHE_DIR: &str = "RAP_CACHE_DIR";
+
+#[derive(Serialize, Deserialize, Default)]
+struct CacheFile {
+    crate_key: String,
+    entries: BTreeMap<String, CacheEntry>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CacheEntry {
+    fingerprint: String,
+    summary: Value,
+}
+
+/// The summaries of one analysis for the local crate.
+///
+/// Entries fetched by [`SummaryCache::get`] or added by [`SummaryCache::insert`] are written back
+/// by [`SummaryCache::store`]; the others are dropped, so that the summaries of removed functions
+/// do not pile up.
+pub struct SummaryCache<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    analysis: &'static str,
+    path: Option<PathBuf>,
+    crate_key: String,
+    cached: BTreeMap<String, CacheEntry>,
+    updated: BTreeMap<String, CacheEntry>,
+    hits: usize,
+    misses: usize,
+}
+
+impl<'tcx> SummaryCache<'tcx> {
+    /// Load the cache of `analysis`. `options` are the settings the results of the analysis depend
+    /// on; the cache is invalidated once they change.
+    pub fn load(tcx: TyCtxt<'tcx>, analysis: &'static str, options: &[&str]) -> Self {
+        let path = env::var_os(RAP_CACHE_DIR)
+            .filter(|dir| !dir.is_empty())
+            .map(|dir| crate_cache_dir(tcx, Path::new(&dir)).join(format!("{analysis}.json")));
+        let crate_key = crate_key(tcx, analysis, options);
+        let mut cached = BTreeMap::new();
+        if let Some(path) = &path {
+            match fs::read_to_string(path) {
+                Ok(content) => match serde_json::from_str::<CacheFile>(&content) {
+                    Ok(file) if file.crate_key == crate_key => cached = file.entries,
+                    Ok(_) => {
+                        rap_debug!("Discard the outdated {analysis} cache {}.", path.display())
+                    }
+                    Err(err) => rap_warn!("Ignore the broken cache {}: {err}", path.display()),
+                },
+                Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+                Err(err) => rap_warn!("Failed to read the cache {}: {err}", path.display()),
+            }
+        }
+        SummaryCache {
+            tcx,
+            analysis,
+            path,
+            crate_key,
+            cached,
+            updated: BTreeMap::new(),
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.path.is_some()
+    }
+
+    /// Return the summary stored under `key` if it is computed from the same `fingerprint`.
+    pub fn get<T: DeserializeOwned>(&mut self, key: &str, fingerprint: Fingerprint) -> Option<T> {
+        let fingerprint = fingerprint.to_hex();
+        let summary = match self.cached.remove(key) {
+            Some(entry) if entry.fingerprint == fingerprint => {
+                serde_json::from_value(entry.summary.clone())
+                    .ok()
+                    .map(|summary| (summary, entry))
+            }
+            _ => None,
+        };
+        match summary {
+            Some((summary, entry)) => {
+                self.hits += 1;
+                self.updated.insert(key.to_owned(), entry);
+                Some(summary)
+            }
+            None => {
+                self.misses += 1;
+                None
+            }
+        }
+    }
+
+    pub fn insert<T: Serialize>(&mut self, key: &str, fingerprint: Fingerprint, summary: &T) {
+        if !self.is_enabled() {
+            return;
+        }
+        let summary = serde_json::to_value(summary).expect("Failed to serialize the summary.");
+        self.updated.insert(
+            key.to_owned(),
+            CacheEntry {
+                fingerprint: fingerprint.to_hex(),
+                summary,
+            },
+        );
+    }
+
+    pub fn get_fn<T: DeserializeOwned>(
+        &mut self,
+        def_id: DefId,
+        fingerprint: Fingerprint,
+    ) -> Option<T> {
+        let key = def_id_key(self.tcx, def_id);
+        self.get(&key, fingerprint)
+    }
+
+    pub fn insert_fn<T: Serialize>(
+        &mut self,
+        def_id: DefId,
+        fingerprint: Fingerprint,
+        summary: &T,
+    ) {
+        let key = def_id_key(self.tcx, def_id);
+        self.insert(&key, fingerprint, summary);
+    }
+
+    /// Write the cache back to the disk.
+    pub fn store(self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        rap_debug!(
+            "{} cache of {}: {} hits, {} misses.",
+            self.analysis,
+            self.tcx.crate_name(LOCAL_CRATE),
+            self.hits,
+            self.misses
+        );
+        let file = CacheFile {
+            crate_key: self.crate_key,
+            entries: self.updated,
+        };
+        if let Err(err) = write_atomically(path, &file) {
+            rap_warn!("Failed to write the cache {}: {err}", path.display());
+        }
+    }
+}
+
+fn write_atomically(path: &Path, file: &CacheFile) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    // rename is atomic, so a concurrent rapx process never reads a half-written cache
+    let tmp = path.with_extension(format!("json.{}", std::process::id()));
+    fs::write(&tmp, serde_json::to_vec(file)?)?;
+    fs::rename(&tmp, path)
+}
+
+fn crate_cache_dir(tcx: TyCtxt<'_>, root: &Path) -> PathBuf {
+    root.join(format!(
+        "{}-{}-{:016x}",
+        tcx.crate_name(LOCAL_CRATE),
+        tcx.crate_types()[0],
+        tcx.stable_crate_id(LOCAL_CRATE).as_u64()
+    ))
+}
+
+fn crate_key(tcx: TyCtxt<'_>, analysis: &str, options: &[&str]) -> String {
+    let mut hasher = StableHasher::new();
+    env!("CARGO_PKG_VERSION").hash(&mut hasher);
+    analysis.hash(&mut hasher);
+    options.hash(&mut hasher);
+    let mut deps: Vec<_> = tcx
+        .crates(())
+        .iter()
+        .map(|&cnum| {
+            (
+                tcx.crate_name(cnum).to_string(),
+                tcx.crate_hash(cnum).as_u128(),
+            )
+        })
+        .collect();
+    deps.sort();
+    deps.hash(&mut hasher);
+    hasher.finish::<Fingerprint>().to_hex()
+}
+
+/// The key of a definition in the cache. Unlike `DefId`, it is stable across compilation sessions.
+pub fn def_id_key(tcx: TyCtxt<'_>, def_id: DefId) -> String {
+    format!(
+        "{:032x}",
+        u128::from_le_bytes(tcx.def_path_hash(def_id).0.to_le_bytes())
+    )
+}
+
+/// Map the key of a definition back to its `DefId`.
+pub fn def_id_from_key(tcx: TyCtxt<'_>, key: &str) -> Option<DefId> {
+    let value = u128::from_str_radix(key, 16).ok()?;
+    let hash = DefPathHash(Fingerprint::from_le_bytes(value.to_le_bytes()));
+    if hash.stable_crate_id() != tcx.stable_crate_id(LOCAL_CRATE)
+        && !tcx
+            .crates(())
+            .iter()
+            .any(|&cnum| tcx.stable_crate_id(cnum) == hash.stable_crate_id())
+    {
+        return None;
+    }
+    tcx.def_path_hash_to_def_id(hash)
+}
+
+/// Return the fingerprints of the local functions with MIR bodies. The fingerprint of a function
+/// covers its own MIR, the MIR of all the local functions and closures it reaches, and the
+/// definitions of the local ADTs; the rest of the world is covered by the crate key.
+pub fn fn_fingerprints(tcx: TyCtxt<'_>) -> FxHashMap<DefId, Fingerprint> {
+    let adt_fingerprint = adt_fingerprint(tcx);
+    let mut own = FxHashMap::default();
+    let mut callees = FxHashMap::default();
+    for local_def_id in tcx.mir_keys(()) {
+        let def_id = local_def_id.to_def_id();
+        if tcx.hir_body_const_context(*local_def_id).is_some() || !tcx.is_mir_available(def_id) {
+            continue;
+        }
+        let body = tcx.optimized_mir(def_id);
+        own.insert(def_id, mir_fingerprint(tcx, body));
+        let mut collector = LocalCalleeCollector {
+            callees: FxHashSet::default(),
+        };
+        collector.visit_body(body);
+        // the targets of indirect calls are not named in the body
+        for bb in body.basic_blocks.indices() {
+            collector.callees.extend(
+                indirect_callees(tcx, def_id, bb)
+                    .iter()
+                    .filter(|id| id.is_local()),
+            );
+        }
+        callees.insert(def_id, collector.callees);
+    }
+
+    own.keys()
+        .map(|&def_id| {
+            let mut reachable = vec![def_id];
+            let mut visited = FxHashSet::default();
+            visited.insert(def_id);
+            while let Some(caller) = reachable.pop() {
+                for &callee in callees.get(&caller).into_iter().flatten() {
+                    if own.contains_key(&callee) && visited.insert(callee) {
+                        reachable.push(callee);
+                    }
+                }
+            }
+            let fingerprint = visited
+                .iter()
+                .map(|id| own[id])
+                .fold(adt_fingerprint, Fingerprint::combine_commutative);
+            (def_id, fingerprint)
+        })
+        .collect()
+}
+
+/// Combine the fingerprints of all the functions, for analyses whose results are computed for the
+/// crate as a whole.
+pub fn crate_fingerprint(fingerprints: &FxHashMap<DefId, Fingerprint>) -> Fingerprint {
+    fingerprints
+        .values()
+        .fold(Fingerprint::ZERO, |acc, fingerprint| {
+            acc.combine_commutative(*fingerprint)
+        })
+}
+
+pub fn mir_fingerprint<'tcx>(tcx: TyCtxt<'tcx>, body: &Body<'tcx>) -> Fingerprint {
+    tcx.with_stable_hashing_context(|mut hcx| {
+        let mut hasher = StableHasher::new();
+        body.hash_stable(&mut hcx, &mut hasher);
+        hasher.finish()
+    })
+}
+
+fn adt_fingerprint(tcx: TyCtxt<'_>) -> Fingerprint {
+    tcx.with_stable_hashing_context(|mut hcx| {
+        let mut fingerprint = Fingerprint::ZERO;
+        for local_def_id in tcx.hir_crate_items(()).definitions() {
+            if !matches!(
+                tcx.def_kind(local_def_id),
+                DefKind::Struct | DefKind::Enum | DefKind::Union
+            ) {
+                continue;
+            }
+            let adt_def = tcx.adt_def(local_def_id);
+            let mut hasher = StableHasher::new();
+            adt_def.hash_stable(&mut hcx, &mut hasher);
+            for field in adt_def.all_fields() {
+                tcx.type_of(field.did)
+                    .instantiate_identity()
+                    .hash_stable(&mut hcx, &mut hasher);
+            }
+            tcx.adt_destructor(local_def_id)
+                .is_some()
+                .hash_stable(&mut hcx, &mut hasher);
+            fingerprint = fingerprint.combine_commutative(hasher.finish());
+        }
+        fingerprint
+    })
+}
+
+/// Collect the local functions and closures referred to by a body.
+struct LocalCalleeCollector {
+    callees: FxHashSet<DefId>,
+}
+
+impl<'tcx> Visitor<'tcx> for LocalCalleeCollector {
+    fn visit_ty(&mut self, ty: Ty<'tcx>, _: TyContext) {
+        if let ty::FnDef(def_id, _) | ty::Closure(def_id, _) = ty.kind() {
+            if def_id.is_local() {
+                self.callees.insert(*def_id);
+            }
+        }
+    }
+
+    fn visit_const_operand(&mut self, constant: &ConstOperand<'tcx>, location: Location) {
+        self.visit_ty(constant.const_.ty(), TyContext::Location(location));
+    }
+}
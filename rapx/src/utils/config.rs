@@ -0,0 +1,309 @@
+//! The configuration file of RAPx.
+//!
+//! A workspace may commit its setup of RAPx in `rapx.toml` at the workspace root, and each package
+//! may refine it in the `[package.metadata.rapx]` section of its `Cargo.toml`:
+//!
+//! ```toml
+//! # the options of rapx run for each crate, as on the command line
+//! analyses = ["-F", "-M"]
+//! # the field depth of the alias analysis and SafeDrop
+//! field-depth = 20
+//! # the depth of the calling contexts of the alias summaries
+//! context-depth = 1
+//! # the globs of the paths to analyze or to skip, relative to the folder of the file
+//! include = ["src/**"]
+//! exclude = ["src/generated/**"]
+//! # only analyze the functions reachable from these ones
+//! entry = ["parser::parse"]
+//! summaries = "summaries.toml"
+//! format = "json"
+//! output = "rapx-out"
+//! sarif = "rapx.sarif"
+//! # fail the run on the findings of this severity or of these rules, e.g., "double_free,memory_leak"
+//! fail-on = "error"
+//! # the workspace members checked at the same time with `recursive`, and the seconds for each
+//! jobs = 4
+//! timeout = 3600
+//!
+//! [budget]
+//! visits = 2000
+//! time = 10
+//! z3 = 3000
+//! ```
+//!
+//! The keys of a package override the ones of the workspace, except that the paths to include and
+//! exclude and the entries are added up; the options on the command line override both. The following keys are
+//! only read from `rapx.toml`, and the environment variables of the same meaning take precedence:
+//! `log` (`RAP_LOG`), `clean` (`RAP_CLEAN`), `cache` (`RAP_CACHE`), `dep-summary`
+//! (`RAP_DEP_SUMMARY`) and `recursive` (`RAP_RECURSIVE`); so are `fail-on`, `jobs` and `timeout`,
+//! which `-fail-on`, `-jobs` and `-timeout` override.
+
+use crate::utils::diagnostic::results::FailOn;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// The name of the configuration file at the workspace root.
+pub const CONFIG_FILE: &str = "rapx.toml";
+
+/// The keys of the configuration file, all of which are optional.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct RapxConfig {
+    pub analyses: Option<Vec<String>>,
+    pub field_depth: Option<usize>,
+    pub context_depth: Option<usize>,
+    #[serde(default)]
+    pub include: Vec<String>,
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    #[serde(default)]
+    pub entry: Vec<String>,
+    pub summaries: Option<PathBuf>,
+    pub format: Option<String>,
+    pub output: Option<PathBuf>,
+    pub sarif: Option<PathBuf>,
+    pub budget: Option<BudgetConfig>,
+    pub log: Option<String>,
+    pub clean: Option<bool>,
+    pub cache: Option<bool>,
+    pub dep_summary: Option<bool>,
+    pub recursive: Option<String>,
+    pub fail_on: Option<String>,
+    pub jobs: Option<usize>,
+    pub timeout: Option<u64>,
+}
+
+/// The `[budget]` table, see [`crate::utils::budget`].
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BudgetConfig {
+    pub visits: Option<usize>,
+    /// In seconds.
+    pub time: Option<u64>,
+    /// In milliseconds.
+    pub z3: Option<u32>,
+}
+
+/// The configuration of a package resolved against the folders of the files declaring it, which is
+/// forwarded from `cargo rapx` to the rustc-wrapper phase.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResolvedConfig {
+    /// The options of rapx, placed before the ones on the command line.
+    pub args: Vec<String>,
+    /// The absolute globs of the paths to analyze; all paths are analyzed if it is empty.
+    pub include: Vec<String>,
+    /// The absolute globs of the paths to skip.
+    pub exclude: Vec<String>,
+}
+
+impl RapxConfig {
+    /// Read the configuration file. It is not an error if the file does not exist.
+    pub fn from_file(path: &Path) -> Result<Option<Self>, String> {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(format!("Failed to read {}: {err}", path.display())),
+        };
+        let config: RapxConfig = toml::from_str(&content)
+            .map_err(|err| format!("Invalid configuration file {}: {err}", path.display()))?;
+        config.validate()?;
+        Ok(Some(config))
+    }
+
+    /// Read the `rapx` table in the `[package.metadata]` section of a package.
+    pub fn from_package_metadata(
+        package: &str,
+        metadata: &serde_json::Value,
+    ) -> Result<Option<Self>, String> {
+        let Some(value) = metadata.get("rapx") else {
+            return Ok(None);
+        };
+        let config: RapxConfig = serde_json::from_value(value.clone())
+            .map_err(|err| format!("Invalid [package.metadata.rapx] of {package}: {err}"))?;
+        config.validate()?;
+        Ok(Some(config))
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        for analysis in self.analyses.iter().flatten() {
+            if !analysis.starts_with('-') {
+                return Err(format!(
+                    "Invalid analysis `{analysis}`: expect an option of rapx, e.g., `-F`"
+                ));
+            }
+        }
+        if let Some(format) = &self.format {
+            if format != "json" && format != "text" {
+                return Err(format!("Invalid format `{format}`: expect json or text"));
+            }
+        }
+        if let Some(fail_on) = &self.fail_on {
+            FailOn::parse(fail_on)?;
+        }
+        if self.jobs == Some(0) {
+            return Err("Invalid jobs `0`: expect a positive number".to_owned());
+        }
+        Ok(())
+    }
+
+    /// Make the relative paths in the keys relative to `base`, the folder of the file declaring
+    /// them.
+    pub fn rebase(mut self, base: &Path) -> Self {
+        for path in [&mut self.summaries, &mut self.output, &mut self.sarif]
+            .into_iter()
+            .flatten()
+        {
+            *path = base.join(&*path);
+        }
+        for glob in self.include.iter_mut().chain(self.exclude.iter_mut()) {
+            *glob = base.join(&*glob).display().to_string();
+        }
+        self
+    }
+
+    /// Refine the configuration of the workspace with the one of a package.
+    pub fn merge(&self, package: &RapxConfig) -> RapxConfig {
+        let budget = match (&self.budget, &package.budget) {
+            (Some(ws), Some(pkg)) => Some(BudgetConfig {
+                visits: pkg.visits.or(ws.visits),
+                time: pkg.time.or(ws.time),
+                z3: pkg.z3.or(ws.z3),
+            }),
+            (ws, pkg) => pkg.clone().or(ws.clone()),
+        };
+        RapxConfig {
+            analyses: package.analyses.clone().or(self.analyses.clone()),
+            field_depth: package.field_depth.or(self.field_depth),
+            context_depth: package.context_depth.or(self.context_depth),
+            include: [self.include.clone(), package.include.clone()].concat(),
+            exclude: [self.exclude.clone(), package.exclude.clone()].concat(),
+            entry: [self.entry.clone(), package.entry.clone()].concat(),
+            summaries: package.summaries.clone().or(self.summaries.clone()),
+            format: package.format.clone().or(self.format.clone()),
+            output: package.output.clone().or(self.output.clone()),
+            sarif: package.sarif.clone().or(self.sarif.clone()),
+            budget,
+            // the keys of the whole run are only read from the workspace
+            ..self.clone()
+        }
+    }
+
+    /// Resolve the keys for the rapx driver.
+    pub fn resolve(&self) -> ResolvedConfig {
+        let mut args = self.analyses.clone().unwrap_or_default();
+        if let Some(depth) = self.field_depth {
+            args.push(format!("-field-depth={depth}"));
+        }
+        if let Some(depth) = self.context_depth {
+            args.push(format!("-context-depth={depth}"));
+        }
+        // the crates out of the paths are skipped by `cargo rapx`, and the functions out of them
+        // by the driver
+        args.extend(self.include.iter().map(|glob| format!("-include={glob}")));
+        args.extend(self.exclude.iter().map(|glob| format!("-exclude={glob}")));
+        args.extend(self.entry.iter().map(|path| format!("-entry={path}")));
+        if let Some(summaries) = &self.summaries {
+            args.push(format!("-summaries={}", summaries.display()));
+        }
+        if let Some(format) = &self.format {
+            args.push(format!("-format={format}"));
+        }
+        if let Some(output) = &self.output {
+            args.push(format!("-output={}", output.display()));
+        }
+        if let Some(sarif) = &self.sarif {
+            args.push(format!("-sarif={}", sarif.display()));
+        }
+        if let Some(budget) = &self.budget {
+            if let Some(visits) = budget.visits {
+                args.push(format!("-budget-visits={visits}"));
+            }
+            if let Some(time) = budget.time {
+                args.push(format!("-budget-time={time}"));
+            }
+            if let Some(z3) = budget.z3 {
+                args.push(format!("-budget-z3={z3}"));
+            }
+        }
+        ResolvedConfig {
+            args,
+            include: self.include.clone(),
+            exclude: self.exclude.clone(),
+        }
+    }
+}
+
+impl ResolvedConfig {
+    /// Test if the crate whose sources are in `dir` should be analyzed: it is not excluded, and
+    /// some of its files may be included.
+    pub fn includes_crate(&self, root_file: &Path, dir: &Path) -> bool {
+        let root_file = root_file.display().to_string();
+        if self
+            .exclude
+            .iter()
+            .any(|glob| Glob::new(glob).is_match(&root_file))
+        {
+            return false;
+        }
+        self.include.is_empty()
+            || self
+                .include
+                .iter()
+                .any(|glob| Glob::new(glob).may_match_under(dir))
+    }
+}
+
+/// A glob of paths: `*` and `?` match within a path segment, and `**` matches across segments.
+pub struct Glob {
+    pattern: String,
+    regex: Regex,
+}
+
+impl Glob {
+    pub fn new(pattern: &str) -> Self {
+        let mut regex = String::from("^");
+        let mut chars = pattern.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '*' if chars.peek() == Some(&'*') => {
+                    chars.next();
+                    // `a/**/b` also matches `a/b`
+                    if chars.peek() == Some(&'/') {
+                        chars.next();
+                        regex.push_str("(.*/)?");
+                    } else {
+                        regex.push_str(".*");
+                    }
+                }
+                '*' => regex.push_str("[^/]*"),
+                '?' => regex.push_str("[^/]"),
+                c => regex.push_str(&regex::escape(&c.to_string())),
+            }
+        }
+        regex.push('$');
+        Glob {
+            pattern: pattern.to_owned(),
+            regex: Regex::new(&regex).expect("the escaped glob is a valid regex"),
+        }
+    }
+
+    pub fn is_match(&self, path: &str) -> bool {
+        self.regex.is_match(path)
+    }
+
+    /// Test if the glob may match a path under `dir`, comparing the folder with the folders of the
+    /// glob before its first wildcard.
+    pub fn may_match_under(&self, dir: &Path) -> bool {
+        let literal = match self.pattern.find(['*', '?']) {
+            Some(idx) => Path::new(&self.pattern[..idx])
+                .parent()
+                .unwrap_or(Path::new("")),
+            None => Path::new(&self.pattern),
+        };
+        literal.starts_with(dir) || dir.starts_with(literal)
+    }
+}
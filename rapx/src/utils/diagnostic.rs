@@ -0,0 +1,316 @@
+//! Structured diagnostics shared by the bug-finding analyses.
+//!
+//! Each finding of SafeDrop, rCanary, senryx and the `opt` checkers is recorded here as a
+//! [`Diagnostic`]. The findings of a crate are emitted as rustc lints (see [`lint`]) and can be
+//! exported in machine-readable formats such as SARIF (see [`sarif`]). Reviewed findings are
+//! suppressed by the `rapx::allow` attribute (see [`suppression`]) or by a baseline of the known
+//! findings (see [`baseline`]). The findings of each crate are also passed back to `cargo rapx`
+//! for the summary of the whole run (see [`results`]).
+
+pub mod baseline;
+pub mod lint;
+pub mod results;
+pub mod sarif;
+pub mod suppression;
+
+use lazy_static::lazy_static;
+use rustc_hir::def_id::DefId;
+use rustc_span::source_map::get_source_map;
+use rustc_span::{FileNameDisplayPreference, Pos, Span};
+use std::fmt::{self, Display};
+use std::{
+    fs::{File, OpenOptions},
+    io,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+lazy_static! {
+    static ref DIAGNOSTICS: Mutex<Vec<Diagnostic>> = Mutex::new(Vec::new());
+}
+
+/// The severity of a finding. The names follow the `level` property of SARIF.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Severity {
+    Error,
+    Warning,
+    Note,
+}
+
+impl Severity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Note => "note",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Severity> {
+        [Severity::Error, Severity::Warning, Severity::Note]
+            .into_iter()
+            .find(|severity| severity.as_str() == name)
+    }
+
+    /// Test if it is as severe as `other` or more severe.
+    pub fn is_at_least(&self, other: Severity) -> bool {
+        let rank = |severity: &Severity| match severity {
+            Severity::Error => 2,
+            Severity::Warning => 1,
+            Severity::Note => 0,
+        };
+        rank(self) >= rank(&other)
+    }
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// The kinds of findings reported by RAPx. Each rule has a stable id which is used as the SARIF
+/// `ruleId`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Rule {
+    UseAfterFree,
+    DoubleFree,
+    DanglingPointer,
+    MemoryLeak,
+    UnsafePrecondition,
+    BoundsChecking,
+    EncodingChecking,
+    SuboptimalCollection,
+    CollectionInitialization,
+    Reallocation,
+    InefficientIterator,
+    MemoryCloning,
+}
+
+impl Rule {
+    pub const ALL: [Rule; 12] = [
+        Rule::UseAfterFree,
+        Rule::DoubleFree,
+        Rule::DanglingPointer,
+        Rule::MemoryLeak,
+        Rule::UnsafePrecondition,
+        Rule::BoundsChecking,
+        Rule::EncodingChecking,
+        Rule::SuboptimalCollection,
+        Rule::CollectionInitialization,
+        Rule::Reallocation,
+        Rule::InefficientIterator,
+        Rule::MemoryCloning,
+    ];
+
+    pub fn id(&self) -> &'static str {
+        match self {
+            Rule::UseAfterFree => "use_after_free",
+            Rule::DoubleFree => "double_free",
+            Rule::DanglingPointer => "dangling_pointer",
+            Rule::MemoryLeak => "memory_leak",
+            Rule::UnsafePrecondition => "unsafe_precondition",
+            Rule::BoundsChecking => "bounds_checking",
+            Rule::EncodingChecking => "encoding_checking",
+            Rule::SuboptimalCollection => "suboptimal_collection",
+            Rule::CollectionInitialization => "collection_initialization",
+            Rule::Reallocation => "reallocation",
+            Rule::InefficientIterator => "inefficient_iterator",
+            Rule::MemoryCloning => "memory_cloning",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Rule> {
+        Rule::ALL.into_iter().find(|rule| rule.id() == id)
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Rule::UseAfterFree => "Use of memory after it has been freed.",
+            Rule::DoubleFree => "Memory is freed more than once.",
+            Rule::DanglingPointer => "A pointer to freed memory escapes the function.",
+            Rule::MemoryLeak => "Heap memory is never released.",
+            Rule::UnsafePrecondition => {
+                "The safety properties required by an unsafe API may not hold."
+            }
+            Rule::BoundsChecking => "Bounds checking that can be proven unnecessary.",
+            Rule::EncodingChecking => "Encoding checking that can be proven unnecessary.",
+            Rule::SuboptimalCollection => "A data collection is used in a suboptimal way.",
+            Rule::CollectionInitialization => "Unnecessary initialization of a data collection.",
+            Rule::Reallocation => "A data collection may be reallocated repeatedly.",
+            Rule::InefficientIterator => "An iterator is used inefficiently.",
+            Rule::MemoryCloning => "Memory is cloned unnecessarily.",
+        }
+    }
+
+    /// The analysis which reports findings of this rule.
+    pub fn analysis(&self) -> &'static str {
+        match self {
+            Rule::UseAfterFree | Rule::DoubleFree | Rule::DanglingPointer => "SafeDrop",
+            Rule::MemoryLeak => "rCanary",
+            Rule::UnsafePrecondition => "senryx",
+            _ => "opt",
+        }
+    }
+
+    pub fn default_severity(&self) -> Severity {
+        match self {
+            Rule::UseAfterFree | Rule::DoubleFree | Rule::DanglingPointer => Severity::Error,
+            Rule::MemoryLeak | Rule::UnsafePrecondition => Severity::Warning,
+            _ => Severity::Note,
+        }
+    }
+}
+
+impl Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.id())
+    }
+}
+
+/// A source region resolved from a `Span`. Lines and columns are 1-based.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DiagSpan {
+    pub file: String,
+    pub start_line: usize,
+    pub start_column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+}
+
+impl DiagSpan {
+    pub fn from_span(span: Span) -> Self {
+        let source_map = get_source_map().unwrap();
+        let lo = source_map.lookup_char_pos(span.lo());
+        let hi = source_map.lookup_char_pos(span.hi());
+        DiagSpan {
+            file: source_map
+                .span_to_filename(span)
+                .display(FileNameDisplayPreference::Local)
+                .to_string(),
+            start_line: lo.line,
+            start_column: lo.col.to_usize() + 1,
+            end_line: hi.line,
+            end_column: hi.col.to_usize() + 1,
+        }
+    }
+}
+
+impl Display for DiagSpan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.file, self.start_line, self.start_column)
+    }
+}
+
+/// One finding of an analysis.
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub rule: Rule,
+    pub severity: Severity,
+    pub message: String,
+    /// The def path of the function in which the finding is reported.
+    pub function: Option<String>,
+    /// The function in which the finding is reported, where its lint is emitted.
+    pub def_id: Option<DefId>,
+    pub span: Span,
+    /// Secondary locations, e.g., the candidates of a memory leak, with their labels.
+    pub related: Vec<(Span, String)>,
+    pub help: Option<String>,
+}
+
+impl Diagnostic {
+    pub fn new(rule: Rule, message: impl ToString, span: Span) -> Self {
+        Diagnostic {
+            rule,
+            severity: rule.default_severity(),
+            message: message.to_string(),
+            function: None,
+            def_id: None,
+            span,
+            related: Vec::new(),
+            help: None,
+        }
+    }
+
+    pub fn severity(mut self, severity: Severity) -> Self {
+        self.severity = severity;
+        self
+    }
+
+    pub fn function(mut self, function: impl ToString) -> Self {
+        self.function = Some(function.to_string());
+        self
+    }
+
+    pub fn def_id(mut self, def_id: DefId) -> Self {
+        self.def_id = Some(def_id);
+        self
+    }
+
+    pub fn related(mut self, span: Span, label: impl ToString) -> Self {
+        self.related.push((span, label.to_string()));
+        self
+    }
+
+    pub fn help(mut self, help: impl ToString) -> Self {
+        self.help = Some(help.to_string());
+        self
+    }
+
+    pub fn fingerprint(&self) -> baseline::Fingerprint {
+        baseline::Fingerprint::new(self.rule, self.function.as_deref(), self.span)
+    }
+
+    /// Test if the finding is recorded in the baseline, in which case it is neither printed nor
+    /// emitted. It should be called once for each finding.
+    pub fn is_baselined(&self) -> bool {
+        baseline::is_known(&self.fingerprint())
+    }
+
+    /// Record the diagnostic in the global collector.
+    pub fn emit(self) {
+        DIAGNOSTICS.lock().unwrap().push(self);
+    }
+}
+
+/// Print the report of a finding rendered by an analysis of the function `def_id`. The findings in
+/// the local functions are emitted as lints (see [`lint::emit_lint`]), which already show them on
+/// the console, so their reports are not printed again.
+pub fn print_report(def_id: DefId, report: impl Display) {
+    if !def_id.is_local() {
+        println!("{report}");
+    }
+}
+
+/// Returns the diagnostics emitted so far and clears the collector.
+pub fn take_diagnostics() -> Vec<Diagnostic> {
+    std::mem::take(&mut *DIAGNOSTICS.lock().unwrap())
+}
+
+/// Returns the number of diagnostics emitted so far.
+pub fn diagnostics_count() -> usize {
+    DIAGNOSTICS.lock().unwrap().len()
+}
+
+/// An advisory lock on a file next to an output file, which serializes concurrent rapx processes
+/// writing it. The OS releases the lock when the process exits, even if it is killed. The lock file
+/// is left in place: removing it would let a process waiting on the removed file and a new one
+/// creating the file again hold the lock at the same time.
+pub(crate) struct FileLock {
+    // the lock is released when the file is closed
+    _file: File,
+}
+
+impl FileLock {
+    pub(crate) fn acquire(path: &Path) -> io::Result<Self> {
+        let mut lock_path = path.as_os_str().to_owned();
+        lock_path.push(".lock");
+        let file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(PathBuf::from(lock_path))?;
+        file.lock()?;
+        Ok(FileLock { _file: file })
+    }
+}
@@ -0,0 +1,169 @@
+//! Baselines of findings, which let a project adopt RAPx without fixing its existing findings
+//! first: only the findings missing from the baseline are reported.
+//!
+//! `-baseline=<file>` compares the findings with the baseline if the file exists, and records the
+//! baseline into it otherwise; `-baseline-write=<file>` always records it. A finding is identified
+//! by its fingerprint, i.e., its rule, the def path of its function, its file and the source code
+//! of its span with the whitespace normalized. Line numbers are left out, so that editing the code
+//! above a finding does not make it new.
+
+use super::{DiagSpan, Diagnostic, FileLock, Rule};
+use rustc_data_structures::fx::FxHashMap;
+use rustc_span::{source_map::get_source_map, Span};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+};
+
+const BASELINE_VERSION: u32 = 1;
+
+/// The number of times each fingerprint of the loaded baseline may still match a finding.
+static KNOWN: Mutex<Option<FxHashMap<Fingerprint, usize>>> = Mutex::new(None);
+/// The number of findings matched with the baseline.
+static SKIPPED: AtomicUsize = AtomicUsize::new(0);
+
+/// How the baseline file is used.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub enum BaselineMode {
+    /// Only report the findings missing from the baseline.
+    Compare(PathBuf),
+    /// Record the findings into the baseline.
+    Write(PathBuf),
+}
+
+impl BaselineMode {
+    /// The mode of `-baseline=<file>`: compare with the file if it exists, otherwise write it.
+    pub fn detect(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        if path.exists() {
+            BaselineMode::Compare(path)
+        } else {
+            BaselineMode::Write(path)
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        match self {
+            BaselineMode::Compare(path) | BaselineMode::Write(path) => path,
+        }
+    }
+}
+
+/// The identity of a finding which survives unrelated edits of the code.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Fingerprint {
+    pub rule: String,
+    pub function: String,
+    pub file: String,
+    pub snippet: String,
+}
+
+impl Fingerprint {
+    pub fn new(rule: Rule, function: Option<&str>, span: Span) -> Self {
+        let snippet = get_source_map()
+            .and_then(|source_map| source_map.span_to_snippet(span).ok())
+            .unwrap_or_default();
+        Fingerprint {
+            rule: rule.id().to_owned(),
+            function: function.unwrap_or_default().to_owned(),
+            file: DiagSpan::from_span(span).file,
+            snippet: snippet.split_whitespace().collect::<Vec<_>>().join(" "),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+struct BaselineEntry {
+    #[serde(flatten)]
+    fingerprint: Fingerprint,
+    /// Not part of the fingerprint; it helps to review the baseline.
+    message: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct BaselineFile {
+    version: u32,
+    findings: Vec<BaselineEntry>,
+}
+
+impl Default for BaselineFile {
+    fn default() -> Self {
+        BaselineFile {
+            version: BASELINE_VERSION,
+            findings: Vec::new(),
+        }
+    }
+}
+
+fn read_baseline(path: &Path) -> io::Result<BaselineFile> {
+    let baseline: BaselineFile = match fs::read_to_string(path) {
+        Ok(content) if !content.trim().is_empty() => serde_json::from_str(&content)?,
+        Ok(_) => return Ok(BaselineFile::default()),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(BaselineFile::default()),
+        Err(err) => return Err(err),
+    };
+    if baseline.version != BASELINE_VERSION {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "unsupported baseline version {}, expect {}",
+                baseline.version, BASELINE_VERSION
+            ),
+        ));
+    }
+    Ok(baseline)
+}
+
+/// Load the baseline to compare the findings of this run with.
+pub fn load(path: &Path) -> io::Result<()> {
+    let baseline = read_baseline(path)?;
+    let mut known = FxHashMap::default();
+    for entry in baseline.findings {
+        *known.entry(entry.fingerprint).or_insert(0) += 1;
+    }
+    *KNOWN.lock().unwrap() = Some(known);
+    Ok(())
+}
+
+/// Test if a finding is recorded in the loaded baseline. Each entry of the baseline matches one
+/// finding, so a finding duplicated since the baseline was recorded is still reported.
+pub fn is_known(fingerprint: &Fingerprint) -> bool {
+    let mut known = KNOWN.lock().unwrap();
+    let Some(count) = known
+        .as_mut()
+        .and_then(|known| known.get_mut(fingerprint))
+        .filter(|count| **count > 0)
+    else {
+        return false;
+    };
+    *count -= 1;
+    SKIPPED.fetch_add(1, Ordering::Relaxed);
+    true
+}
+
+/// Return the number of findings matched with the baseline so far.
+pub fn skipped() -> usize {
+    SKIPPED.load(Ordering::Relaxed)
+}
+
+/// Merge the findings into the baseline at `path`. The file is created if it does not exist.
+pub fn write_baseline(path: &Path, diagnostics: &[Diagnostic]) -> io::Result<()> {
+    let _lock = FileLock::acquire(path)?;
+    let mut baseline = read_baseline(path)?;
+    baseline
+        .findings
+        .extend(diagnostics.iter().map(|diagnostic| BaselineEntry {
+            fingerprint: diagnostic.fingerprint(),
+            message: diagnostic.message.clone(),
+        }));
+    // keep the file stable for version control
+    baseline.findings.sort();
+    let mut content = serde_json::to_string_pretty(&baseline)?;
+    content.push('\n');
+    fs::write(path, content)
+}
@@ -0,0 +1,93 @@
+//! The findings as rustc lints of the `rapx` tool.
+//!
+//! Each rule is a tool lint named after its id, e.g., `rapx::use_after_free`. The findings are
+//! emitted through the lint machinery of rustc, so that the lint levels (`#![deny(..)]`,
+//! `#[allow(..)]`, `-A`/`-W`/`-D`, `--cap-lints`) apply to them and they show up in the JSON
+//! messages of `cargo --message-format=json`, e.g., in the flycheck of rust-analyzer.
+
+use super::{Diagnostic, Rule};
+use rustc_lint::LintStore;
+use rustc_middle::ty::TyCtxt;
+use rustc_session::{declare_tool_lint, lint::Lint, Session};
+
+declare_tool_lint! {
+    pub rapx::USE_AFTER_FREE, Warn, "use of memory after it has been freed (SafeDrop)"
+}
+declare_tool_lint! {
+    pub rapx::DOUBLE_FREE, Warn, "memory freed more than once (SafeDrop)"
+}
+declare_tool_lint! {
+    pub rapx::DANGLING_POINTER, Warn, "a pointer to freed memory escapes the function (SafeDrop)"
+}
+declare_tool_lint! {
+    pub rapx::MEMORY_LEAK, Warn, "heap memory never released (rCanary)"
+}
+declare_tool_lint! {
+    pub rapx::UNSAFE_PRECONDITION, Warn, "the safety properties of an unsafe API may not hold (senryx)"
+}
+declare_tool_lint! {
+    pub rapx::BOUNDS_CHECKING, Warn, "bounds checking proven unnecessary (opt)"
+}
+declare_tool_lint! {
+    pub rapx::ENCODING_CHECKING, Warn, "encoding checking proven unnecessary (opt)"
+}
+declare_tool_lint! {
+    pub rapx::SUBOPTIMAL_COLLECTION, Warn, "a data collection used in a suboptimal way (opt)"
+}
+declare_tool_lint! {
+    pub rapx::COLLECTION_INITIALIZATION, Warn, "unnecessary initialization of a data collection (opt)"
+}
+declare_tool_lint! {
+    pub rapx::REALLOCATION, Warn, "a data collection may be reallocated repeatedly (opt)"
+}
+declare_tool_lint! {
+    pub rapx::INEFFICIENT_ITERATOR, Warn, "an iterator used inefficiently (opt)"
+}
+declare_tool_lint! {
+    pub rapx::MEMORY_CLONING, Warn, "memory cloned unnecessarily (opt)"
+}
+
+/// Return the lint of a rule.
+pub fn lint_of(rule: Rule) -> &'static Lint {
+    match rule {
+        Rule::UseAfterFree => USE_AFTER_FREE,
+        Rule::DoubleFree => DOUBLE_FREE,
+        Rule::DanglingPointer => DANGLING_POINTER,
+        Rule::MemoryLeak => MEMORY_LEAK,
+        Rule::UnsafePrecondition => UNSAFE_PRECONDITION,
+        Rule::BoundsChecking => BOUNDS_CHECKING,
+        Rule::EncodingChecking => ENCODING_CHECKING,
+        Rule::SuboptimalCollection => SUBOPTIMAL_COLLECTION,
+        Rule::CollectionInitialization => COLLECTION_INITIALIZATION,
+        Rule::Reallocation => REALLOCATION,
+        Rule::InefficientIterator => INEFFICIENT_ITERATOR,
+        Rule::MemoryCloning => MEMORY_CLONING,
+    }
+}
+
+/// Register the lints of all rules; it is set as the `register_lints` hook of the compiler.
+pub fn register_lints(_sess: &Session, store: &mut LintStore) {
+    let lints: Vec<_> = Rule::ALL.into_iter().map(lint_of).collect();
+    store.register_lints(&lints);
+}
+
+/// Emit a finding as a lint at the function it is found in. Findings outside the local functions
+/// are only recorded.
+pub fn emit_lint(tcx: TyCtxt<'_>, diagnostic: &Diagnostic) {
+    let Some(local_def_id) = diagnostic.def_id.and_then(|def_id| def_id.as_local()) else {
+        return;
+    };
+    let hir_id = tcx.local_def_id_to_hir_id(local_def_id);
+    tcx.node_span_lint(lint_of(diagnostic.rule), hir_id, diagnostic.span, |diag| {
+        diag.primary_message(diagnostic.message.clone());
+        // the enclosing function is already pointed to by the primary span
+        for (span, label) in diagnostic.related.iter() {
+            if !span.contains(diagnostic.span) {
+                diag.span_label(*span, label.clone());
+            }
+        }
+        if let Some(help) = &diagnostic.help {
+            diag.help(help.clone());
+        }
+    });
+}
@@ -0,0 +1,124 @@
+//! The findings passed from the rapx driver back to `cargo rapx`.
+//!
+//! Each crate is analyzed in its own rustc-wrapper process, so `cargo rapx` sets `RAP_RESULTS_DIR`
+//! to a folder where the driver writes the findings of each compilation unit. After cargo has
+//! finished, `cargo rapx` reads them to print a summary of the whole run and to gate the exit
+//! status with `-fail-on=<severity|rules>`. The folder is kept across runs, so that the findings
+//! of the crates cargo considers fresh, which are not analyzed again, are still counted.
+
+use super::{DiagSpan, Diagnostic, Rule, Severity};
+use serde::{Deserialize, Serialize};
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+    process,
+};
+
+pub const RAP_RESULTS_DIR: &str = "RAP_RESULTS_DIR";
+
+/// A finding of a crate, in the form written to the results folder.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Finding {
+    pub rule: String,
+    pub severity: String,
+    pub function: Option<String>,
+    pub location: String,
+    pub message: String,
+}
+
+impl Finding {
+    fn new(diagnostic: &Diagnostic) -> Self {
+        Finding {
+            rule: diagnostic.rule.id().to_owned(),
+            severity: diagnostic.severity.as_str().to_owned(),
+            function: diagnostic.function.clone(),
+            location: DiagSpan::from_span(diagnostic.span).to_string(),
+            message: diagnostic.message.clone(),
+        }
+    }
+}
+
+/// The findings of a crate.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrateResults {
+    pub krate: String,
+    pub findings: Vec<Finding>,
+}
+
+/// The file of the findings of a compilation unit. A crate may be compiled several times, e.g., as
+/// a library and as tests, so the file is named after the crate and the `-C extra-filename` which
+/// cargo assigns to the unit.
+pub fn unit_file(dir: &Path, krate: &str, extra_filename: &str) -> PathBuf {
+    dir.join(format!("{krate}{extra_filename}.json"))
+}
+
+/// Write the findings of a compilation unit to the results folder if `cargo rapx` has set one.
+pub fn write_results(
+    krate: &str,
+    extra_filename: &str,
+    diagnostics: &[Diagnostic],
+) -> io::Result<()> {
+    let Some(dir) = env::var_os(RAP_RESULTS_DIR) else {
+        return Ok(());
+    };
+    let results = CrateResults {
+        krate: krate.to_owned(),
+        findings: diagnostics.iter().map(Finding::new).collect(),
+    };
+    // members checked in parallel may analyze the same path dependency, so the file is renamed into
+    // place to never be read half-written
+    let path = unit_file(Path::new(&dir), krate, extra_filename);
+    let tmp = path.with_extension(format!("json.{}", process::id()));
+    fs::write(&tmp, serde_json::to_string(&results)?)?;
+    fs::rename(&tmp, path)
+}
+
+/// Read the findings of all crates in the results folder.
+pub fn read_results(dir: &Path) -> io::Result<Vec<CrateResults>> {
+    let mut results = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|ext| ext == "json") {
+            results.push(serde_json::from_str(&fs::read_to_string(path)?)?);
+        }
+    }
+    results.sort_by(|a: &CrateResults, b| a.krate.cmp(&b.krate));
+    Ok(results)
+}
+
+/// The findings which fail the run, given by `-fail-on=<severity|rules>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FailOn {
+    /// The findings of this severity or a more severe one, e.g., `-fail-on=warning`.
+    Severity(Severity),
+    /// The findings of these rules, e.g., `-fail-on=double_free,use_after_free`.
+    Rules(Vec<Rule>),
+}
+
+impl FailOn {
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        if let Some(severity) = Severity::parse(spec) {
+            return Ok(FailOn::Severity(severity));
+        }
+        spec.split(',')
+            .map(|id| {
+                Rule::from_id(id.trim()).ok_or_else(|| {
+                    format!(
+                        "Invalid -fail-on `{id}`: expect error, warning, note or a list of rules"
+                    )
+                })
+            })
+            .collect::<Result<_, _>>()
+            .map(FailOn::Rules)
+    }
+
+    /// Test if a finding fails the run.
+    pub fn gates(&self, finding: &Finding) -> bool {
+        match self {
+            FailOn::Severity(severity) => {
+                Severity::parse(&finding.severity).is_some_and(|found| found.is_at_least(*severity))
+            }
+            FailOn::Rules(rules) => rules.iter().any(|rule| rule.id() == finding.rule),
+        }
+    }
+}
@@ -0,0 +1,247 @@
+//! A writer for SARIF 2.1.0 logs (<https://docs.oasis-open.org/sarif/sarif/v2.1.0/sarif-v2.1.0.html>).
+//!
+//! Under `cargo rapx`, each crate is analyzed by a separate rapx process. All of them write to the
+//! same log, so the results of a crate are merged into the single run of an existing log instead
+//! of overwriting it.
+
+use super::{DiagSpan, Diagnostic, FileLock, Rule};
+use serde::{Deserialize, Serialize};
+use std::{fs, io, path::Path};
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+const SARIF_VERSION: &str = "2.1.0";
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SarifLog {
+    #[serde(rename = "$schema")]
+    pub schema: String,
+    pub version: String,
+    pub runs: Vec<Run>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Run {
+    pub tool: Tool,
+    pub results: Vec<SarifResult>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Tool {
+    pub driver: ToolComponent,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolComponent {
+    pub name: String,
+    pub version: String,
+    pub information_uri: String,
+    pub rules: Vec<ReportingDescriptor>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportingDescriptor {
+    pub id: String,
+    pub short_description: Message,
+    pub default_configuration: ReportingConfiguration,
+    pub properties: RuleProperties,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ReportingConfiguration {
+    pub level: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RuleProperties {
+    pub analysis: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifResult {
+    pub rule_id: String,
+    pub level: String,
+    pub message: Message,
+    pub locations: Vec<Location>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub related_locations: Vec<Location>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Message {
+    pub text: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Location {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<usize>,
+    pub physical_location: PhysicalLocation,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub logical_locations: Vec<LogicalLocation>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<Message>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PhysicalLocation {
+    pub artifact_location: ArtifactLocation,
+    pub region: Region,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ArtifactLocation {
+    pub uri: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Region {
+    pub start_line: usize,
+    pub start_column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct LogicalLocation {
+    pub fully_qualified_name: String,
+    pub kind: String,
+}
+
+impl SarifLog {
+    pub fn new() -> Self {
+        SarifLog {
+            schema: SARIF_SCHEMA.to_owned(),
+            version: SARIF_VERSION.to_owned(),
+            runs: vec![Run {
+                tool: Tool {
+                    driver: ToolComponent {
+                        name: "RAPx".to_owned(),
+                        version: env!("CARGO_PKG_VERSION").to_owned(),
+                        information_uri: env!("CARGO_PKG_REPOSITORY").to_owned(),
+                        rules: Vec::new(),
+                    },
+                },
+                results: Vec::new(),
+            }],
+        }
+    }
+
+    /// Append the diagnostics to the first run of the log, and register the rules they refer to.
+    pub fn add_diagnostics(&mut self, diagnostics: &[Diagnostic]) {
+        let run = &mut self.runs[0];
+        for diagnostic in diagnostics {
+            let rule = diagnostic.rule;
+            if !run.tool.driver.rules.iter().any(|r| r.id == rule.id()) {
+                run.tool.driver.rules.push(rule_descriptor(rule));
+            }
+            run.results.push(sarif_result(diagnostic));
+        }
+        run.tool.driver.rules.sort_by(|a, b| a.id.cmp(&b.id));
+    }
+}
+
+impl Default for SarifLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn rule_descriptor(rule: Rule) -> ReportingDescriptor {
+    ReportingDescriptor {
+        id: rule.id().to_owned(),
+        short_description: Message {
+            text: rule.description().to_owned(),
+        },
+        default_configuration: ReportingConfiguration {
+            level: rule.default_severity().as_str().to_owned(),
+        },
+        properties: RuleProperties {
+            analysis: rule.analysis().to_owned(),
+        },
+    }
+}
+
+fn sarif_result(diagnostic: &Diagnostic) -> SarifResult {
+    let mut text = diagnostic.message.clone();
+    if let Some(help) = &diagnostic.help {
+        text = format!("{} Help: {}", text, help);
+    }
+    let mut location = physical_location(diagnostic.span, None);
+    if let Some(function) = &diagnostic.function {
+        location.logical_locations.push(LogicalLocation {
+            fully_qualified_name: function.clone(),
+            kind: "function".to_owned(),
+        });
+    }
+    let related_locations = diagnostic
+        .related
+        .iter()
+        .enumerate()
+        .map(|(idx, (span, label))| {
+            let mut location = physical_location(*span, Some(label.clone()));
+            location.id = Some(idx);
+            location
+        })
+        .collect();
+    SarifResult {
+        rule_id: diagnostic.rule.id().to_owned(),
+        level: diagnostic.severity.as_str().to_owned(),
+        message: Message { text },
+        locations: vec![location],
+        related_locations,
+    }
+}
+
+fn physical_location(span: rustc_span::Span, label: Option<String>) -> Location {
+    let span = DiagSpan::from_span(span);
+    Location {
+        id: None,
+        physical_location: PhysicalLocation {
+            artifact_location: ArtifactLocation {
+                uri: file_uri(&span.file),
+            },
+            region: Region {
+                start_line: span.start_line,
+                start_column: span.start_column,
+                end_line: span.end_line,
+                end_column: span.end_column,
+            },
+        },
+        logical_locations: Vec::new(),
+        message: label.map(|text| Message { text }),
+    }
+}
+
+fn file_uri(file: &str) -> String {
+    let file = file.replace('\\', "/");
+    if file.starts_with('/') {
+        format!("file://{}", file)
+    } else {
+        file
+    }
+}
+
+/// Merge the diagnostics into the SARIF log at `path`. The log is created if it does not exist.
+pub fn write_sarif(path: &Path, diagnostics: &[Diagnostic]) -> io::Result<()> {
+    let _lock = FileLock::acquire(path)?;
+    let mut log = match fs::read_to_string(path) {
+        Ok(content) if !content.trim().is_empty() => serde_json::from_str(&content)?,
+        Ok(_) => SarifLog::new(),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => SarifLog::new(),
+        Err(err) => return Err(err),
+    };
+    if log.runs.is_empty() {
+        log = SarifLog::new();
+    }
+    log.add_diagnostics(diagnostics);
+    let file = fs::File::create(path)?;
+    serde_json::to_writer_pretty(file, &log)?;
+    Ok(())
+}
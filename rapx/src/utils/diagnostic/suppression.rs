@@ -0,0 +1,109 @@
+//! Suppress reviewed findings in the source code with the `rapx::allow` tool attribute.
+//!
+//! The attribute takes the ids of the rules to allow, and applies to the item it is attached to and
+//! all items nested in it, e.g., a function, an impl block, a module or the whole crate:
+//!
+//! ```ignore
+//! #[cfg_attr(rapx, rapx::allow(use_after_free, dangling_pointer))]
+//! fn reviewed() { /* ... */ }
+//! ```
+//!
+//! RAPx registers `rapx` as a tool and sets `--cfg rapx` when analyzing the crates it analyzes, so
+//! wrapping the attribute in `cfg_attr` keeps the crate compiling with plain rustc.
+//!
+//! Allowing the lint of a rule, e.g., `#[allow(rapx::memory_leak)]` or `-A rapx::memory_leak`,
+//! suppresses its findings as well; see [`super::lint`].
+
+use super::{lint::lint_of, Rule};
+use rustc_ast::{
+    ast,
+    attr::{mk_attr_nested_word, AttrIdGenerator},
+    AttrStyle, Safety,
+};
+use rustc_hir::def_id::DefId;
+use rustc_middle::ty::TyCtxt;
+use rustc_session::lint::Level;
+use rustc_span::{sym, Symbol, DUMMY_SP};
+
+/// The name of the tool registered for the attributes of RAPx.
+pub const TOOL: &str = "rapx";
+
+/// Register the `rapx` tool in the crate being analyzed unless the crate does it itself, e.g., to
+/// annotate the safety properties checked by senryx.
+pub fn register_tool(krate: &mut ast::Crate, g: &AttrIdGenerator) {
+    let tool = Symbol::intern(TOOL);
+    let has_nested = |outer: Symbol, inner: Symbol| {
+        krate.attrs.iter().any(|attr| {
+            attr.has_name(outer)
+                && attr
+                    .meta_item_list()
+                    .is_some_and(|items| items.iter().any(|item| item.has_name(inner)))
+        })
+    };
+    let mut attrs = Vec::new();
+    if !has_nested(sym::feature, sym::register_tool) {
+        attrs.push((sym::feature, sym::register_tool));
+    }
+    if !has_nested(sym::register_tool, tool) {
+        attrs.push((sym::register_tool, tool));
+    }
+    for (outer, inner) in attrs {
+        krate.attrs.push(mk_attr_nested_word(
+            g,
+            AttrStyle::Inner,
+            Safety::Default,
+            outer,
+            inner,
+            DUMMY_SP,
+        ));
+    }
+}
+
+/// Return the rules allowed for a function by the `rapx::allow` attributes on it and on the items
+/// enclosing it, or by the levels of their lints at the function.
+pub fn allowed_rules(tcx: TyCtxt<'_>, def_id: DefId) -> Vec<Rule> {
+    let path = [Symbol::intern(TOOL), Symbol::intern("allow")];
+    let mut rules = Vec::new();
+    if !def_id.is_local() {
+        return rules;
+    }
+    let mut current = Some(def_id);
+    while let Some(def_id) = current {
+        for attr in tcx.get_attrs_by_path(def_id, &path) {
+            let Some(items) = attr.meta_item_list() else {
+                tcx.dcx().span_warn(
+                    attr.span(),
+                    "`rapx::allow` expects the ids of rules, e.g., `rapx::allow(memory_leak)`",
+                );
+                continue;
+            };
+            for item in items.iter() {
+                match item.name().and_then(|name| Rule::from_id(name.as_str())) {
+                    Some(rule) => rules.push(rule),
+                    None => {
+                        let ids: Vec<_> = Rule::ALL.iter().map(Rule::id).collect();
+                        tcx.dcx().span_warn(
+                            item.span(),
+                            format!("unknown rule of RAPx, expect one of: {}", ids.join(", ")),
+                        );
+                    }
+                }
+            }
+        }
+        current = tcx.opt_parent(def_id);
+    }
+    let hir_id = tcx.local_def_id_to_hir_id(def_id.expect_local());
+    for rule in Rule::ALL {
+        if tcx.lint_level_at_node(lint_of(rule), hir_id).level == Level::Allow
+            && !rules.contains(&rule)
+        {
+            rules.push(rule);
+        }
+    }
+    rules
+}
+
+/// Test if the findings of `rule` in a function are allowed.
+pub fn is_allowed(tcx: TyCtxt<'_>, def_id: DefId, rule: Rule) -> bool {
+    allowed_rules(tcx, def_id).contains(&rule)
+}
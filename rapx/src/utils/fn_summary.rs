@@ -0,0 +1,260 @@
+//! Summaries of opaque callees supplied by users with `-summaries=<file>`.
+//!
+//! FFI functions, intrinsics and the functions of crates that are not analyzed have no MIR, so the
+//! analyses cannot tell what a call to them does. A summary file describes such functions by their
+//! def paths, either in TOML or in JSON (chosen by the extension of the file):
+//!
+//! ```toml
+//! [[function]]
+//! path = "mycrate::ffi::buf_free"
+//! drops = [1]
+//!
+//! [[function]]
+//! path = "mycrate::ffi::buf_data"
+//! aliases = [["0", "1.0"]]
+//!
+//! [[function]]
+//! path = "mycrate::ffi::buf_new"
+//! allocates = true
+//! ```
+//!
+//! As in the results of the alias analysis, 0 stands for the return value and the arguments are
+//! numbered from 1; `1.0` is the first field of the first argument. The keys of a function are:
+//! * `aliases`: the pairs of places that alias each other after the call;
+//! * `drops`: the arguments dropped or freed by the callee;
+//! * `transfers`: the arguments whose ownership is taken over by the callee;
+//! * `allocates`: whether the return value owns a new piece of heap memory.
+//!
+//! The summaries are read by the alias analysis (and thus by every analysis built upon it),
+//! SafeDrop, rCanary and senryx. A summary takes precedence over the MIR of the function.
+//!
+//! The common methods of the std containers and smart pointers, e.g., `Vec::push`,
+//! `HashMap::get`, `Box::into_raw` and `Option::take`, have built-in summaries in the same format
+//! (`data/std_models.toml`), so the analyses do not descend into their bodies; a summary in the
+//! file given by users replaces the built-in one of the same function.
+
+use crate::{
+    analysis::{core::alias_analysis::AAFact, utils::def_path::def_path_def_ids},
+    rap_debug, rap_error, rap_warn,
+};
+use rustc_data_structures::{fingerprint::Fingerprint, fx::FxHashMap, stable_hasher::StableHasher};
+use rustc_hir::{
+    def::DefKind,
+    def_id::{DefId, LOCAL_CRATE},
+};
+use rustc_middle::ty::TyCtxt;
+use serde::Deserialize;
+use std::{fs, hash::Hash, path::Path, sync::OnceLock};
+
+static SUMMARIES: OnceLock<FnSummaries> = OnceLock::new();
+
+const STD_MODELS: &str = include_str!("data/std_models.toml");
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SummaryFile {
+    #[serde(default)]
+    function: Vec<RawFnSummary>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawFnSummary {
+    path: String,
+    #[serde(default)]
+    aliases: Vec<[String; 2]>,
+    #[serde(default)]
+    drops: Vec<usize>,
+    #[serde(default)]
+    transfers: Vec<usize>,
+    #[serde(default)]
+    allocates: bool,
+}
+
+/// The effects of calling a function, as declared in the summary file.
+#[derive(Debug, Clone, Default)]
+pub struct FnSummary {
+    /// The alias pairs established by the call.
+    pub aliases: Vec<AAFact>,
+    /// The arguments (starting from 1) dropped or freed by the callee.
+    pub drops: Vec<usize>,
+    /// The arguments (starting from 1) whose ownership is taken over by the callee.
+    pub transfers: Vec<usize>,
+    /// Whether the return value owns a new piece of heap memory.
+    pub allocates: bool,
+}
+
+impl FnSummary {
+    /// Test if the callee releases the given argument, either by freeing it or by taking it over.
+    pub fn releases(&self, arg: usize) -> bool {
+        self.drops.contains(&arg) || self.transfers.contains(&arg)
+    }
+}
+
+struct FnSummaries {
+    map: FxHashMap<DefId, FnSummary>,
+    fingerprint: String,
+}
+
+/// Load the built-in models and the summary file, and resolve the functions declared in them. It
+/// should be called once before the analyses start.
+pub fn init(tcx: TyCtxt<'_>, path: Option<&Path>) {
+    SUMMARIES.get_or_init(|| {
+        let mut summaries = FnSummaries {
+            map: FxHashMap::default(),
+            fingerprint: String::new(),
+        };
+        match toml::from_str::<SummaryFile>(STD_MODELS) {
+            Ok(file) => summaries.add(tcx, file),
+            Err(err) => rap_error!("Invalid std models: {err}"),
+        }
+        if let Some(path) = path {
+            load(tcx, path, &mut summaries);
+        }
+        summaries
+    });
+}
+
+/// Return the user-supplied summary of a function.
+pub fn get(def_id: DefId) -> Option<&'static FnSummary> {
+    SUMMARIES.get()?.map.get(&def_id)
+}
+
+/// Iterate over all the functions with a user-supplied summary.
+pub fn summaries() -> impl Iterator<Item = (DefId, &'static FnSummary)> {
+    SUMMARIES
+        .get()
+        .into_iter()
+        .flat_map(|summaries| summaries.map.iter().map(|(def_id, s)| (*def_id, s)))
+}
+
+/// The fingerprint of the content of the summary file, or an empty string if there is none. The
+/// cached results of the analyses reading the summaries depend on it.
+pub fn fingerprint() -> &'static str {
+    SUMMARIES
+        .get()
+        .map(|summaries| summaries.fingerprint.as_str())
+        .unwrap_or_default()
+}
+
+fn load(tcx: TyCtxt<'_>, path: &Path, summaries: &mut FnSummaries) {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(err) => {
+            rap_error!("Failed to read the summary file {}: {err}", path.display());
+            return;
+        }
+    };
+    let file = match path.extension().and_then(|ext| ext.to_str()) {
+        Some("json") => serde_json::from_str::<SummaryFile>(&content).map_err(|e| e.to_string()),
+        _ => toml::from_str::<SummaryFile>(&content).map_err(|e| e.to_string()),
+    };
+    let file = match file {
+        Ok(file) => file,
+        Err(err) => {
+            rap_error!("Invalid summary file {}: {err}", path.display());
+            return;
+        }
+    };
+
+    let mut hasher = StableHasher::new();
+    content.hash(&mut hasher);
+    summaries.fingerprint = hasher.finish::<Fingerprint>().to_hex();
+    summaries.add(tcx, file);
+}
+
+impl FnSummaries {
+    /// Add the summaries of a file, which replace the ones of the same functions.
+    fn add(&mut self, tcx: TyCtxt<'_>, file: SummaryFile) {
+        for raw in file.function {
+            let def_ids = resolve(tcx, &raw.path);
+            if def_ids.is_empty() {
+                // the file may be shared by the crates of a workspace, and the std models name
+                // the crates that may not be linked
+                rap_debug!("Function {} in the summary file is not found.", raw.path);
+                continue;
+            }
+            for def_id in def_ids {
+                let arg_count = tcx
+                    .fn_sig(def_id)
+                    .skip_binder()
+                    .inputs()
+                    .skip_binder()
+                    .len();
+                match parse_summary(&raw, arg_count) {
+                    Ok(summary) => {
+                        rap_debug!("Load the summary of {}: {:?}", raw.path, summary);
+                        self.map.insert(def_id, summary);
+                    }
+                    Err(err) => rap_warn!("Ignore the summary of {}: {err}", raw.path),
+                }
+            }
+        }
+    }
+}
+
+/// Resolve a def path to the functions it names. The path starts with the name of a crate, and
+/// `crate::` can be used for the local one.
+fn resolve(tcx: TyCtxt<'_>, path: &str) -> Vec<DefId> {
+    let crate_name = tcx.crate_name(LOCAL_CRATE);
+    let path = match path.strip_prefix("crate::") {
+        Some(rest) => format!("{crate_name}::{rest}"),
+        None => path.to_owned(),
+    };
+    let segments: Vec<&str> = path.split("::").collect();
+    def_path_def_ids(&tcx, &segments)
+        .filter(|def_id| matches!(tcx.def_kind(*def_id), DefKind::Fn | DefKind::AssocFn))
+        .collect()
+}
+
+fn parse_summary(raw: &RawFnSummary, arg_count: usize) -> Result<FnSummary, String> {
+    let check_arg = |arg: usize| {
+        if arg == 0 || arg > arg_count {
+            Err(format!("argument {arg} is out of 1..={arg_count}"))
+        } else {
+            Ok(arg)
+        }
+    };
+    let mut aliases = Vec::with_capacity(raw.aliases.len());
+    for [lhs, rhs] in &raw.aliases {
+        let (lhs_no, lhs_fields) = parse_place(lhs, arg_count)?;
+        let (rhs_no, rhs_fields) = parse_place(rhs, arg_count)?;
+        aliases.push(AAFact {
+            lhs_no,
+            lhs_fields,
+            rhs_no,
+            rhs_fields,
+        });
+    }
+    Ok(FnSummary {
+        aliases,
+        drops: raw
+            .drops
+            .iter()
+            .map(|arg| check_arg(*arg))
+            .collect::<Result<_, _>>()?,
+        transfers: raw
+            .transfers
+            .iter()
+            .map(|arg| check_arg(*arg))
+            .collect::<Result<_, _>>()?,
+        allocates: raw.allocates,
+    })
+}
+
+/// Parse a place of the form `<local>(.<field>)*`, e.g., `1.0.2` for the third field of the first
+/// field of the first argument. The local is 0 for the return value or an argument up to
+/// `arg_count`; the fields are numbered from 0, and the blanks around the numbers are ignored.
+fn parse_place(place: &str, arg_count: usize) -> Result<(usize, Vec<usize>), String> {
+    let mut indices = place.split('.').map(|index| {
+        index
+            .trim()
+            .parse::<usize>()
+            .map_err(|_| format!("invalid place `{place}`"))
+    });
+    let local = indices.next().unwrap()?;
+    if local > arg_count {
+        return Err(format!("place `{place}` is out of 0..={arg_count}"));
+    }
+    Ok((local, indices.collect::<Result<_, _>>()?))
+}
@@ -0,0 +1,56 @@
+use rustc_hir::def_id::LOCAL_CRATE;
+use rustc_middle::ty::TyCtxt;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// This collects the results of the core analyses of the local crate, and writes them into one
+/// JSON file named `rapx_<crate>_<crate type>.json`. Each result is stored under the name of its
+/// analysis, e.g., `alias` or `range`.
+pub struct JsonReport {
+    crate_name: String,
+    crate_type: String,
+    results: Map<String, Value>,
+}
+
+impl JsonReport {
+    pub fn new(tcx: TyCtxt<'_>) -> Self {
+        Self {
+            crate_name: tcx.crate_name(LOCAL_CRATE).to_string(),
+            crate_type: tcx.crate_types()[0].to_string(),
+            results: Map::new(),
+        }
+    }
+
+    /// Add the result of an analysis. The report is left unchanged if the result cannot be
+    /// serialized.
+    pub fn add(&mut self, analysis: &str, result: impl Serialize) -> serde_json::Result<()> {
+        let value = serde_json::to_value(result)?;
+        self.results.insert(analysis.to_owned(), value);
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.results.is_empty()
+    }
+
+    /// Write the report into the directory `dir`, and return the path of the JSON file.
+    pub fn dump_to_json(&self, dir: impl AsRef<Path>) -> io::Result<PathBuf> {
+        let dir = dir.as_ref();
+        fs::create_dir_all(dir)?;
+        let path = dir.join(format!("rapx_{}_{}.json", self.crate_name, self.crate_type));
+        let mut report = Map::new();
+        report.insert("crate".to_owned(), Value::from(self.crate_name.clone()));
+        report.insert(
+            "crate_type".to_owned(),
+            Value::from(self.crate_type.clone()),
+        );
+        report.extend(self.results.clone());
+        let file = fs::File::create(&path)?;
+        serde_json::to_writer_pretty(file, &report)?;
+        Ok(path)
+    }
+}
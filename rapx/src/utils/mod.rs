@@ -1,4 +1,13 @@
+pub mod budget;
+pub mod cache;
+pub mod config;
+pub mod diagnostic;
+pub mod fn_summary;
 pub mod fs;
+pub mod json;
 #[macro_use]
 pub mod log;
+pub mod precision;
+pub mod profile;
+pub mod scope;
 pub mod source;
@@ -0,0 +1,87 @@
+//! The precision of the alias analysis (MoP) and SafeDrop.
+//!
+//! The levels are set with `-alias0`..`-alias2` for MoP and `-F0`..`-F2` for both analyses, where
+//! `-F` stands for `-F1`:
+//! * level 0: the field depth is limited to 10, and the flows within each strongly-connected
+//!   component are not distinguished;
+//! * level 1: the field depth is limited to 20;
+//! * level 2: the field depth is limited to 30;
+//! * level 3: the field depth is limited to 50; no option selects it, but a driver may [`init`]
+//!   the precision with it.
+//!
+//! Without a level, e.g., with `-alias`, the field depth is limited to 15. `-field-depth=<n>`
+//! overrides the limit of both analyses.
+//!
+//! `-context-depth=<k>` analyzes the callees of the local crate again in the context of each call,
+//! up to k calls deep; by default, the summary of a callee is shared by all its callers.
+
+use std::sync::OnceLock;
+
+static PRECISION: OnceLock<Precision> = OnceLock::new();
+
+#[derive(Debug, Clone, Default, Hash, PartialEq, Eq)]
+pub struct Precision {
+    /// The level of MoP.
+    pub mop: Option<usize>,
+    /// The level of SafeDrop.
+    pub safedrop: Option<usize>,
+    /// The field depth overriding the one of the levels.
+    pub field_depth: Option<usize>,
+    /// The depth of the calling contexts of the callee summaries, 0 for none.
+    pub context_depth: usize,
+}
+
+impl Precision {
+    /// The maximum field depth of MoP.
+    pub fn mop_field_depth(&self) -> usize {
+        self.field_depth
+            .unwrap_or_else(|| level_field_depth(self.mop))
+    }
+
+    /// The maximum field depth of SafeDrop.
+    pub fn safedrop_field_depth(&self) -> usize {
+        self.field_depth
+            .unwrap_or_else(|| level_field_depth(self.safedrop))
+    }
+
+    /// Test if MoP visits the blocks of a strongly-connected component in a single flow.
+    pub fn mop_merges_scc(&self) -> bool {
+        self.mop == Some(0)
+    }
+
+    /// Test if SafeDrop visits the blocks of a strongly-connected component in a single flow.
+    pub fn safedrop_merges_scc(&self) -> bool {
+        self.safedrop == Some(0)
+    }
+
+    /// The fingerprint of the precision of MoP. The cached results of MoP depend on it.
+    pub fn mop_fingerprint(&self) -> String {
+        format!(
+            "{:?}:{}:{}",
+            self.mop,
+            self.mop_field_depth(),
+            self.context_depth
+        )
+    }
+}
+
+fn level_field_depth(level: Option<usize>) -> usize {
+    match level {
+        Some(0) => 10,
+        Some(1) => 20,
+        Some(2) => 30,
+        Some(3) => 50,
+        _ => 15,
+    }
+}
+
+/// Set the precision. It should be called once before the analyses start; the default precision is
+/// used otherwise.
+pub fn init(precision: Precision) {
+    PRECISION.get_or_init(|| precision);
+}
+
+/// Return the precision of the analyses.
+pub fn get() -> &'static Precision {
+    PRECISION.get_or_init(Precision::default)
+}
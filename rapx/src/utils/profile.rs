@@ -0,0 +1,238 @@
+//! The resources consumed by the analyses of a run, reported with `-profile`.
+//!
+//! Each analysis records its wall-clock time and its peak memory, i.e., the peak resident set size
+//! of the process while it runs. The time of an analysis excludes the ones of the core analyses it
+//! requests first, which are reported on their own. The path-sensitive analyses also record the
+//! time of each function along with their own counters, e.g., the path visits of MoP and SafeDrop,
+//! the Z3 solver calls of rCanary, the size of the constraint graphs of the range analysis and the
+//! paths of senryx, so that the functions worth a tighter budget stand out.
+//!
+//! The report is printed as a table and written to `rapx_profile_<crate>_<type>.json`.
+
+use rustc_hir::def_id::{DefId, LOCAL_CRATE};
+use rustc_middle::ty::TyCtxt;
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs, io,
+    path::{Path, PathBuf},
+    sync::{Mutex, OnceLock},
+    time::{Duration, Instant},
+};
+
+/// The number of the most expensive functions reported for each analysis.
+const TOP_FUNCTIONS: usize = 10;
+
+static ENABLED: OnceLock<bool> = OnceLock::new();
+static PROFILE: Mutex<Profile> = Mutex::new(Profile::new());
+
+struct Profile {
+    analyses: Vec<AnalysisProfile>,
+    running: Vec<Running>,
+    functions: Option<HashMap<(&'static str, DefId), FnProfile>>,
+}
+
+impl Profile {
+    const fn new() -> Self {
+        Profile {
+            analyses: Vec::new(),
+            running: Vec::new(),
+            functions: None,
+        }
+    }
+}
+
+/// An analysis being run, which may request other analyses.
+struct Running {
+    name: String,
+    start: Instant,
+    nested: Duration,
+    peak_memory: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct AnalysisProfile {
+    name: String,
+    /// In seconds, excluding the analyses it requests.
+    time: f64,
+    /// In seconds, including the analyses it requests.
+    total_time: f64,
+    /// In bytes, if the platform reports it.
+    peak_memory: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct FnProfile {
+    time: Duration,
+    counters: BTreeMap<&'static str, usize>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct FnReport {
+    function: String,
+    /// In seconds.
+    time: f64,
+    counters: BTreeMap<&'static str, usize>,
+}
+
+/// Enable profiling. It should be called once before the analyses start; nothing is recorded
+/// otherwise.
+pub fn init(enabled: bool) {
+    ENABLED.get_or_init(|| enabled);
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.get().copied().unwrap_or(false)
+}
+
+/// Run an analysis and record its time and peak memory.
+pub fn analysis<R>(name: &str, run: impl FnOnce() -> R) -> R {
+    if !is_enabled() {
+        return run();
+    }
+    {
+        let mut profile = PROFILE.lock().unwrap();
+        // the peak of the running analysis is taken before it is reset for the new one
+        if let Some(parent) = profile.running.last_mut() {
+            parent.peak_memory = parent.peak_memory.max(memory::peak());
+        }
+        memory::reset_peak();
+        profile.running.push(Running {
+            name: name.to_owned(),
+            start: Instant::now(),
+            nested: Duration::ZERO,
+            peak_memory: None,
+        });
+    }
+    let result = run();
+    let mut profile = PROFILE.lock().unwrap();
+    let running = profile.running.pop().expect("the analysis is running");
+    let total = running.start.elapsed();
+    let peak_memory = running.peak_memory.max(memory::peak());
+    if let Some(parent) = profile.running.last_mut() {
+        parent.nested += total;
+        parent.peak_memory = parent.peak_memory.max(peak_memory);
+    }
+    profile.analyses.push(AnalysisProfile {
+        name: running.name,
+        time: total.saturating_sub(running.nested).as_secs_f64(),
+        total_time: total.as_secs_f64(),
+        peak_memory,
+    });
+    result
+}
+
+/// Add the time spent by an analysis on a function.
+pub fn record_time(analysis: &'static str, def_id: DefId, time: Duration) {
+    if is_enabled() {
+        with_function(analysis, def_id, |function| function.time += time);
+    }
+}
+
+/// Add to a counter of an analysis on a function, e.g., the number of path visits.
+pub fn record_count(analysis: &'static str, def_id: DefId, counter: &'static str, count: usize) {
+    if is_enabled() {
+        with_function(analysis, def_id, |function| {
+            *function.counters.entry(counter).or_default() += count
+        });
+    }
+}
+
+fn with_function(analysis: &'static str, def_id: DefId, update: impl FnOnce(&mut FnProfile)) {
+    let mut profile = PROFILE.lock().unwrap();
+    let functions = profile.functions.get_or_insert_with(HashMap::new);
+    update(functions.entry((analysis, def_id)).or_default());
+}
+
+/// Print the profile of the crate and write it to `dir`; return the path of the JSON file.
+pub fn report(tcx: TyCtxt<'_>, dir: &Path) -> io::Result<PathBuf> {
+    let mut profile = PROFILE.lock().unwrap();
+    let analyses = std::mem::take(&mut profile.analyses);
+    let mut functions: BTreeMap<&'static str, Vec<FnReport>> = BTreeMap::new();
+    for ((analysis, def_id), function) in profile.functions.take().unwrap_or_default() {
+        functions.entry(analysis).or_default().push(FnReport {
+            function: tcx.def_path_str(def_id),
+            time: function.time.as_secs_f64(),
+            counters: function.counters,
+        });
+    }
+    for reports in functions.values_mut() {
+        reports.sort_by(|a, b| {
+            b.time
+                .total_cmp(&a.time)
+                .then_with(|| a.function.cmp(&b.function))
+        });
+    }
+
+    let mut table = vec![format!(
+        "{:<16}{:>12}{:>12}{:>14}",
+        "analysis", "time (s)", "total (s)", "peak (MiB)"
+    )];
+    for analysis in analyses.iter() {
+        let peak = analysis.peak_memory.map_or("-".to_owned(), |bytes| {
+            format!("{:.1}", bytes as f64 / (1 << 20) as f64)
+        });
+        table.push(format!(
+            "{:<16}{:>12.3}{:>12.3}{:>14}",
+            analysis.name, analysis.time, analysis.total_time, peak
+        ));
+    }
+    for (analysis, reports) in functions.iter() {
+        table.push(format!(
+            "The most expensive functions of {analysis} ({} analyzed):",
+            reports.len()
+        ));
+        for report in reports.iter().take(TOP_FUNCTIONS) {
+            let counters: Vec<String> = report
+                .counters
+                .iter()
+                .map(|(counter, count)| format!("{counter}={count}"))
+                .collect();
+            table.push(format!(
+                "{:>10.3}s  {}  {}",
+                report.time,
+                report.function,
+                counters.join(" ")
+            ));
+        }
+    }
+    rap_info!("Profile:\n{}", table.join("\n"));
+
+    let crate_name = tcx.crate_name(LOCAL_CRATE);
+    let crate_type = tcx.crate_types()[0];
+    let report = serde_json::json!({
+        "crate": crate_name.as_str(),
+        "crate_type": crate_type.to_string(),
+        "analyses": analyses,
+        "functions": functions,
+    });
+    fs::create_dir_all(dir)?;
+    let path = dir.join(format!("rapx_profile_{crate_name}_{crate_type}.json"));
+    fs::write(&path, serde_json::to_string_pretty(&report)?)?;
+    Ok(path)
+}
+
+/// The peak resident set size of the process, read from procfs on Linux.
+mod memory {
+    #[cfg(target_os = "linux")]
+    pub fn peak() -> Option<u64> {
+        let status = std::fs::read_to_string("/proc/self/status").ok()?;
+        let line = status.lines().find(|line| line.starts_with("VmHWM:"))?;
+        let kb: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+        Some(kb * 1024)
+    }
+
+    /// Reset the peak to the current resident set size.
+    #[cfg(target_os = "linux")]
+    pub fn reset_peak() {
+        _ = std::fs::write("/proc/self/clear_refs", "5");
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub fn peak() -> Option<u64> {
+        None
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub fn reset_peak() {}
+}
@@ -0,0 +1,155 @@
+//! The functions to analyze, for targeted audits of large crates.
+//!
+//! * `-include=<path glob>` and `-exclude=<path glob>` select the functions by the source files
+//!   they are defined in, e.g., `-include=src/parser/**` for a module; the `include` and `exclude`
+//!   keys of the configuration file apply the same way;
+//! * `-entry=<fn path>` selects the functions reachable from the entry in the call graph, e.g.,
+//!   `-entry=parser::parse`; the crate name may lead the path.
+//!
+//! The findings are only reported for the functions in the scope; closures and constants follow
+//! the function they are defined in. The core analyzers also analyze the callees of the functions
+//! in the scope, so that the interprocedural results within the scope are not weakened.
+
+use crate::{
+    analysis::context::AnalysisContext,
+    rap_info, rap_warn,
+    utils::{config::Glob, log::span_to_filename},
+};
+use rustc_data_structures::fx::FxHashSet;
+use rustc_hir::def_id::{DefId, LOCAL_CRATE};
+use rustc_middle::ty::TyCtxt;
+use std::{path::Path, sync::OnceLock};
+
+static SCOPE: OnceLock<Option<Scope>> = OnceLock::new();
+
+/// The filters given on the command line.
+#[derive(Debug, Clone, Default, Hash, PartialEq, Eq)]
+pub struct ScopeFilters {
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+    pub entries: Vec<String>,
+}
+
+impl ScopeFilters {
+    pub fn is_empty(&self) -> bool {
+        self.include.is_empty() && self.exclude.is_empty() && self.entries.is_empty()
+    }
+}
+
+struct Scope {
+    /// The functions whose findings are reported.
+    reported: FxHashSet<DefId>,
+    /// The functions analyzed by the core analyzers: the reported ones and their callees.
+    analyzed: FxHashSet<DefId>,
+}
+
+/// Resolve the scope of the local crate. It should be called once before the analyses start; all
+/// functions are in the scope otherwise.
+pub fn init(ctx: &AnalysisContext<'_>, filters: &ScopeFilters) {
+    SCOPE.get_or_init(|| (!filters.is_empty()).then(|| Scope::new(ctx, filters)));
+}
+
+/// Test if the findings of a function are reported.
+pub fn contains(tcx: TyCtxt<'_>, def_id: DefId) -> bool {
+    match SCOPE.get() {
+        Some(Some(scope)) => scope.reported.contains(&tcx.typeck_root_def_id(def_id)),
+        _ => true,
+    }
+}
+
+/// Test if a function is analyzed by the core analyzers.
+pub fn needs(tcx: TyCtxt<'_>, def_id: DefId) -> bool {
+    match SCOPE.get() {
+        Some(Some(scope)) => scope.analyzed.contains(&tcx.typeck_root_def_id(def_id)),
+        _ => true,
+    }
+}
+
+impl Scope {
+    fn new(ctx: &AnalysisContext<'_>, filters: &ScopeFilters) -> Self {
+        let tcx = ctx.tcx;
+        let functions: Vec<DefId> = tcx
+            .mir_keys(())
+            .iter()
+            .map(|local_def_id| local_def_id.to_def_id())
+            .filter(|def_id| tcx.typeck_root_def_id(*def_id) == *def_id)
+            .collect();
+        let callgraph = ctx.callgraph();
+
+        let mut reported: FxHashSet<DefId> = if filters.entries.is_empty() {
+            functions.iter().copied().collect()
+        } else {
+            let mut entries = Vec::new();
+            for entry in filters.entries.iter() {
+                let found: Vec<_> = functions
+                    .iter()
+                    .copied()
+                    .filter(|def_id| is_entry(tcx, *def_id, entry))
+                    .collect();
+                if found.is_empty() {
+                    rap_warn!("No function matches the entry `{}`.", entry);
+                }
+                entries.extend(found);
+            }
+            callgraph
+                .reachable_from(entries)
+                .into_iter()
+                .filter(|def_id| def_id.is_local())
+                .map(|def_id| tcx.typeck_root_def_id(def_id))
+                .collect()
+        };
+        let include: Vec<_> = filters
+            .include
+            .iter()
+            .map(|glob| absolute_glob(glob))
+            .collect();
+        let exclude: Vec<_> = filters
+            .exclude
+            .iter()
+            .map(|glob| absolute_glob(glob))
+            .collect();
+        reported.retain(|def_id| {
+            let file = source_file(tcx, *def_id);
+            (include.is_empty() || include.iter().any(|glob| glob.is_match(&file)))
+                && !exclude.iter().any(|glob| glob.is_match(&file))
+        });
+
+        let analyzed: FxHashSet<DefId> = callgraph
+            .reachable_from(reported.iter().copied())
+            .into_iter()
+            .filter(|def_id| def_id.is_local())
+            .map(|def_id| tcx.typeck_root_def_id(def_id))
+            .collect();
+        rap_info!(
+            "Analyze {} of {} functions in the scope, and {} callees out of it.",
+            reported.len(),
+            functions.len(),
+            analyzed.len() - reported.len()
+        );
+        Scope { reported, analyzed }
+    }
+}
+
+/// Test if a function is the entry given by its def path, with or without the crate name.
+fn is_entry(tcx: TyCtxt<'_>, def_id: DefId, entry: &str) -> bool {
+    let path = tcx.def_path_str(def_id);
+    let crate_name = tcx.crate_name(LOCAL_CRATE);
+    path == entry || format!("{crate_name}::{path}") == entry
+}
+
+/// `cargo rapx` passes absolute globs, while the relative ones given to the driver are relative to
+/// the current folder, as the source files are.
+fn absolute_glob(glob: &str) -> Glob {
+    match std::path::absolute(Path::new(glob)) {
+        Ok(path) => Glob::new(&path.display().to_string()),
+        Err(_) => Glob::new(glob),
+    }
+}
+
+/// The absolute path of the source file defining a function.
+fn source_file(tcx: TyCtxt<'_>, def_id: DefId) -> String {
+    let file = span_to_filename(tcx.def_span(def_id));
+    std::path::absolute(Path::new(&file))
+        .map(|path| path.display().to_string())
+        .unwrap_or(file)
+}
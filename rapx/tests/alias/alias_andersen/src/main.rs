@@ -0,0 +1,41 @@
+#![allow(dead_code)]
+
+pub struct Pair {
+    a: i32,
+    b: i32,
+}
+
+// the return value may be either argument
+pub fn select<'a>(a: &'a i32, b: &'a i32, first: bool) -> &'a i32 {
+    if first {
+        a
+    } else {
+        b
+    }
+}
+
+pub fn field(p: &Pair) -> &i32 {
+    &p.b
+}
+
+fn identity(x: &i32) -> &i32 {
+    x
+}
+
+// the fn pointer is bound to `identity` once it reaches the call
+pub fn apply(f: fn(&i32) -> &i32, x: &i32) -> &i32 {
+    f(x)
+}
+
+// the pointer returned by `Box::into_raw` is a new object
+pub fn fresh(x: &i32) -> *mut i32 {
+    Box::into_raw(Box::new(*x))
+}
+
+fn main() {
+    let pair = Pair { a: 1, b: 2 };
+    select(&pair.a, &pair.b, true);
+    field(&pair);
+    apply(identity, &pair.a);
+    fresh(&pair.a);
+}
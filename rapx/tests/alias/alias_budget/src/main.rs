@@ -0,0 +1,37 @@
+// The paths of `explode` double with each branch after choosing the returned reference, so a small
+// budget runs out before the path returning `b` is visited.
+fn explode<'a>(a: &'a mut i32, b: &'a mut i32, flags: &[bool]) -> &'a mut i32 {
+    let r = if flags[0] { a } else { b };
+    if flags[1] {
+        *r += 1;
+    }
+    if flags[2] {
+        *r += 2;
+    }
+    if flags[3] {
+        *r += 3;
+    }
+    if flags[4] {
+        *r += 4;
+    }
+    if flags[5] {
+        *r += 5;
+    }
+    if flags[6] {
+        *r += 6;
+    }
+    if flags[7] {
+        *r += 7;
+    }
+    if flags[8] {
+        *r += 8;
+    }
+    r
+}
+
+fn main() {
+    let mut a = 0;
+    let mut b = 0;
+    let flags = [true; 9];
+    *explode(&mut a, &mut b, &flags) += 1;
+}
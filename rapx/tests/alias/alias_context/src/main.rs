@@ -0,0 +1,36 @@
+#![allow(dead_code)]
+
+fn pick<'a>(a: &'a Vec<i32>, b: &'a Vec<i32>, first: bool) -> &'a Vec<i32> {
+    if first {
+        a
+    } else {
+        b
+    }
+}
+
+// the constant argument prunes the path of `pick` returning `b`
+pub fn first<'a>(a: &'a Vec<i32>, b: &'a Vec<i32>) -> &'a Vec<i32> {
+    pick(a, b, true)
+}
+
+fn apply<'a, F: Fn(&'a Vec<i32>) -> &'a Vec<i32>>(f: F, a: &'a Vec<i32>) -> &'a Vec<i32> {
+    f(a)
+}
+
+pub fn identity(a: &Vec<i32>) -> &Vec<i32> {
+    apply(|a| a, a)
+}
+
+// `apply` is analyzed with the closure of each caller rather than with both of them, so the
+// result is not the argument of `apply`
+pub fn captured<'a>(a: &'a Vec<i32>, c: &'a Vec<i32>) -> &'a Vec<i32> {
+    apply(move |_| c, a)
+}
+
+fn main() {
+    let a = vec![1];
+    let b = vec![2];
+    first(&a, &b);
+    identity(&a);
+    captured(&a, &b);
+}
@@ -0,0 +1,13 @@
+#![allow(dead_code)]
+#![allow(unused_variables)]
+
+//Expected alias analysis result: (0,2), which follows the summary of alias_dep_lib::pick
+fn foo<'a>(x: &'a mut Vec<i32>, y: &'a mut Vec<i32>) -> &'a mut Vec<i32> {
+    alias_dep_lib::pick(x, y)
+}
+
+fn main() {
+    let mut a = vec![1];
+    let mut b = vec![2];
+    let _result = foo(&mut a, &mut b);
+}
@@ -0,0 +1,8 @@
+#![allow(dead_code)]
+#![allow(unused_variables)]
+
+// The MIR of this function is not encoded in the metadata of the crate.
+pub fn pick<'a>(x: &'a mut Vec<i32>, y: &'a mut Vec<i32>) -> &'a mut Vec<i32> {
+    y.push(1);
+    y
+}
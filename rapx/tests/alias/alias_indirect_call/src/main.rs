@@ -0,0 +1,48 @@
+#![allow(dead_code)]
+
+pub struct Node {
+    val: Box<i32>,
+}
+
+fn first(node: &Node) -> &Box<i32> {
+    &node.val
+}
+
+// the callee is a fn pointer
+pub fn by_fn_ptr(node: &Node) -> &Box<i32> {
+    let f: fn(&Node) -> &Box<i32> = first;
+    f(node)
+}
+
+pub trait Getter {
+    fn get<'a>(&self, node: &'a Node) -> &'a Box<i32>;
+}
+
+pub struct ValGetter;
+
+impl Getter for ValGetter {
+    fn get<'a>(&self, node: &'a Node) -> &'a Box<i32> {
+        &node.val
+    }
+}
+
+// the callee is a method of a trait object
+pub fn by_dyn<'a>(getter: &dyn Getter, node: &'a Node) -> &'a Box<i32> {
+    getter.get(node)
+}
+
+fn apply<'a, F: Fn(&'a Node) -> &'a Box<i32>>(f: F, node: &'a Node) -> &'a Box<i32> {
+    f(node)
+}
+
+// the callee is a closure passed to a generic function
+pub fn by_closure(node: &Node) -> &Box<i32> {
+    apply(|n| &n.val, node)
+}
+
+fn main() {
+    let node = Node { val: Box::new(1) };
+    by_fn_ptr(&node);
+    by_dyn(&ValGetter, &node);
+    by_closure(&node);
+}
@@ -0,0 +1,35 @@
+pub fn add(p: *const Vec<i32>) -> *const Vec<i32> {
+    unsafe { p.add(1) }
+}
+
+pub fn byte_add(p: *const Vec<i32>) -> *const Vec<i32> {
+    unsafe { p.byte_add(8) }
+}
+
+pub fn wrapping_sub(p: *const Vec<i32>) -> *const Vec<i32> {
+    p.wrapping_sub(1)
+}
+
+pub fn round_trip(p: *const Vec<i32>) -> *const Vec<i32> {
+    let addr = p as usize;
+    (addr + 24) as *const Vec<i32>
+}
+
+pub fn transmuted(p: *const Vec<i32>) -> *const u8 {
+    unsafe { std::mem::transmute(p) }
+}
+
+pub fn forged(addr: usize) -> *const Vec<i32> {
+    addr as *const Vec<i32>
+}
+
+fn main() {
+    let v = vec![1, 2, 3];
+    let p = &v as *const Vec<i32>;
+    add(p);
+    byte_add(p);
+    wrapping_sub(p);
+    round_trip(p);
+    transmuted(p);
+    forged(0);
+}
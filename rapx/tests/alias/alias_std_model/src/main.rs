@@ -0,0 +1,62 @@
+#![allow(dead_code)]
+use std::collections::HashMap;
+use std::rc::Rc;
+
+pub fn vec_push_pop(p: *const i32) -> Option<*const i32> {
+    let mut v = Vec::new();
+    v.push(p);
+    v.pop()
+}
+
+pub fn vec_push_get(v: &mut Vec<*const i32>, p: *const i32) -> Option<&*const i32> {
+    v.push(p);
+    v.get(0)
+}
+
+pub fn vec_insert_remove(v: &mut Vec<*const i32>, p: *const i32) -> *const i32 {
+    v.insert(0, p);
+    v.remove(0)
+}
+
+pub fn vec_as_ptr(v: &Vec<i32>) -> *const i32 {
+    v.as_ptr()
+}
+
+pub fn map_insert_get(m: &mut HashMap<u32, *const i32>, p: *const i32) -> Option<&*const i32> {
+    m.insert(0, p);
+    m.get(&0)
+}
+
+pub fn map_remove(m: &mut HashMap<u32, *const i32>) -> Option<*const i32> {
+    m.remove(&0)
+}
+
+pub fn option_take(o: &mut Option<Box<i32>>) -> Option<Box<i32>> {
+    o.take()
+}
+
+pub fn option_replace(o: &mut Option<Box<i32>>, b: Box<i32>) -> Option<Box<i32>> {
+    o.replace(b)
+}
+
+pub fn box_into_raw(b: Box<i32>) -> *mut i32 {
+    Box::into_raw(b)
+}
+
+pub fn box_from_raw(p: *mut i32) -> Box<i32> {
+    unsafe { Box::from_raw(p) }
+}
+
+pub fn rc_into_raw(r: Rc<i32>) -> *const i32 {
+    Rc::into_raw(r)
+}
+
+pub fn rc_from_raw(p: *const i32) -> Rc<i32> {
+    unsafe { Rc::from_raw(p) }
+}
+
+pub fn rc_as_ptr(r: &Rc<i32>) -> *const i32 {
+    Rc::as_ptr(r)
+}
+
+fn main() {}
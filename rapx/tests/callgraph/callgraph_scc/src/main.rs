@@ -0,0 +1,27 @@
+fn is_even(n: u32) -> bool {
+    if n == 0 {
+        true
+    } else {
+        is_odd(n - 1)
+    }
+}
+
+fn is_odd(n: u32) -> bool {
+    if n == 0 {
+        false
+    } else {
+        is_even(n - 1)
+    }
+}
+
+fn parity(n: u32) -> &'static str {
+    if is_even(n) {
+        "even"
+    } else {
+        "odd"
+    }
+}
+
+fn main() {
+    println!("{}", parity(7));
+}
@@ -0,0 +1,16 @@
+// a double free, which is an error
+fn double_free() {
+    let mut s = String::from("a tmp string");
+    let ptr = s.as_mut_ptr();
+    let _v = unsafe { Vec::from_raw_parts(ptr, s.len(), s.len()) };
+}
+
+// a memory leak, which is a warning
+fn leak() {
+    let _ptr = Box::into_raw(Box::new(1));
+}
+
+fn main() {
+    double_free();
+    leak();
+}
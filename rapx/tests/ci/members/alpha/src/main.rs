@@ -0,0 +1,7 @@
+fn leak_in_alpha() {
+    let _ptr = Box::into_raw(Box::new(1));
+}
+
+fn main() {
+    leak_in_alpha();
+}
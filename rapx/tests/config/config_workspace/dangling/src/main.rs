@@ -0,0 +1,15 @@
+fn create_vec() -> *mut Vec<i32> {
+    let mut v = Vec::new();
+    v.push(1);
+    &mut v as *mut Vec<i32>
+}
+
+fn leak_in_dangling() {
+    let buf = Box::new("buffer");
+    let _ptr = Box::into_raw(buf);
+}
+
+fn main() {
+    let _p = create_vec();
+    leak_in_dangling();
+}
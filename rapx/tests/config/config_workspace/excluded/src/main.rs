@@ -0,0 +1,8 @@
+fn leak_in_excluded() {
+    let buf = Box::new("buffer");
+    let _ptr = Box::into_raw(buf);
+}
+
+fn main() {
+    leak_in_excluded();
+}
@@ -0,0 +1,22 @@
+extern "C" {
+    fn buf_new() -> *mut Box<i32>;
+    fn buf_free(ptr: *mut Box<i32>);
+}
+
+// the buffer allocated by buf_new is never freed
+fn leak() {
+    let _buf = unsafe { buf_new() };
+}
+
+// the buffer is handed over to buf_free
+fn give() {
+    let buf = Box::new(Box::new(1));
+    unsafe {
+        buf_free(Box::into_raw(buf));
+    }
+}
+
+fn main() {
+    leak();
+    give();
+}
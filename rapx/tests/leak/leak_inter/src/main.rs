@@ -0,0 +1,63 @@
+#![allow(dead_code)]
+
+struct Proxy<T> {
+    p: *mut T,
+}
+
+// the returned proxy takes over the buffer
+fn new_proxy() -> Proxy<&'static str> {
+    let buf = Box::new("buffer");
+    Proxy {
+        p: Box::into_raw(buf),
+    }
+}
+
+// the buffer held by the proxy is never freed
+fn leak_ctor() {
+    let _proxy = new_proxy();
+}
+
+fn new_raw() -> *mut i32 {
+    let buf = Box::new(1);
+    Box::into_raw(buf)
+}
+
+fn free_raw(ptr: *mut i32) {
+    unsafe {
+        drop(Box::from_raw(ptr));
+    }
+}
+
+// the buffer is freed by the callee
+fn give() {
+    let ptr = new_raw();
+    free_raw(ptr);
+}
+
+fn ignore(_ptr: *mut i32) {}
+
+// the callee forgets to free the buffer
+fn leak_arg() {
+    let ptr = new_raw();
+    ignore(ptr);
+}
+
+fn id(ptr: *mut i32) -> *mut i32 {
+    ptr
+}
+
+// the buffer comes back from the callee and is freed
+fn give_back() {
+    let buf = Box::new(1);
+    let ptr = id(Box::into_raw(buf));
+    unsafe {
+        drop(Box::from_raw(ptr));
+    }
+}
+
+fn main() {
+    leak_ctor();
+    give();
+    leak_arg();
+    give_back();
+}
@@ -0,0 +1,38 @@
+// every iteration leaks the buffer it allocates
+fn leak_in_loop(n: usize) {
+    for _ in 0..n {
+        let buf = Box::new("buffer");
+        let _ptr = Box::into_raw(buf);
+    }
+}
+
+// the leak is in the inner loop
+fn leak_in_nested_loop(n: usize) {
+    let mut i = 0;
+    while i < n {
+        let mut j = 0;
+        while j < n {
+            let buf = Box::new(j);
+            let _ptr = Box::into_raw(buf);
+            j += 1;
+        }
+        i += 1;
+    }
+}
+
+// the buffers are freed in every iteration
+fn free_in_loop(n: usize) {
+    for _ in 0..n {
+        let buf = Box::new("buffer");
+        let ptr = Box::into_raw(buf);
+        unsafe {
+            drop(Box::from_raw(ptr));
+        }
+    }
+}
+
+fn main() {
+    leak_in_loop(2);
+    leak_in_nested_loop(2);
+    free_in_loop(2);
+}
@@ -0,0 +1,26 @@
+#![allow(dead_code)]
+
+fn new_raw() -> *mut i32 {
+    Box::into_raw(Box::new(1))
+}
+
+// reported at the default level
+fn leak_warned() {
+    let _ptr = new_raw();
+}
+
+#[cfg_attr(rapx, allow(rapx::memory_leak))]
+fn leak_allowed() {
+    let _ptr = new_raw();
+}
+
+#[cfg_attr(rapx, deny(rapx::memory_leak))]
+fn leak_denied() {
+    let _ptr = new_raw();
+}
+
+fn main() {
+    leak_warned();
+    leak_allowed();
+    leak_denied();
+}
@@ -0,0 +1,13 @@
+fn escaping() -> *const i32 {
+    let buf = vec![1, 2, 3];
+    buf.as_ptr()
+}
+
+fn borrowed(buf: &Vec<i32>) -> *const i32 {
+    buf.as_ptr()
+}
+
+fn main() {
+    let buf = vec![4, 5, 6];
+    println!("{:?} {:?}", escaping(), borrowed(&buf));
+}
@@ -0,0 +1,15 @@
+extern "C" {
+    fn abs(x: i32) -> i32;
+}
+
+fn call_abs(x: i32) -> i32 {
+    unsafe { abs(x) }
+}
+
+fn safe_abs(x: i32) -> i32 {
+    x.abs()
+}
+
+fn main() {
+    println!("{} {}", call_abs(-1), safe_abs(-2));
+}
@@ -0,0 +1,6 @@
+fn main() {
+    let mut k: i128 = 1 << 70;
+    while k < 1 << 71 {
+        k += 1;
+    }
+}
@@ -0,0 +1,17 @@
+#![allow(dead_code)]
+
+mod parser;
+
+// called from src/parser.rs, so it is analyzed but not reported with -include=src/parser.rs
+pub fn new_raw() -> *mut i32 {
+    Box::into_raw(Box::new(1))
+}
+
+fn leak_main() {
+    let _ptr = new_raw();
+}
+
+fn main() {
+    parser::parse();
+    leak_main();
+}
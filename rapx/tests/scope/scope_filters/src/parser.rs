@@ -0,0 +1,13 @@
+use crate::new_raw;
+
+pub fn parse() {
+    leak_reachable();
+}
+
+fn leak_reachable() {
+    let _ptr = new_raw();
+}
+
+pub fn leak_unreachable() {
+    let _ptr = new_raw();
+}
@@ -0,0 +1,56 @@
+#![allow(dead_code)]
+
+fn new_raw() -> *mut i32 {
+    Box::into_raw(Box::new(1))
+}
+
+// not suppressed
+fn leak_reported() {
+    let _ptr = new_raw();
+}
+
+#[cfg_attr(rapx, rapx::allow(memory_leak))]
+fn leak_allowed() {
+    let _ptr = new_raw();
+}
+
+// the attribute allows another rule
+#[cfg_attr(rapx, rapx::allow(use_after_free))]
+fn leak_other_rule() {
+    let _ptr = new_raw();
+}
+
+// the attribute of the module applies to the functions in it
+#[cfg_attr(rapx, rapx::allow(memory_leak))]
+mod reviewed {
+    pub fn leak_in_module() {
+        let _ptr = super::new_raw();
+    }
+}
+
+struct Factory;
+
+// the attribute of the impl block applies to its methods
+#[cfg_attr(rapx, rapx::allow(dangling_pointer))]
+impl Factory {
+    fn dangling_allowed() -> *mut Vec<i32> {
+        let mut v = Vec::new();
+        v.push(1);
+        &mut v as *mut Vec<i32>
+    }
+}
+
+fn dangling_reported() -> *mut Vec<i32> {
+    let mut v = Vec::new();
+    v.push(1);
+    &mut v as *mut Vec<i32>
+}
+
+fn main() {
+    leak_reported();
+    leak_allowed();
+    leak_other_rule();
+    reviewed::leak_in_module();
+    let _p = Factory::dangling_allowed();
+    let _q = dangling_reported();
+}
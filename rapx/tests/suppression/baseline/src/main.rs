@@ -0,0 +1,20 @@
+#![allow(dead_code)]
+
+fn new_raw() -> *mut i32 {
+    Box::into_raw(Box::new(1))
+}
+
+// recorded in baseline.json
+fn leak_old() {
+    let _ptr = new_raw();
+}
+
+// missing from baseline.json
+fn leak_new() {
+    let _ptr = new_raw();
+}
+
+fn main() {
+    leak_old();
+    leak_new();
+}
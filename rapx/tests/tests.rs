@@ -1,3 +1,4 @@
+use std::env;
 use std::path::Path;
 use std::process::Command;
 
@@ -34,6 +35,48 @@ fn test_df_min() {
     );
 }
 
+#[test]
+fn test_df_min_sarif() {
+    let project_path = Path::new("./tests/uaf/df_min");
+    Command::new("cargo")
+        .args(["rapx", "-F", "-sarif=target/rapx.sarif"])
+        .current_dir(project_path)
+        .output()
+        .expect("Failed to execute cargo rapx");
+    let sarif = std::fs::read_to_string(project_path.join("target/rapx.sarif"))
+        .expect("Failed to read the SARIF log");
+    assert!(sarif.contains("\"version\": \"2.1.0\""));
+    assert!(sarif.contains("\"ruleId\": \"double_free\""));
+    assert!(sarif.contains("\"uri\": \"src/main.rs\""));
+}
+
+#[test]
+fn test_df_min_cache() {
+    // the second run reuses the summaries cached by the first one
+    for _ in 0..2 {
+        let output = running_tests_with_arg("uaf/df_min", "-F");
+        assert!(output.contains("Double free detected in function main"));
+    }
+    let cache = std::fs::read_dir("./tests/uaf/df_min/target/rapx/cache")
+        .expect("Missing the cache folder")
+        .filter_map(|entry| entry.ok())
+        .any(|entry| entry.path().join("alias.json").exists());
+    assert!(cache);
+}
+
+#[test]
+fn test_df_ffi_summaries() {
+    // free_box is opaque without the summary file
+    let project_path = Path::new("./tests/uaf/df_ffi");
+    let output = Command::new("cargo")
+        .args(["rapx", "-F", "-summaries=summaries.toml"])
+        .current_dir(project_path)
+        .output()
+        .expect("Failed to execute cargo rapx");
+    let output = String::from_utf8_lossy(&output.stderr);
+    assert!(output.contains("Double free detected in function main"));
+}
+
 #[test]
 fn test_dp_lengthy() {
     let output = running_tests_with_arg("uaf/dp_lengthy", "-F");
@@ -79,6 +122,36 @@ fn test_uaf_lifetime() {
     );
 }
 
+#[test]
+fn test_uaf_context() {
+    let output = running_tests_with_arg("uaf/uaf_context", "-F");
+    assert!(output.contains("Use after free detected in function \"main\""));
+    let project_path = Path::new("./tests/uaf/uaf_context");
+    let output = Command::new("cargo")
+        .args(["rapx", "-F", "-context-depth=1"])
+        .current_dir(project_path)
+        .output()
+        .expect("Failed to execute cargo rapx");
+    let output = String::from_utf8_lossy(&output.stderr);
+    assert!(!output.contains("Use after free detected"));
+}
+
+#[test]
+fn test_uaf_ptr_arith() {
+    let output = running_tests_with_arg("uaf/uaf_ptr_arith", "-F");
+    assert!(output.contains("Use after free detected in function \"by_offset\""));
+    assert!(output.contains("Use after free detected in function \"by_address\""));
+}
+
+#[test]
+fn test_uaf_std_model() {
+    let output = running_tests_with_arg("uaf/uaf_std_model", "-F");
+    assert!(output.contains("Use after free detected in function \"from_slice\""));
+    assert!(output.contains("Use after free detected in function \"from_map\""));
+    assert!(output.contains("Use after free detected in function \"from_map_get\""));
+    assert!(output.contains("Use after free detected in function \"from_box\""));
+}
+
 #[test]
 fn test_uaf_small() {
     let output = running_tests_with_arg("uaf/uaf_small", "-F");
@@ -122,6 +195,25 @@ fn test_alias_field() {
     );
 }
 
+#[test]
+fn test_alias_field_json() {
+    let project_path = Path::new("./tests/alias/alias_field");
+    Command::new("cargo")
+        .args(["rapx", "-alias", "-format=json", "-output=target/rapx-json"])
+        .current_dir(project_path)
+        .output()
+        .expect("Failed to execute cargo rapx");
+    let json =
+        std::fs::read_to_string(project_path.join("target/rapx-json/rapx_alias_field_bin.json"))
+            .expect("Failed to read the JSON results");
+    let json: serde_json::Value = serde_json::from_str(&json).expect("Invalid JSON results");
+    let aliases = json["alias"]["foo"]["aliases"]
+        .as_array()
+        .expect("Missing aliases of foo");
+    assert_eq!(aliases.len(), 2);
+    assert_eq!(json["alias"]["foo"]["arg_size"], 1);
+}
+
 #[test]
 fn test_alias_lib_no_caller() {
     let output = running_tests_with_arg("alias/alias_lib_no_caller", "-alias");
@@ -152,6 +244,179 @@ fn test_alias_indirect() {
     assert_eq!(output.contains("iter_prop\": (0,1.0)"), true);
 }
 
+#[test]
+fn test_alias_indirect_call() {
+    let output = running_tests_with_arg("alias/alias_indirect_call", "-alias");
+    assert!(output.contains("by_fn_ptr\": (0,1)"));
+    assert!(output.contains("by_dyn\": (0,2)"));
+    assert!(output.contains("by_closure\": (0,1)"));
+}
+
+#[test]
+fn test_alias_ptr_arith() {
+    let output = running_tests_with_arg("alias/alias_ptr_arith", "-alias");
+    assert!(output.contains("\"add\": (0,1)"));
+    assert!(output.contains("\"byte_add\": (0,1)"));
+    assert!(output.contains("\"wrapping_sub\": (0,1)"));
+    assert!(output.contains("\"round_trip\": (0,1)"));
+    assert!(output.contains("\"transmuted\": (0,1)"));
+    // nothing is exposed to the integer argument
+    assert!(output.contains("\"forged\": null"));
+}
+
+#[test]
+fn test_alias_std_model() {
+    let output = running_tests_with_arg("alias/alias_std_model", "-alias");
+    assert!(output.contains("\"vec_push_pop\": (0,1)"));
+    assert!(output.contains("\"vec_as_ptr\": (0,1)"));
+    assert!(output.contains("\"map_remove\": (0,1)"));
+    assert!(output.contains("\"option_take\": (0,1)"));
+    assert!(output.contains("\"box_into_raw\": (0,1)"));
+    assert!(output.contains("\"box_from_raw\": (0,1)"));
+    assert!(output.contains("\"rc_into_raw\": (0,1)"));
+    assert!(output.contains("\"rc_from_raw\": (0,1)"));
+    assert!(output.contains("\"rc_as_ptr\": (0,1)"));
+}
+
+#[test]
+fn test_alias_andersen() {
+    let output = running_tests_with_arg("alias/alias_andersen", "-alias=andersen");
+    assert!(output.contains("select\": (0,1), (0,2)"));
+    assert!(output.contains("field\": (0,1.1)"));
+    assert!(output.contains("apply\": (0,2)"));
+    assert!(output.contains("fresh\": null"));
+}
+
+#[test]
+fn test_alias_andersen_indirect_call() {
+    let output = running_tests_with_arg("alias/alias_indirect_call", "-alias=andersen");
+    assert!(output.contains("by_fn_ptr\": (0,1.0)"));
+    assert!(output.contains("by_dyn\": (0,2.0)"));
+    assert!(output.contains("by_closure\": (0,1.0)"));
+}
+
+#[test]
+fn test_alias_context() {
+    let line = |output: &str, name: &str| {
+        output
+            .lines()
+            .find(|line| line.contains(&format!("Alias of \"{name}\"")))
+            .unwrap_or_default()
+            .to_owned()
+    };
+    // the summaries of `pick` and `apply` are shared by their callers
+    let output = running_tests_with_arg("alias/alias_context", "-alias");
+    assert!(line(&output, "first").contains("(0,2)"));
+    assert!(line(&output, "captured").contains("(0,1)"));
+    let project_path = Path::new("./tests/alias/alias_context");
+    let output = Command::new("cargo")
+        .args(["rapx", "-alias", "-context-depth=1"])
+        .current_dir(project_path)
+        .output()
+        .expect("Failed to execute cargo rapx");
+    let output = String::from_utf8_lossy(&output.stderr);
+    assert!(output.contains("first\": (0,1)\n"));
+    assert!(output.contains("captured\": (0,2)\n"));
+}
+
+#[test]
+fn test_alias_budget() {
+    let explode = |output: &str| {
+        output
+            .lines()
+            .find(|line| line.contains("Alias of \"explode\""))
+            .unwrap_or_default()
+            .to_owned()
+    };
+    // the path returning `b` is only covered by the path-insensitive fallback
+    let output = running_tests_with_arg("alias/alias_budget", "-alias");
+    assert!(output.contains("[alias] explode: path visits"));
+    assert!(explode(&output).contains("(0,1)") && explode(&output).contains("(0,2)"));
+    let project_path = Path::new("./tests/alias/alias_budget");
+    let output = Command::new("cargo")
+        .args(["rapx", "-alias", "-budget-visits=100000"])
+        .current_dir(project_path)
+        .output()
+        .expect("Failed to execute cargo rapx");
+    let output = String::from_utf8_lossy(&output.stderr);
+    assert!(explode(&output).contains("(0,1)") && explode(&output).contains("(0,2)"));
+    assert!(!explode(&output).contains("(1,2)"));
+    assert!(!output.contains("ran out of budget"));
+}
+
+#[test]
+fn test_uaf_budget() {
+    // the path dropping `data` is only covered by the path-insensitive fallback
+    let project_path = Path::new("./tests/uaf/uaf_budget");
+    for budget in ["-budget-visits=10", "-budget-visits=100000"] {
+        let output = Command::new("cargo")
+            .args(["rapx", "-F", budget])
+            .current_dir(project_path)
+            .output()
+            .expect("Failed to execute cargo rapx");
+        let output = String::from_utf8_lossy(&output.stderr);
+        assert!(output.contains("Use after free detected in function \"explode\""));
+        assert!(!output.contains("Double free detected"));
+        assert_eq!(
+            output.contains("[safedrop] explode: path visits"),
+            budget == "-budget-visits=10"
+        );
+    }
+}
+
+#[test]
+fn test_callgraph_indirect_call() {
+    let output = running_tests_with_arg("alias/alias_indirect_call", "-callgraph");
+    assert!(output.contains("by_fn_ptr calls:\n    -> first"));
+    assert!(output.contains("-> <ValGetter as Getter>::get"));
+    assert!(output.contains("-> by_closure::{closure#0}"));
+}
+
+#[test]
+fn test_alias_dep() {
+    let output = running_tests_with_arg("alias/alias_dep", "-alias");
+    assert!(output.contains("foo\": (0,2)"));
+}
+
+#[test]
+fn test_callgraph_scc() {
+    let output = running_tests_with_arg("callgraph/callgraph_scc", "-callgraph");
+    assert!(output.contains("Recursion groups:\n  {is_even, is_odd}"));
+}
+
+#[test]
+fn test_callgraph_dot_json() {
+    let project_path = Path::new("./tests/callgraph/recursion");
+    Command::new("cargo")
+        .args([
+            "rapx",
+            "-callgraph=dot",
+            "-format=json",
+            "-output=target/rapx-callgraph",
+        ])
+        .current_dir(project_path)
+        .output()
+        .expect("Failed to execute cargo rapx");
+    let output = project_path.join("target/rapx-callgraph");
+    let dot = std::fs::read_to_string(output.join("callgraph_recursion_bin.dot"))
+        .expect("Failed to read the DOT file");
+    assert!(dot.contains("\"factorial\" -> \"factorial\" [label=\"src/main.rs:5:13\"];"));
+    let json = std::fs::read_to_string(output.join("rapx_recursion_bin.json"))
+        .expect("Failed to read the JSON results");
+    let json: serde_json::Value = serde_json::from_str(&json).expect("Invalid JSON results");
+    // the calls expanded from println! are located at the macro call
+    assert_eq!(
+        json["callgraph"]["calls"]["main"][0]["span"],
+        "src/main.rs:11:5"
+    );
+    assert_eq!(json["callgraph"]["recursion_groups"][0][0], "factorial");
+    let order = json["callgraph"]["bottom_up_order"]
+        .as_array()
+        .expect("Missing the bottom-up order");
+    assert_eq!(order.first().unwrap(), "factorial");
+    assert_eq!(order.last().unwrap(), "main");
+}
+
 #[test]
 fn test_leak_ctor() {
     let output = running_tests_with_arg("leak/leak_ctor", "-M");
@@ -179,6 +444,324 @@ fn test_leak_proxy() {
     );
 }
 
+#[test]
+fn test_leak_loop() {
+    let output = running_tests_with_arg("leak/leak_loop", "-M");
+    assert!(output.contains("Memory Leak detected in function leak_in_loop"));
+    assert!(output.contains("Memory Leak detected in function leak_in_nested_loop"));
+    assert!(!output.contains("Memory Leak detected in function free_in_loop"));
+}
+
+#[test]
+fn test_leak_inter() {
+    let output = running_tests_with_arg("leak/leak_inter", "-M");
+    assert!(output.contains("Memory Leak detected in function leak_ctor"));
+    assert!(output.contains("Memory Leak detected in function leak_arg"));
+    assert!(!output.contains("Memory Leak detected in function give"));
+}
+
+#[test]
+fn test_leak_ffi_summaries() {
+    let output = running_tests_with_arg("leak/leak_ffi", "-M");
+    assert!(!output.contains("Memory Leak detected in function leak"));
+    let project_path = Path::new("./tests/leak/leak_ffi");
+    let output = Command::new("cargo")
+        .args(["rapx", "-M", "-summaries=summaries.json"])
+        .current_dir(project_path)
+        .output()
+        .expect("Failed to execute cargo rapx");
+    let output = String::from_utf8_lossy(&output.stderr);
+    assert!(output.contains("Memory Leak detected in function leak"));
+    assert!(!output.contains("Memory Leak detected in function give"));
+}
+
+#[test]
+fn test_config_workspace() {
+    // the analyses are given by rapx.toml and [package.metadata.rapx]
+    let project_path = Path::new("./tests/config/config_workspace");
+    let output = Command::new("cargo")
+        .arg("rapx")
+        .current_dir(project_path)
+        .output()
+        .expect("Failed to execute cargo rapx");
+    let output = String::from_utf8_lossy(&output.stderr);
+    assert!(output.contains("Memory Leak detected in function leak_in_leaky"));
+    assert!(!output.contains("leak_in_excluded"));
+    assert!(output.contains("Dangling pointer detected in function \"create_vec\""));
+    assert!(!output.contains("Memory Leak detected in function leak_in_dangling"));
+}
+
+#[test]
+fn test_allow_attr() {
+    let project_path = Path::new("./tests/suppression/allow_attr");
+    let output = Command::new("cargo")
+        .args(["rapx", "-F", "-M"])
+        .current_dir(project_path)
+        .output()
+        .expect("Failed to execute cargo rapx");
+    let output = String::from_utf8_lossy(&output.stderr);
+    assert!(output.contains("Memory Leak detected in function leak_reported"));
+    assert!(output.contains("Memory Leak detected in function leak_other_rule"));
+    assert!(!output.contains("Memory Leak detected in function leak_allowed"));
+    assert!(!output.contains("Memory Leak detected in function leak_in_module"));
+    assert!(output.contains("Dangling pointer detected in function \"dangling_reported\""));
+    assert!(!output.contains("Dangling pointer detected in function \"dangling_allowed\""));
+}
+
+#[test]
+fn test_baseline() {
+    // baseline.json records the leak in leak_old only
+    let project_path = Path::new("./tests/suppression/baseline");
+    let output = Command::new("cargo")
+        .args(["rapx", "-M", "-baseline=baseline.json"])
+        .current_dir(project_path)
+        .output()
+        .expect("Failed to execute cargo rapx");
+    let output = String::from_utf8_lossy(&output.stderr);
+    assert!(output.contains("Memory Leak detected in function leak_new"));
+    assert!(!output.contains("Memory Leak detected in function leak_old"));
+    assert!(output.contains("Skip 1 findings recorded in the baseline"));
+
+    // a missing baseline is recorded
+    let recorded = std::env::temp_dir().join("rapx_test_baseline.json");
+    let _ = std::fs::remove_file(&recorded);
+    let output = Command::new("cargo")
+        .args(["rapx", "-M"])
+        .arg(format!("-baseline={}", recorded.display()))
+        .current_dir(project_path)
+        .output()
+        .expect("Failed to execute cargo rapx");
+    let output = String::from_utf8_lossy(&output.stderr);
+    assert!(output.contains("Record 2 findings in the baseline"));
+    let baseline = std::fs::read_to_string(&recorded).expect("The baseline is not recorded");
+    assert!(baseline.contains("\"function\": \"leak_old\""));
+    assert!(baseline.contains("\"function\": \"leak_new\""));
+}
+
+#[test]
+fn test_lint_levels() {
+    let output = running_tests_with_arg("lint/lint_levels", "-M");
+    assert!(output.contains("warning: Memory Leak detected in function `leak_warned`."));
+    assert!(output.contains("`#[warn(rapx::memory_leak)]` on by default"));
+    assert!(output.contains("error: Memory Leak detected in function `leak_denied`."));
+    assert!(!output.contains("leak_allowed"));
+}
+
+#[test]
+fn test_scope_filters() {
+    let project_path = Path::new("./tests/scope/scope_filters");
+    let run = |filter: &str| {
+        let output = Command::new("cargo")
+            .args(["rapx", "-M", filter])
+            .current_dir(project_path)
+            .output()
+            .expect("Failed to execute cargo rapx");
+        String::from_utf8_lossy(&output.stderr).into_owned()
+    };
+    let reported = |output: &str, function: &str| {
+        output.contains(&format!("Memory Leak detected in function `{function}`."))
+    };
+
+    let output = run("-include=src/parser.rs");
+    assert!(reported(&output, "parser::leak_reachable"));
+    assert!(reported(&output, "parser::leak_unreachable"));
+    assert!(!reported(&output, "leak_main"));
+
+    let output = run("-exclude=src/parser.rs");
+    assert!(!reported(&output, "parser::leak_reachable"));
+    assert!(reported(&output, "leak_main"));
+
+    let output = run("-entry=scope_filters::parser::parse");
+    assert!(reported(&output, "parser::leak_reachable"));
+    assert!(!reported(&output, "parser::leak_unreachable"));
+    assert!(!reported(&output, "leak_main"));
+
+    let output = run("-entry=parser::missing");
+    assert!(output.contains("No function matches the entry `parser::missing`."));
+}
+
+#[test]
+fn test_profile() {
+    let project_path = Path::new("./tests/scope/scope_filters");
+    let output = Command::new("cargo")
+        .args([
+            "rapx",
+            "-F",
+            "-M",
+            "-profile",
+            "-output=target/rapx-profile",
+        ])
+        .current_dir(project_path)
+        .output()
+        .expect("Failed to execute cargo rapx");
+    let output = String::from_utf8_lossy(&output.stderr);
+    assert!(output.contains("Profile:"));
+    let json = std::fs::read_to_string(
+        project_path.join("target/rapx-profile/rapx_profile_scope_filters_bin.json"),
+    )
+    .expect("Failed to read the profile");
+    let json: serde_json::Value = serde_json::from_str(&json).expect("Invalid profile");
+    let analyses: Vec<&str> = json["analyses"]
+        .as_array()
+        .expect("Missing analyses")
+        .iter()
+        .filter_map(|analysis| analysis["name"].as_str())
+        .collect();
+    for name in ["alias", "ownedheap", "safedrop", "rcanary"] {
+        assert!(analyses.contains(&name), "missing {name} in {analyses:?}");
+    }
+    let leak_main = json["functions"]["rcanary"]
+        .as_array()
+        .expect("Missing functions of rcanary")
+        .iter()
+        .find(|function| function["function"] == "leak_main")
+        .expect("Missing leak_main");
+    assert!(leak_main["counters"]["solver_calls"].as_u64() > Some(0));
+    let safedrop = json["functions"]["safedrop"]
+        .as_array()
+        .expect("Missing functions of safedrop");
+    assert!(safedrop
+        .iter()
+        .all(|function| function["counters"]["visits"].as_u64() > Some(0)));
+}
+
+#[test]
+fn test_plugin_driver() {
+    // cargo builds the examples along with the tests, into target/<profile>/examples
+    let driver = env::current_exe()
+        .unwrap()
+        .parent()
+        .and_then(Path::parent)
+        .unwrap()
+        .join("examples/plugin_driver");
+    let output = Command::new("cargo")
+        .args(["rapx", "-ffi"])
+        .env("RAP_DRIVER", &driver)
+        .current_dir("./tests/plugin/ffi_calls")
+        .output()
+        .expect("Failed to execute cargo rapx");
+    let output = String::from_utf8_lossy(&output.stderr);
+    assert!(output.contains("Foreign function `abs` called in `call_abs`"));
+    assert!(!output.contains("safe_abs"));
+}
+
+#[test]
+fn test_plugin_points_to() {
+    let driver = env::current_exe()
+        .unwrap()
+        .parent()
+        .and_then(Path::parent)
+        .unwrap()
+        .join("examples/plugin_driver");
+    let output = Command::new("cargo")
+        .args(["rapx", "-escaping-ptr"])
+        .env("RAP_DRIVER", &driver)
+        .current_dir("./tests/plugin/escaping_ptr")
+        .output()
+        .expect("Failed to execute cargo rapx");
+    let output = String::from_utf8_lossy(&output.stderr);
+    assert!(output.contains("The raw pointer returned by `escaping` may point to the local `buf`"));
+    assert!(!output.contains("returned by `borrowed`"));
+}
+
+#[test]
+fn test_fail_on() {
+    let project_path = Path::new("./tests/ci/fail_on");
+    let run = |fail_on: &str| {
+        let output = Command::new("cargo")
+            .args(["rapx", "-F", "-M", fail_on])
+            .current_dir(project_path)
+            .output()
+            .expect("Failed to execute cargo rapx");
+        let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+        (output.status.code(), stderr)
+    };
+
+    let (code, output) = run("-fail-on=error");
+    assert_eq!(code, Some(2));
+    assert!(
+        output.contains("Summary: 2 findings in 1 analyzed crates (1 error, 1 warning, 0 note)")
+    );
+    assert!(output.contains("[double_free] Double free detected."));
+    assert!(output.contains("1 findings fail the run with -fail-on"));
+
+    let (code, output) = run("-fail-on=dangling_pointer,use_after_free");
+    assert_eq!(code, Some(0));
+    assert!(!output.contains("fail the run"));
+
+    let (code, _) = run("-fail-on=bogus");
+    assert_eq!(code, Some(1));
+}
+
+#[test]
+fn test_incremental() {
+    let project_path = Path::new("./tests/ci/fresh_crate");
+    let run = |args: &[&str], clean: bool| {
+        let output = Command::new("cargo")
+            .arg("rapx")
+            .args(args)
+            .env("RAP_CLEAN", clean.to_string())
+            .current_dir(project_path)
+            .output()
+            .expect("Failed to execute cargo rapx");
+        String::from_utf8_lossy(&output.stderr).into_owned()
+    };
+
+    let output = run(&["-M"], true);
+    assert!(output.contains("Checking fresh_crate"));
+    assert!(output.contains("Memory Leak detected in function leak"));
+
+    // the crate is fresh, but its findings are still counted
+    let output = run(&["-M"], false);
+    assert!(!output.contains("Checking fresh_crate"));
+    assert!(output.contains("Summary: 1 findings in 1 analyzed crates"));
+
+    // the options of rapx have changed
+    let output = run(&["-M", "-F"], false);
+    assert!(output.contains("Checking fresh_crate"));
+
+    // the build of the user is left alone
+    assert!(!project_path.join("target/debug").exists());
+    assert!(project_path.join("target/rapx/build").exists());
+}
+
+#[test]
+fn test_parallel_members() {
+    let project_path = Path::new("./tests/ci/members");
+    let run = |timeout: &str| {
+        let output = Command::new("cargo")
+            .args(["rapx", "-M", "-jobs=2", timeout])
+            .env("RAP_RECURSIVE", "shallow")
+            .env("RAP_CLEAN", "true")
+            .current_dir(project_path)
+            .output()
+            .expect("Failed to execute cargo rapx");
+        let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+        (output.status.code(), stderr)
+    };
+
+    let (code, output) = run("-timeout=600");
+    assert_eq!(code, Some(0));
+    assert!(output.contains("Check 2 package folders with 2 jobs."));
+    // the output of each member is printed as a whole
+    for member in ["alpha", "beta"] {
+        let header = output
+            .find(&format!("/members/{member}\n"))
+            .expect("The output of a member is missing");
+        let leak = output
+            .find(&format!(
+                "Memory Leak detected in function leak_in_{member}"
+            ))
+            .expect("The leak of a member is missing");
+        assert!(header < leak);
+    }
+    assert!(output.contains("Summary: 2 findings in 2 analyzed crates"));
+
+    let (code, output) = run("-timeout=0");
+    assert_eq!(code, Some(1));
+    assert!(output.contains("Process killed due to timeout"));
+}
+
 #[test]
 fn test_heap_cell() {
     let output = running_tests_with_arg("ownedheap/heap_cell", "-ownedheap");
@@ -301,3 +884,30 @@ fn test_interprocedual_range_analysis() {
         );
     }
 }
+
+#[test]
+fn test_range_wide_json() {
+    let project_path = Path::new("./tests/range/range_wide");
+    Command::new("cargo")
+        .args(["rapx", "-range", "-format=json", "-output=target/rapx-json"])
+        .current_dir(project_path)
+        .output()
+        .expect("Failed to execute cargo rapx");
+    let json =
+        std::fs::read_to_string(project_path.join("target/rapx-json/rapx_range_wide_bin.json"))
+            .expect("Failed to read the JSON results");
+    let json: serde_json::Value = serde_json::from_str(&json).expect("Invalid JSON results");
+    let ranges = json["range"]["main"]
+        .as_object()
+        .expect("Missing ranges of main");
+    // the i128 bounds out of i64 are written as decimal strings
+    let wide: Vec<i128> = ranges
+        .values()
+        .flat_map(|range| [&range["lower"], &range["upper"]])
+        .filter_map(|bound| bound.as_str())
+        .filter(|bound| *bound != "Min" && *bound != "Max")
+        .map(|bound| bound.parse().expect("Invalid bound"))
+        .collect();
+    assert!(!wide.is_empty());
+    assert!(wide.iter().all(|bound| i64::try_from(*bound).is_err()));
+}
@@ -0,0 +1,12 @@
+extern "C" {
+    fn free_box(ptr: *mut Box<i32>);
+}
+
+fn main() {
+    let mut x = Box::new(0);
+    let ptr = &mut x as *mut Box<i32>;
+    unsafe {
+        // x is freed here and dropped again at the end of main
+        free_box(ptr);
+    }
+}
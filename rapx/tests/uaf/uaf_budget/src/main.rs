@@ -0,0 +1,66 @@
+/*
+ * This is a buggy case: use-after-free on a path visited after the budget runs out
+ */
+struct Data {
+    value: Box<i32>,
+}
+
+impl Data {
+    fn new(value: i32) -> Data {
+        Data {
+            value: Box::new(value),
+        }
+    }
+
+    fn print_value(&self) {
+        println!("Value: {}", self.value);
+    }
+}
+
+// The paths double with each branch after the first one, so a small budget runs out before the
+// path pointing to the dropped `data` is visited.
+fn explode(flags: [bool; 10], counter: &mut i32) {
+    let data_ptr: *const Data;
+    let kept = Data::new(0);
+    if flags[0] {
+        let data = Data::new(42);
+        data_ptr = &data as *const Data;
+    } else {
+        data_ptr = &kept as *const Data;
+    }
+    if flags[1] {
+        *counter += 1;
+    }
+    if flags[2] {
+        *counter += 2;
+    }
+    if flags[3] {
+        *counter += 3;
+    }
+    if flags[4] {
+        *counter += 4;
+    }
+    if flags[5] {
+        *counter += 5;
+    }
+    if flags[6] {
+        *counter += 6;
+    }
+    if flags[7] {
+        *counter += 7;
+    }
+    if flags[8] {
+        *counter += 8;
+    }
+    if flags[9] {
+        *counter += 9;
+    }
+    unsafe {
+        (*data_ptr).print_value();
+    }
+}
+
+fn main() {
+    let mut counter = 0;
+    explode([true; 10], &mut counter);
+}
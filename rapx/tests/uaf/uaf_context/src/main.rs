@@ -0,0 +1,20 @@
+/*
+ * This is a safe case: `p` only points to `v1` with the constant passed to `pick`.
+ */
+fn pick(a: *const Vec<i32>, b: *const Vec<i32>, first: bool) -> *const Vec<i32> {
+    if first {
+        a
+    } else {
+        b
+    }
+}
+
+fn main() {
+    let v1 = vec![1];
+    let v2 = vec![2];
+    let p = pick(&v1, &v2, true);
+    drop(v2);
+    unsafe {
+        println!("{}", (*p).len());
+    }
+}
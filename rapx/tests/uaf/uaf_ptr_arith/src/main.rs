@@ -0,0 +1,28 @@
+/*
+ * `q` is derived from the pointer to `v` by arithmetic in `by_offset`, and by a round trip through
+ * an exposed address in `by_address`.
+ */
+fn by_offset() {
+    let v = vec![1, 2, 3];
+    let p = &v as *const Vec<i32>;
+    let q = p.wrapping_add(1).wrapping_sub(1);
+    drop(v);
+    unsafe {
+        (*q).len();
+    }
+}
+
+fn by_address() {
+    let v = vec![1, 2, 3];
+    let addr = &v as *const Vec<i32> as usize;
+    let q = addr as *const Vec<i32>;
+    drop(v);
+    unsafe {
+        (*q).len();
+    }
+}
+
+fn main() {
+    by_offset();
+    by_address();
+}
@@ -0,0 +1,50 @@
+use std::collections::HashMap;
+
+fn from_slice() {
+    let v = vec![1];
+    let mut ptrs = Vec::new();
+    ptrs.push(&v as *const Vec<i32>);
+    drop(v);
+    let p = *ptrs.get(0).unwrap();
+    unsafe {
+        (*p).len();
+    }
+}
+
+fn from_map() {
+    let v = vec![1];
+    let mut ptrs = HashMap::new();
+    ptrs.insert(1, &v as *const Vec<i32>);
+    drop(v);
+    let p = ptrs.remove(&1).unwrap();
+    unsafe {
+        (*p).len();
+    }
+}
+
+fn from_map_get() {
+    let v = vec![1];
+    let mut ptrs = HashMap::new();
+    ptrs.insert(1, &v as *const Vec<i32>);
+    drop(v);
+    let p = *ptrs.get(&1).unwrap();
+    unsafe {
+        (*p).len();
+    }
+}
+
+fn from_box() {
+    let b = Box::new(vec![1]);
+    let p = Box::into_raw(b);
+    unsafe {
+        drop(Box::from_raw(p));
+        (*p).len();
+    }
+}
+
+fn main() {
+    from_slice();
+    from_map();
+    from_map_get();
+    from_box();
+}